@@ -1,5 +1,20 @@
 //STD
+use std::env;
 use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// - internal
+use crate::constants::*;
+
+// - external
+use nix::unistd::Uid;
+
+// the unmount mechanisms, which were found by the first probe (see unmount_mechanisms).
+static UNMOUNT_MECHANISMS: Mutex<Option<Vec<UnmountMechanism>>> = Mutex::new(None);
 
 /// Parse a single key-value pair
 pub(crate) fn parse_key_val<T, U>(s: &str) -> Result<(T, U), Box<dyn Error + Send + Sync + 'static>>
@@ -13,4 +28,194 @@ where
         .find(':')
         .ok_or_else(|| format!("invalid KEY:value -> no `:` found in `{s}`"))?;
     Ok((s[..pos].parse()?, s[pos + 1..].parse()?))
-}
\ No newline at end of file
+}
+
+/// Parse a container definition (name=glob pattern). The name is used as directory name, so it must not contain a slash.
+pub(crate) fn parse_container(s: &str) -> Result<(String, String), Box<dyn Error + Send + Sync + 'static>> {
+    let (name, pattern) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid NAME=pattern -> no `=` found in `{s}`"))?;
+    if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+        return Err(format!("invalid container name `{name}`").into());
+    }
+    Ok((name.to_string(), pattern.to_string()))
+}
+
+/// Parse the prefix of the object directories. The prefix is a part of a filename, so it must not be empty or contain a slash.
+pub(crate) fn parse_object_dir_prefix(s: &str) -> Result<String, Box<dyn Error + Send + Sync + 'static>> {
+    if s.is_empty() || s.contains(['/', '\0']) {
+        return Err(format!("invalid object directory prefix `{s}`").into());
+    }
+    Ok(s.to_string())
+}
+
+/// Parse a size with an optional binary unit suffix (K, M, G, T), e.g. 2G = 2147483648 bytes.
+pub(crate) fn parse_size(s: &str) -> Result<u64, Box<dyn Error + Send + Sync + 'static>> {
+    let s = s.trim();
+    let (number, multiplier) = match s.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&s[..s.len() - 1], 1u64 << 10),
+        Some('M') => (&s[..s.len() - 1], 1u64 << 20),
+        Some('G') => (&s[..s.len() - 1], 1u64 << 30),
+        Some('T') => (&s[..s.len() - 1], 1u64 << 40),
+        _ => (s, 1),
+    };
+    let size = number.parse::<u64>()?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("the size `{s}` is too large"))?;
+    if size == 0 {
+        return Err("the size must be greater than 0".into());
+    }
+    Ok(size)
+}
+
+/// Parse a size (see parse_size), which has to fit into 32 bits (e.g. for the FUSE kernel parameters).
+pub(crate) fn parse_size_u32(s: &str) -> Result<u32, Box<dyn Error + Send + Sync + 'static>> {
+    let size = parse_size(s)?;
+    u32::try_from(size).map_err(|_| format!("the size `{s}` is too large").into())
+}
+
+/// Parse a duration with an optional unit suffix (s, m, h, d), e.g. 30m = 1800 seconds. Without a suffix, the value is in seconds.
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, Box<dyn Error + Send + Sync + 'static>> {
+    let s = s.trim();
+    let (number, multiplier) = match s.chars().last().map(|c| c.to_ascii_lowercase()) {
+        Some('s') => (&s[..s.len() - 1], 1u64),
+        Some('m') => (&s[..s.len() - 1], 60),
+        Some('h') => (&s[..s.len() - 1], 60 * 60),
+        Some('d') => (&s[..s.len() - 1], 24 * 60 * 60),
+        _ => (s, 1),
+    };
+    let seconds = number.parse::<u64>()?
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("the duration `{s}` is too large"))?;
+    if seconds == 0 {
+        return Err("the duration must be greater than 0".into());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// A way to unmount a FUSE filesystem.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum UnmountMechanism {
+    /// The umount2 syscall (requires CAP_SYS_ADMIN, i.e. root).
+    Umount2,
+    /// The given (setuid) fusermount binary, which is used by unprivileged users.
+    Fusermount(PathBuf),
+}
+
+impl fmt::Display for UnmountMechanism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnmountMechanism::Umount2 => write!(f, "umount2"),
+            UnmountMechanism::Fusermount(binary) => write!(f, "{}", binary.display()),
+        }
+    }
+}
+
+/// The results of probing the environment for the unmount mechanisms.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UnmountProbe {
+    pub is_root: bool,
+    pub fusermount: Option<PathBuf>,
+}
+
+impl UnmountProbe {
+    /// Probes the current environment.
+    pub(crate) fn current() -> Self {
+        Self {
+            is_root: Uid::effective().is_root(),
+            fusermount: find_fusermount(),
+        }
+    }
+}
+
+/// Returns the usable unmount mechanisms of the given probe, in the order they should be tried. Root uses the umount2 syscall
+/// first and falls back to fusermount (e.g. in a user namespace without CAP_SYS_ADMIN). An empty list means, that the
+/// filesystem can not be unmounted by this process.
+pub(crate) fn select_unmount_mechanisms(probe: &UnmountProbe) -> Vec<UnmountMechanism> {
+    let mut mechanisms = Vec::new();
+    if probe.is_root {
+        mechanisms.push(UnmountMechanism::Umount2);
+    }
+    if let Some(binary) = &probe.fusermount {
+        mechanisms.push(UnmountMechanism::Fusermount(binary.clone()));
+    }
+    mechanisms
+}
+
+/// Returns the usable unmount mechanisms of the current environment. The environment is only probed once.
+pub(crate) fn unmount_mechanisms() -> Vec<UnmountMechanism> {
+    let mut cached = match UNMOUNT_MECHANISMS.lock() {
+        Ok(cached) => cached,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cached.get_or_insert_with(|| select_unmount_mechanisms(&UnmountProbe::current())).clone()
+}
+
+/// Returns the path of the first fusermount binary (fusermount3 is preferred), which was found on the PATH.
+pub(crate) fn find_fusermount() -> Option<PathBuf> {
+    find_fusermount_in(&env::var_os("PATH")?)
+}
+
+// returns the path of the first fusermount binary in the given search path (see find_fusermount).
+fn find_fusermount_in(path: &OsStr) -> Option<PathBuf> {
+    FUSERMOUNT_BINARIES.iter()
+        .flat_map(|binary| env::split_paths(path).map(move |dir| dir.join(binary)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn probe(is_root: bool, fusermount: Option<&str>) -> UnmountProbe {
+        UnmountProbe { is_root, fusermount: fusermount.map(PathBuf::from) }
+    }
+
+    #[test]
+    fn root_uses_umount2() {
+        assert_eq!(select_unmount_mechanisms(&probe(true, None)), [UnmountMechanism::Umount2]);
+    }
+
+    #[test]
+    fn root_falls_back_to_fusermount_after_umount2() {
+        // umount2 fails with EPERM in a user namespace without CAP_SYS_ADMIN, so fusermount has to be tried afterwards.
+        assert_eq!(
+            select_unmount_mechanisms(&probe(true, Some("/usr/bin/fusermount3"))),
+            [UnmountMechanism::Umount2, UnmountMechanism::Fusermount(PathBuf::from("/usr/bin/fusermount3"))]);
+    }
+
+    #[test]
+    fn unprivileged_uses_fusermount3() {
+        assert_eq!(
+            select_unmount_mechanisms(&probe(false, Some("/usr/bin/fusermount3"))),
+            [UnmountMechanism::Fusermount(PathBuf::from("/usr/bin/fusermount3"))]);
+    }
+
+    #[test]
+    fn unprivileged_uses_fusermount() {
+        assert_eq!(
+            select_unmount_mechanisms(&probe(false, Some("/bin/fusermount"))),
+            [UnmountMechanism::Fusermount(PathBuf::from("/bin/fusermount"))]);
+    }
+
+    #[test]
+    fn unprivileged_without_fusermount_has_no_mechanism() {
+        assert!(select_unmount_mechanisms(&probe(false, None)).is_empty());
+    }
+
+    #[test]
+    fn fusermount3_is_preferred() {
+        let base = env::temp_dir().join(format!("zffmount-test-fusermount-{}", std::process::id()));
+        let (first, second) = (base.join("first"), base.join("second"));
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("fusermount"), "").unwrap();
+        let path = env::join_paths([&first, &second]).unwrap();
+        assert_eq!(find_fusermount_in(&path), Some(first.join("fusermount")));
+        fs::write(second.join("fusermount3"), "").unwrap();
+        assert_eq!(find_fusermount_in(&path), Some(second.join("fusermount3")));
+        fs::remove_dir_all(&base).unwrap();
+        assert_eq!(find_fusermount_in(&path), None);
+    }
+}
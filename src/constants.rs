@@ -7,10 +7,18 @@ use fuser::{FileAttr, FileType};
 // - errors
 pub(crate) const EXIT_STATUS_ERROR: i32 = 1;
 pub(crate) const EXIT_STATUS_SUCCESS: i32 = 0;
+pub(crate) const EXIT_STATUS_NOT_MOUNTED: i32 = 2;
+pub(crate) const EXIT_STATUS_BUSY: i32 = 3;
+pub(crate) const EXIT_STATUS_PERMISSION_DENIED: i32 = 4;
+pub(crate) const EXIT_STATUS_FORCED_UNMOUNT: i32 = 5;
+pub(crate) const EXIT_STATUS_VERIFICATION_FAILED: i32 = 6;
+pub(crate) const EXIT_STATUS_DECRYPTION_FAILED: i32 = 7;
+// 128 + SIGINT, as used by the shells.
+pub(crate) const EXIT_STATUS_ABORTED: i32 = 130;
 
 // Zff Overlay FS
 pub(crate) const ZFF_OVERLAY_FS_NAME: &str = "ZffOverlayFs";
-pub(crate) const OBJECT_PREFIX: &str = "object_";
+// the template of the root directory attributes - the owner and the timestamps are set at mount time.
 pub(crate) const DEFAULT_ROOT_DIR_ATTR: FileAttr = FileAttr {
     ino: SPECIAL_INODE_ROOT_DIR,
     size: 0,
@@ -32,17 +40,39 @@ pub(crate) const DEFAULT_ROOT_DIR_ATTR: FileAttr = FileAttr {
 pub(crate) const SPECIAL_INODE_ROOT_DIR: u64 = 1;
 pub(crate) const DEFAULT_BLOCKSIZE: u32 = 512;
 pub(crate) const ZFF_PHYSICAL_OBJECT_NAME: &str = "zff_image.dd";
+pub(crate) const IMAGE_NAME_PLACEHOLDER_OBJECT: &str = "{object}";
+pub(crate) const IMAGE_NAME_PLACEHOLDER_CASE: &str = "{case}";
+pub(crate) const IMAGE_NAME_PLACEHOLDER_EVIDENCE: &str = "{evidence}";
+pub(crate) const IMAGE_NAME_UNKNOWN_VALUE: &str = "unknown";
 
 pub(crate) const DEFAULT_TRASHFOLDER_NAME: &str = ".Trash";
 
 pub(crate) const DEFAULT_ENTRY_GENERATION: u64 = 0;
+// the handle 0 is used if nothing was cached for the file or directory (e.g. by the kernel without opendir).
+pub(crate) const FIRST_HANDLE: u64 = 1;
+
+// the maximum number of hardlinks, which will be followed to resolve a chain of hardlinks.
+pub(crate) const MAX_HARDLINK_HOPS: usize = 16;
+
+// the number of caught panics of an inode, after which the operations of the inode fail immediately.
+pub(crate) const PANIC_FAIL_FAST_THRESHOLD: u64 = 2;
 
 // fuser constants
 pub(crate) const TTL: Duration = Duration::from_secs(1); // 1 second
+// the TTL of the entries and attributes with --cache-forever (the content of a container never changes).
+pub(crate) const CACHE_FOREVER_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 365); // 1 year
+// the default max readahead is a multiple of the largest chunk size, so a readahead covers multiple chunks.
+pub(crate) const DEFAULT_READAHEAD_CHUNKS: u64 = 4;
+// the fusermount binaries, in the order they will be searched on the PATH.
+pub(crate) const FUSERMOUNT_BINARIES: [&str; 2] = ["fusermount3", "fusermount"];
+// the interval to check, if the unmount of the filesystem has finished or should be forced.
+pub(crate) const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 // special paths
 pub(crate) const CURRENT_DIR: &str = ".";
 pub(crate) const PARENT_DIR: &str = "..";
+// the presented name of an empty filename, if the names are sanitized.
+pub(crate) const SANITIZED_EMPTY_FILENAME: &str = "(empty name)";
 
 // prefix
 pub(crate) const OBJECT_PATH_PREFIX: &str = "object_";
@@ -51,3 +81,75 @@ pub(crate) const ATIME: &str = "atime";
 pub(crate) const MTIME: &str = "mtime";
 pub(crate) const CTIME: &str = "ctime";
 pub(crate) const BTIME: &str = "btime";
+pub(crate) const UID: &str = "uid";
+pub(crate) const GID: &str = "gid";
+// the nanoseconds of a timestamp are stored in a separate key, e.g. atime_nsec.
+pub(crate) const TIMESTAMP_NSEC_SUFFIX: &str = "_nsec";
+pub(crate) const NANOSECONDS_PER_SECOND: i64 = 1_000_000_000;
+
+// benchmark
+pub(crate) const BENCHMARK_SEQUENTIAL_READ_SIZE: usize = 1024*1024; // 1 MiB
+pub(crate) const BENCHMARK_RANDOM_READ_SIZE: usize = 64*1024; // 64 KiB
+pub(crate) const BENCHMARK_METADATA_ROUNDS: u64 = 10;
+
+// extraction
+pub(crate) const EXTRACT_BUFFER_SIZE: usize = 1024*1024; // 1 MiB
+
+// samebytes
+// the size of the static zero buffer, which is used to reply reads of zero chunks (larger reads use an allocated buffer).
+pub(crate) const SAMEBYTES_ZERO_BUFFER_SIZE: usize = 1024*1024; // 1 MiB
+
+// extended attributes
+pub(crate) const XATTR_ORIGINAL_NAME: &str = "user.zff.original_name";
+pub(crate) const XATTR_SOURCE_UID: &str = "user.zff.uid";
+pub(crate) const XATTR_SOURCE_GID: &str = "user.zff.gid";
+pub(crate) const XATTR_SYMLINK_TARGET: &str = "user.zff.symlink_target";
+// the byte ranges of a file, which could not be read while acquiring it (e.g. 0-4095,65536-98303 - the ends are inclusive).
+pub(crate) const XATTR_ACQUISITION_ERROR: &str = "user.zff.acquisition_error";
+// the encryption state of the objects (at the object directories and the locked placeholder directories).
+pub(crate) const XATTR_ENCRYPTED: &str = "user.zff.encrypted";
+pub(crate) const XATTR_DECRYPTED: &str = "user.zff.decrypted";
+pub(crate) const XATTR_ENCRYPTION_ALGORITHM: &str = "user.zff.encryption_algorithm";
+// the objects of the container (at the root directory of the container) - the object list is a JSON array.
+pub(crate) const XATTR_OBJECT_COUNT: &str = "user.zff.object_count";
+pub(crate) const XATTR_OBJECTS: &str = "user.zff.objects";
+
+// virtual files
+// the virtual files are placed far above the inodes of the chunks, but below the container inode range (see CONTAINER_INODE_SHIFT).
+pub(crate) const SPECIAL_INODE_VIRTUAL_FILES_START: u64 = 1 << 55;
+
+// empty files
+// files without any chunk are placed below the virtual files. The object number is shifted by EMPTY_FILE_INODE_OBJECT_SHIFT,
+// so each object has its own inode range for its empty files.
+pub(crate) const SPECIAL_INODE_EMPTY_FILES_START: u64 = 1 << 54;
+pub(crate) const EMPTY_FILE_INODE_OBJECT_SHIFT: u32 = 32;
+
+// locked objects
+// the placeholder directories of the encrypted objects, which could not be decrypted, are placed above the virtual files
+// (SPECIAL_INODE_LOCKED_OBJECTS_START + object number), but below the container inode range (see CONTAINER_INODE_SHIFT).
+pub(crate) const SPECIAL_INODE_LOCKED_OBJECTS_START: u64 = (1 << 55) | (1 << 54);
+pub(crate) const LOCKED_OBJECT_DIR_SUFFIX: &str = ".locked";
+pub(crate) const LOCKED_NOTICE_FILE_NAME: &str = "LOCKED.txt";
+pub(crate) const LOCKED_OBJECT_DIR_PERMISSIONS: u16 = 0o555;
+
+// multiple containers
+// the inodes of each container are shifted by (container index + 1) << CONTAINER_INODE_SHIFT, so containers never share inodes.
+pub(crate) const CONTAINER_INODE_SHIFT: u32 = 56;
+pub(crate) const VIRTUAL_FILE_PERMISSIONS: u16 = 0o444;
+pub(crate) const VERIFICATION_FILE_NAME: &str = "verification.txt";
+// the extension of the following files depends on the report format (.txt or .json).
+pub(crate) const ACQUISITION_REPORT_FILE_STEM: &str = "acquisition_report";
+pub(crate) const UNREADABLE_RANGES_FILE_STEM: &str = "unreadable_ranges";
+pub(crate) const VERIFICATION_FILE_SIZE_ESTIMATE: u64 = 4096;
+pub(crate) const UNREADABLE_RANGES_FILE_SIZE_ESTIMATE: u64 = 4096;
+pub(crate) const CHUNKMAP_FILE_NAME: &str = "chunkmap.csv";
+// the segments of split raw images are named e.g. zff_image.001, zff_image.002, ...
+pub(crate) const SPLIT_RAW_FILE_PREFIX: &str = "zff_image";
+pub(crate) const SPLIT_RAW_MIN_EXTENSION_WIDTH: usize = 3;
+// the virtual tar archives of the logical objects are named e.g. object_1.tar (see --virtual-archives).
+pub(crate) const ARCHIVE_FILE_EXTENSION: &str = "tar";
+pub(crate) const TAR_BLOCK_SIZE: u64 = 512;
+// the end of a tar archive is marked by two empty blocks.
+pub(crate) const TAR_END_OF_ARCHIVE_BLOCKS: u64 = 2;
+// the end offset is exclusive.
+pub(crate) const CHUNKMAP_CSV_HEADER: &str = "chunk_number,start_offset,end_offset,samebytes,duplicate,encrypted,error,compressed_size\n";
@@ -0,0 +1,90 @@
+// - STD
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+// - internal
+use crate::constants::*;
+
+// - external
+use zff::ZffError;
+
+/// The errors, which abort the initialization or the mount. The Display implementation returns the user-facing
+/// message; the underlying error (if any) is returned by [ZffMountError::detail] and is logged at the debug level.
+#[derive(Debug)]
+pub enum ZffMountError {
+    /// An error of the zff library (e.g. while reading the container).
+    Zff { context: String, source: ZffError },
+    /// An I/O error (e.g. while opening the input files).
+    Io { context: String, source: io::Error },
+    /// An error of the preload chunkmap database.
+    Redb { context: String, source: redb::Error },
+    /// The mount point does not exist or is not a directory.
+    MountPointInvalid { path: PathBuf, reason: String },
+    /// The given object does not exist in the container.
+    ObjectNotFound { object: u64 },
+    /// The given path does not exist in the given object.
+    PathNotFound { object: u64, path: PathBuf },
+    /// The given object could not be decrypted.
+    DecryptionFailed { object: u64 },
+    /// The given objects could not be decrypted, but all objects have to be decrypted (--require-all-decrypted).
+    NotAllDecrypted { objects: Vec<u64> },
+    /// FUSE is not usable in the current environment (e.g. /dev/fuse does not exist). Contains a hint how to fix it.
+    FuseUnavailable { reason: String, hint: String },
+    /// The initialization was aborted by a signal.
+    Aborted,
+}
+
+impl ZffMountError {
+    pub(crate) fn zff<C: Into<String>>(context: C, source: ZffError) -> Self {
+        Self::Zff { context: context.into(), source }
+    }
+
+    pub(crate) fn io<C: Into<String>>(context: C, source: io::Error) -> Self {
+        Self::Io { context: context.into(), source }
+    }
+
+    pub(crate) fn redb<C: Into<String>, E: Into<redb::Error>>(context: C, source: E) -> Self {
+        Self::Redb { context: context.into(), source: source.into() }
+    }
+
+    /// Returns the message of the underlying error, if any.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            ZffMountError::Zff { source, .. } => Some(source.to_string()),
+            ZffMountError::Io { source, .. } => Some(source.to_string()),
+            ZffMountError::Redb { source, .. } => Some(source.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Returns the exit status of the process for this error.
+    pub fn exit_status(&self) -> i32 {
+        match self {
+            ZffMountError::Aborted => EXIT_STATUS_ABORTED,
+            ZffMountError::NotAllDecrypted { .. } => EXIT_STATUS_DECRYPTION_FAILED,
+            _ => EXIT_STATUS_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ZffMountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ZffMountError::Zff { context, .. } | ZffMountError::Io { context, .. } | ZffMountError::Redb { context, .. } => write!(f, "{context}"),
+            ZffMountError::MountPointInvalid { path, reason } => write!(f, "The mount point {} is invalid: {reason}.", path.display()),
+            ZffMountError::ObjectNotFound { object } => write!(f, "Object {object} does not exist in the container."),
+            ZffMountError::PathNotFound { object, path } => write!(f, "The path {} does not exist in object {object}.", path.display()),
+            ZffMountError::DecryptionFailed { object } => write!(f, "Object {object} could not be decrypted."),
+            ZffMountError::NotAllDecrypted { objects } => {
+                let objects: Vec<String> = objects.iter().map(|object| object.to_string()).collect();
+                write!(f, "The objects {} could not be decrypted, but --require-all-decrypted is set.", objects.join(", "))
+            },
+            ZffMountError::FuseUnavailable { reason, hint } => write!(f, "FUSE is not available: {reason}. {hint}"),
+            ZffMountError::Aborted => write!(f, "Initialization aborted."),
+        }
+    }
+}
+
+impl Error for ZffMountError {}
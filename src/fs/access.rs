@@ -0,0 +1,53 @@
+// - STD
+use std::collections::BTreeSet;
+
+/// Restricts the access to the mounted filesystem to the given users. Requests of all other users are answered with EACCES,
+/// so neither the content nor the metadata of the container is disclosed to them.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct AccessRestriction {
+    uids: BTreeSet<u32>,
+    allow_root: bool,
+}
+
+impl AccessRestriction {
+    /// Creates a new restriction to the given uids. The root user is only permitted, if allow_root is set (or uid 0 is given).
+    pub fn new<I: IntoIterator<Item=u32>>(uids: I, allow_root: bool) -> Self {
+        Self {
+            uids: uids.into_iter().collect(),
+            allow_root,
+        }
+    }
+
+    /// Returns true, if the user with the given uid is permitted to access the filesystem.
+    pub fn permits(&self, uid: u32) -> bool {
+        self.uids.contains(&uid) || (self.allow_root && uid == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permits_the_listed_uids_only() {
+        let restriction = AccessRestriction::new([1000, 1001], false);
+        assert!(restriction.permits(1000));
+        assert!(restriction.permits(1001));
+        assert!(!restriction.permits(1002));
+    }
+
+    #[test]
+    fn permits_root_only_with_allow_root() {
+        assert!(!AccessRestriction::new([1000], false).permits(0));
+        assert!(AccessRestriction::new([1000], true).permits(0));
+        // allow_root does not permit other users.
+        assert!(!AccessRestriction::new([1000], true).permits(1001));
+    }
+
+    #[test]
+    fn permits_root_if_uid_0_is_listed() {
+        let restriction = AccessRestriction::new([0], false);
+        assert!(restriction.permits(0));
+        assert!(!restriction.permits(1000));
+    }
+}
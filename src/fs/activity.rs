@@ -0,0 +1,50 @@
+// - STD
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Tracks the time of the last filesystem operation and the number of open file handles.
+/// The tracker is shared (by an Arc) between the filesystem and the main thread, which checks the idle time.
+#[derive(Debug)]
+pub struct ActivityTracker {
+    last_activity: AtomicU64, // seconds since UNIX_EPOCH
+    open_handles: AtomicU64,
+}
+
+impl ActivityTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_activity: AtomicU64::new(now()),
+            open_handles: AtomicU64::new(0),
+        }
+    }
+
+    /// Records a filesystem operation.
+    pub(crate) fn touch(&self) {
+        self.last_activity.store(now(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn open_handle(&self) {
+        self.open_handles.fetch_add(1, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub(crate) fn release_handle(&self) {
+        // the kernel never releases more handles than were opened, but we won't underflow in any case.
+        let _ = self.open_handles.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |handles| handles.checked_sub(1));
+        self.touch();
+    }
+
+    /// Returns the time since the last filesystem operation, or None if there are open file handles
+    /// (e.g. a long-running hash job, which reads the image in large intervals).
+    pub fn idle_time(&self) -> Option<Duration> {
+        if self.open_handles.load(Ordering::Relaxed) > 0 {
+            return None;
+        }
+        let last_activity = self.last_activity.load(Ordering::Relaxed);
+        Some(Duration::from_secs(now().saturating_sub(last_activity)))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default()
+}
@@ -0,0 +1,565 @@
+// - STD
+use std::borrow::Cow;
+use std::cmp::min;
+use std::collections::HashSet;
+use std::io::{self, Read, Seek};
+use std::os::unix::ffi::OsStrExt;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+// - internal
+use super::ZffFs;
+use super::virtual_files::{VirtualFile, VirtualFileType, virtual_file_attr};
+use crate::constants::*;
+use zff::io::zffreader::ObjectType as ZffReaderObjectType;
+
+// - external
+use log::{debug, info, warn};
+use fuser::{FileAttr, FileType};
+use libc::{c_int, EIO};
+
+// the fields of a ustar header block (offset, length).
+const NAME_FIELD: (usize, usize) = (0, 100);
+const MODE_FIELD: (usize, usize) = (100, 8);
+const UID_FIELD: (usize, usize) = (108, 8);
+const GID_FIELD: (usize, usize) = (116, 8);
+const SIZE_FIELD: (usize, usize) = (124, 12);
+const MTIME_FIELD: (usize, usize) = (136, 12);
+const CHECKSUM_FIELD: (usize, usize) = (148, 8);
+const TYPE_FLAG_FIELD: (usize, usize) = (156, 1);
+const LINKNAME_FIELD: (usize, usize) = (157, 100);
+const MAGIC_FIELD: (usize, usize) = (257, 6);
+const VERSION_FIELD: (usize, usize) = (263, 2);
+const DEVMAJOR_FIELD: (usize, usize) = (329, 8);
+const DEVMINOR_FIELD: (usize, usize) = (337, 8);
+
+const USTAR_MAGIC: &[u8] = b"ustar\0";
+const USTAR_VERSION: &[u8] = b"00";
+const PAX_HEADER_TYPE_FLAG: u8 = b'x';
+const PAX_HEADER_PREFIX: &[u8] = b"PaxHeaders/";
+const PAX_HEADER_PERMISSIONS: u32 = 0o644;
+
+/// The layout of a virtual tar archive, which contains the whole content of a logical object. The headers of the entries
+/// are generated again by each read, so only the offsets and the metadata of the entries are kept in memory.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(crate) struct ArchiveLayout {
+    entries: Vec<ArchiveEntry>, // sorted by the offset.
+    end_of_entries: u64,
+}
+
+impl ArchiveLayout {
+    /// Returns the size of the archive (including the end of archive blocks).
+    pub(crate) fn size(&self) -> u64 {
+        self.end_of_entries + TAR_END_OF_ARCHIVE_BLOCKS * TAR_BLOCK_SIZE
+    }
+
+    /// Returns the number of entries of the archive.
+    pub(crate) fn number_of_entries(&self) -> usize {
+        self.entries.len()
+    }
+
+    // appends the given entry to the archive.
+    fn add(&mut self, inode: u64, header: TarHeader) {
+        let offset = self.end_of_entries;
+        self.end_of_entries += header.blocks().len() as u64 + padded_size(header.size);
+        self.entries.push(ArchiveEntry { offset, inode, header });
+    }
+}
+
+// a single file or directory of a virtual tar archive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ArchiveEntry {
+    offset: u64, // the offset of the (first) header block in the archive.
+    inode: u64, // the inode of the file, which contains the data of the entry.
+    header: TarHeader,
+}
+
+// the metadata of an entry, which is needed to generate its header blocks.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct TarHeader {
+    path: Box<[u8]>, // relative to the object directory, with a trailing slash for directories.
+    link_target: Option<Box<[u8]>>,
+    type_flag: u8,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    size: u64, // the size of the data (only regular files contain data).
+    mtime: i64,
+    rdev: u32,
+}
+
+impl TarHeader {
+    // returns the header of the given file, or None, if the file type could not be archived (sockets).
+    fn new(mut path: Vec<u8>, attr: &FileAttr, link_target: Option<Vec<u8>>) -> Option<Self> {
+        let type_flag = match attr.kind {
+            FileType::RegularFile => b'0',
+            FileType::Symlink => b'2',
+            FileType::CharDevice => b'3',
+            FileType::BlockDevice => b'4',
+            FileType::Directory => b'5',
+            FileType::NamedPipe => b'6',
+            FileType::Socket => return None,
+        };
+        if attr.kind == FileType::Directory {
+            path.push(b'/');
+        }
+        let mtime = match attr.mtime.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        Some(Self {
+            path: path.into_boxed_slice(),
+            link_target: link_target.map(Vec::into_boxed_slice),
+            type_flag,
+            mode: attr.perm as u32,
+            uid: attr.uid,
+            gid: attr.gid,
+            size: if attr.kind == FileType::RegularFile { attr.size } else { 0 },
+            mtime,
+            rdev: attr.rdev,
+        })
+    }
+
+    // returns the header blocks of the entry. The values, which do not fit into the ustar header (e.g. long paths), are
+    // stored in a preceding pax extended header.
+    fn blocks(&self) -> Vec<u8> {
+        let mut records = Vec::new();
+        if self.path.len() > NAME_FIELD.1 {
+            records.extend(pax_record("path", &self.path));
+        }
+        if let Some(link_target) = self.link_target.as_deref().filter(|link_target| link_target.len() > LINKNAME_FIELD.1) {
+            records.extend(pax_record("linkpath", link_target));
+        }
+        for (key, value, field) in [("size", self.size, SIZE_FIELD), ("uid", self.uid as u64, UID_FIELD), ("gid", self.gid as u64, GID_FIELD)] {
+            if value > max_octal(field) {
+                records.extend(pax_record(key, value.to_string().as_bytes()));
+            }
+        }
+        if self.mtime < 0 || self.mtime as u64 > max_octal(MTIME_FIELD) {
+            records.extend(pax_record("mtime", self.mtime.to_string().as_bytes()));
+        }
+
+        let mut blocks = Vec::new();
+        if !records.is_empty() {
+            let name = self.path.strip_suffix(b"/").unwrap_or(&self.path[..]);
+            let name = name.rsplit(|byte| *byte == b'/').next().unwrap_or(name);
+            let pax_header = Self {
+                path: [PAX_HEADER_PREFIX, name].concat().into_boxed_slice(),
+                link_target: None,
+                type_flag: PAX_HEADER_TYPE_FLAG,
+                mode: PAX_HEADER_PERMISSIONS,
+                uid: 0,
+                gid: 0,
+                size: records.len() as u64,
+                mtime: self.mtime,
+                rdev: 0,
+            };
+            blocks.extend_from_slice(&pax_header.ustar_block());
+            blocks.extend_from_slice(&records);
+            blocks.resize(padded_size(blocks.len() as u64) as usize, 0);
+        }
+        blocks.extend_from_slice(&self.ustar_block());
+        blocks
+    }
+
+    // returns the ustar header block of the entry. Too long values are truncated (and stored in the pax header).
+    fn ustar_block(&self) -> [u8; TAR_BLOCK_SIZE as usize] {
+        let mut block = [0; TAR_BLOCK_SIZE as usize];
+        set_field(&mut block, NAME_FIELD, &self.path);
+        set_octal(&mut block, MODE_FIELD, (self.mode & 0o7777) as u64);
+        set_octal(&mut block, UID_FIELD, self.uid as u64);
+        set_octal(&mut block, GID_FIELD, self.gid as u64);
+        set_octal(&mut block, SIZE_FIELD, self.size);
+        set_octal(&mut block, MTIME_FIELD, self.mtime.max(0) as u64);
+        set_field(&mut block, TYPE_FLAG_FIELD, &[self.type_flag]);
+        if let Some(link_target) = &self.link_target {
+            set_field(&mut block, LINKNAME_FIELD, link_target);
+        }
+        set_field(&mut block, MAGIC_FIELD, USTAR_MAGIC);
+        set_field(&mut block, VERSION_FIELD, USTAR_VERSION);
+        if matches!(self.type_flag, b'3' | b'4') {
+            let major = (self.rdev >> 8) & 0xfff;
+            let minor = (self.rdev & 0xff) | ((self.rdev >> 12) & 0xfff00);
+            set_octal(&mut block, DEVMAJOR_FIELD, major as u64);
+            set_octal(&mut block, DEVMINOR_FIELD, minor as u64);
+        }
+        // the checksum is calculated with spaces in the checksum field.
+        set_field(&mut block, CHECKSUM_FIELD, &[b' '; CHECKSUM_FIELD.1]);
+        let checksum: u64 = block.iter().map(|byte| *byte as u64).sum();
+        set_field(&mut block, CHECKSUM_FIELD, format!("{checksum:06o}\0 ").as_bytes());
+        block
+    }
+}
+
+impl<R: Read + Seek> ZffFs<R> {
+    /// Places a virtual tar archive (e.g. object_1.tar) with the whole content of the object into the directory of each
+    /// logical object. The layout of the archives is built while mounting, which needs a walk through all directories.
+    pub(crate) fn add_virtual_archives(&mut self) {
+        let logical_objects: Vec<u64> = self.cache.object_list.iter()
+            .filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Logical)
+            .map(|(&object_number, _)| object_number)
+            .collect();
+        let mut inode = self.cache.virtual_files.keys().next_back().map_or(SPECIAL_INODE_VIRTUAL_FILES_START, |inode| inode + 1);
+        for object_number in logical_objects {
+            let object_dir_attr = match self.cache.inode_attributes_map.get(&(object_number + 1)) {
+                Some(attr) => *attr,
+                None => continue,
+            };
+            info!(object = object_number; "Building the virtual archive of object {object_number} ...");
+            let layout = self.archive_layout(object_number);
+            debug!("The virtual archive of object {object_number} contains {} entries ({} bytes).", layout.number_of_entries(), layout.size());
+            let name = format!("{}{object_number}.{ARCHIVE_FILE_EXTENSION}", self.object_dir_prefix());
+            self.cache.inode_attributes_map.insert(inode, virtual_file_attr(layout.size(), &object_dir_attr));
+            self.cache.virtual_files.insert(inode, VirtualFile::new(object_number, object_number + 1, name, VirtualFileType::Archive(Arc::new(layout))));
+            inode += 1;
+        }
+    }
+
+    // returns the layout of the virtual tar archive of the given logical object. The entries, which could not be read,
+    // are logged and left out.
+    fn archive_layout(&mut self, object_number: u64) -> ArchiveLayout {
+        let mut layout = ArchiveLayout::default();
+        let mut visited = HashSet::new();
+        self.add_archive_directory(object_number + 1, &[], &mut layout, &mut visited);
+        layout
+    }
+
+    // appends the content of the given directory (with the given path in the archive) to the given layout. A directory,
+    // which was already visited (e.g. a directory, which contains itself in a corrupted container), is left out.
+    fn add_archive_directory(&mut self, ino: u64, path: &[u8], layout: &mut ArchiveLayout, visited: &mut HashSet<u64>) {
+        if !visited.insert(ino) {
+            warn!("Leaving the directory inode {ino} out of the virtual archive, as it was already added.");
+            return;
+        }
+        let entries = match self.directory_entries(ino) {
+            Ok(entries) => entries,
+            Err(errno) => {
+                warn!("Could not list the directory inode {ino} for the virtual archive: {}", io::Error::from_raw_os_error(errno));
+                return;
+            }
+        };
+        for (inode, file_type, name) in entries {
+            if *name == *CURRENT_DIR || *name == *PARENT_DIR || self.cache.virtual_files.contains_key(&inode) {
+                continue;
+            }
+            if name.as_bytes().contains(&b'/') {
+                warn!("Leaving {:?} out of the virtual archive, as the name contains a slash.", name);
+                continue;
+            }
+            let mut entry_path = path.to_vec();
+            if !entry_path.is_empty() {
+                entry_path.push(b'/');
+            }
+            entry_path.extend_from_slice(name.as_bytes());
+            let attr = match self.file_attr(inode) {
+                Some(attr) => attr,
+                None => {
+                    warn!("Leaving {:?} out of the virtual archive, as the attributes are not available.", name);
+                    continue;
+                }
+            };
+            let link_target = if file_type == FileType::Symlink {
+                match self.symlink_target(inode) {
+                    Ok(link_target) => Some(link_target),
+                    Err(e) => {
+                        warn!("Leaving {:?} out of the virtual archive, as the symlink target could not be read.", name);
+                        debug!("{e}");
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            match TarHeader::new(entry_path.clone(), &attr, link_target) {
+                Some(header) => layout.add(inode, header),
+                None => {
+                    debug!("Leaving {:?} out of the virtual archive, as {:?} entries could not be archived.", name, attr.kind);
+                    continue;
+                }
+            }
+            if file_type == FileType::Directory {
+                self.add_archive_directory(inode, &entry_path, layout, visited);
+            }
+        }
+    }
+
+    // reads the given range of the given virtual archive. The headers are generated and the data of the files is read
+    // by the usual read path, so the archive could be read at any offset.
+    pub(crate) fn read_archive(&mut self, layout: &ArchiveLayout, offset: u64, size: u32) -> Result<Vec<u8>, c_int> {
+        read_archive_with(layout, offset, size, |inode, offset, size| self.read_data(inode, offset, size))
+    }
+}
+
+// reads the given range of the given virtual archive. The data of the files is read by the given function (inode, offset
+// in the file, size).
+fn read_archive_with<F>(layout: &ArchiveLayout, offset: u64, size: u32, mut read_data: F) -> Result<Vec<u8>, c_int>
+where
+    F: FnMut(u64, u64, u32) -> Result<Cow<'static, [u8]>, c_int>,
+{
+    let end = min(offset.saturating_add(size as u64), layout.size());
+    let mut data = Vec::with_capacity(end.saturating_sub(offset) as usize);
+    let mut position = offset;
+    // the last entry, which starts at or before the offset.
+    let mut index = layout.entries.partition_point(|entry| entry.offset <= position).saturating_sub(1);
+    while position < end {
+        let entry = match layout.entries.get(index) {
+            Some(entry) => entry,
+            None => {
+                // the end of archive blocks.
+                data.resize(data.len() + (end - position) as usize, 0);
+                break;
+            }
+        };
+        let header = entry.header.blocks();
+        let data_start = entry.offset + header.len() as u64;
+        let data_end = data_start + entry.header.size;
+        let entry_end = data_start + padded_size(entry.header.size);
+        if position < data_start {
+            let start = (position - entry.offset) as usize;
+            let length = (min(end, data_start) - position) as usize;
+            data.extend_from_slice(&header[start..start + length]);
+            position += length as u64;
+        } else if position < data_end {
+            let length = min(end, data_end) - position;
+            let content = read_data(entry.inode, position - data_start, length as u32)?;
+            let content = &content[..min(content.len(), length as usize)];
+            if content.is_empty() {
+                debug!("The data of inode {} ends at offset {} of {} bytes.", entry.inode, position - data_start, entry.header.size);
+                return Err(EIO);
+            }
+            data.extend_from_slice(content);
+            position += content.len() as u64;
+        } else if position < entry_end {
+            let length = min(end, entry_end) - position;
+            data.resize(data.len() + length as usize, 0);
+            position += length;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(data)
+}
+
+// returns the given size rounded up to whole tar blocks.
+fn padded_size(size: u64) -> u64 {
+    (size + TAR_BLOCK_SIZE - 1) / TAR_BLOCK_SIZE * TAR_BLOCK_SIZE
+}
+
+// returns the largest number, which could be stored in the given octal field (the last byte is the terminating NUL).
+fn max_octal(field: (usize, usize)) -> u64 {
+    (1 << (3 * (field.1 - 1))) - 1
+}
+
+// copies the given value into the given field. Too long values are truncated.
+fn set_field(block: &mut [u8], (offset, length): (usize, usize), value: &[u8]) {
+    let length = min(length, value.len());
+    block[offset..offset + length].copy_from_slice(&value[..length]);
+}
+
+// stores the given number as zero-padded octal number in the given field. Too large numbers are stored as the largest
+// possible number (the exact value is part of the pax header).
+fn set_octal(block: &mut [u8], field: (usize, usize), value: u64) {
+    let digits = field.1 - 1;
+    let value = min(value, max_octal(field));
+    set_field(block, field, format!("{value:0digits$o}").as_bytes());
+}
+
+// returns the pax record "<length> <key>=<value>\n". The length includes the decimal digits of the length itself.
+fn pax_record(key: &str, value: &[u8]) -> Vec<u8> {
+    let payload = key.len() + value.len() + 3; // the space, the equals sign and the newline.
+    let mut length = payload + 1;
+    while length != payload + length.to_string().len() {
+        length = payload + length.to_string().len();
+    }
+    let mut record = format!("{length} {key}=").into_bytes();
+    record.extend_from_slice(value);
+    record.push(b'\n');
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_header(path: &[u8], size: u64) -> TarHeader {
+        TarHeader {
+            path: path.into(),
+            link_target: None,
+            type_flag: b'0',
+            mode: 0o644,
+            uid: 1000,
+            gid: 1000,
+            size,
+            mtime: 1_700_000_000,
+            rdev: 0,
+        }
+    }
+
+    fn octal_field(block: &[u8], (offset, length): (usize, usize)) -> u64 {
+        let digits: String = block[offset..offset + length].iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .map(|byte| *byte as char)
+            .collect();
+        u64::from_str_radix(&digits, 8).unwrap()
+    }
+
+    // the content of the files in the test archives: a pattern, which depends on the inode and the offset.
+    fn file_byte(inode: u64, offset: u64) -> u8 {
+        (inode * 37 + offset) as u8
+    }
+
+    fn read_test_file(inode: u64, offset: u64, size: u32) -> Result<Cow<'static, [u8]>, c_int> {
+        Ok(Cow::Owned((offset..offset + size as u64).map(|offset| file_byte(inode, offset)).collect()))
+    }
+
+    fn test_layout() -> ArchiveLayout {
+        let mut layout = ArchiveLayout::default();
+        layout.add(1, file_header(b"a", 5));
+        layout.add(2, file_header(b"dir/b", 600));
+        layout.add(3, file_header(&[b'c'; 150], 10));
+        layout
+    }
+
+    // returns the whole archive of the given layout, built entry by entry.
+    fn expected_archive(layout: &ArchiveLayout) -> Vec<u8> {
+        let mut archive = Vec::new();
+        for entry in &layout.entries {
+            assert_eq!(entry.offset, archive.len() as u64);
+            archive.extend_from_slice(&entry.header.blocks());
+            archive.extend((0..entry.header.size).map(|offset| file_byte(entry.inode, offset)));
+            archive.resize(padded_size(archive.len() as u64) as usize, 0);
+        }
+        archive.resize(archive.len() + (TAR_END_OF_ARCHIVE_BLOCKS * TAR_BLOCK_SIZE) as usize, 0);
+        archive
+    }
+
+    #[test]
+    fn pax_record_length_includes_its_own_digits() {
+        assert_eq!(pax_record("path", b"a"), b"9 path=a\n");
+        // the length grows from 9 to 11, as the length itself needs two digits.
+        assert_eq!(pax_record("path", b"ab"), b"11 path=ab\n");
+        assert_eq!(pax_record("path", &[b'a'; 90]).len(), 99);
+        // the length grows from 99 to 101, as the length itself needs three digits.
+        assert_eq!(pax_record("path", &[b'a'; 91]).len(), 101);
+        assert!(pax_record("path", &[b'a'; 91]).starts_with(b"101 path="));
+    }
+
+    #[test]
+    fn pax_record_length_is_consistent_for_all_lengths() {
+        for value_length in 0..1100 {
+            let record = pax_record("linkpath", &vec![b'x'; value_length]);
+            let length: usize = std::str::from_utf8(record.split(|byte| *byte == b' ').next().unwrap()).unwrap().parse().unwrap();
+            assert_eq!(length, record.len(), "value length {value_length}");
+        }
+    }
+
+    #[test]
+    fn ustar_block_checksum() {
+        let block = file_header(b"a", 5).ustar_block();
+        let mut unsummed = block;
+        unsummed[CHECKSUM_FIELD.0..CHECKSUM_FIELD.0 + CHECKSUM_FIELD.1].fill(b' ');
+        let checksum: u64 = unsummed.iter().map(|byte| *byte as u64).sum();
+        assert_eq!(octal_field(&block, CHECKSUM_FIELD), checksum);
+        assert_eq!(&block[CHECKSUM_FIELD.0 + 6..CHECKSUM_FIELD.0 + 8], b"\0 ");
+        assert_eq!(&block[MAGIC_FIELD.0..MAGIC_FIELD.0 + MAGIC_FIELD.1], USTAR_MAGIC);
+    }
+
+    #[test]
+    fn max_octal_of_the_fields() {
+        assert_eq!(max_octal(SIZE_FIELD), (8 << 30) - 1);
+        assert_eq!(max_octal(UID_FIELD), 0o7777777);
+    }
+
+    #[test]
+    fn sizes_below_8_gib_fit_into_the_ustar_header() {
+        let blocks = file_header(b"a", (8 << 30) - 1).blocks();
+        assert_eq!(blocks.len() as u64, TAR_BLOCK_SIZE);
+        assert_eq!(octal_field(&blocks, SIZE_FIELD), (8 << 30) - 1);
+    }
+
+    #[test]
+    fn sizes_of_8_gib_are_stored_in_a_pax_header() {
+        let blocks = file_header(b"a", 8 << 30).blocks();
+        // the pax header, the (padded) records and the ustar header.
+        assert_eq!(blocks.len() as u64, 3 * TAR_BLOCK_SIZE);
+        assert_eq!(blocks[TYPE_FLAG_FIELD.0], PAX_HEADER_TYPE_FLAG);
+        assert!(blocks[TAR_BLOCK_SIZE as usize..].starts_with(&pax_record("size", b"8589934592")));
+        let ustar_block = &blocks[2 * TAR_BLOCK_SIZE as usize..];
+        assert_eq!(octal_field(ustar_block, SIZE_FIELD), max_octal(SIZE_FIELD));
+    }
+
+    #[test]
+    fn large_ids_are_stored_in_a_pax_header() {
+        let mut header = file_header(b"a", 0);
+        header.uid = 0o10000000;
+        let blocks = header.blocks();
+        assert!(blocks[TAR_BLOCK_SIZE as usize..].starts_with(&pax_record("uid", b"2097152")));
+        assert_eq!(octal_field(&blocks[2 * TAR_BLOCK_SIZE as usize..], UID_FIELD), max_octal(UID_FIELD));
+    }
+
+    #[test]
+    fn long_names_are_stored_in_a_pax_header() {
+        let path = [b"dir/".as_slice(), &[b'n'; 150]].concat();
+        let blocks = file_header(&path, 0).blocks();
+        assert_eq!(blocks.len() as u64, 3 * TAR_BLOCK_SIZE);
+        let pax_name = [PAX_HEADER_PREFIX, &[b'n'; 150]].concat();
+        assert_eq!(&blocks[..NAME_FIELD.1], &pax_name[..NAME_FIELD.1]);
+        assert!(blocks[TAR_BLOCK_SIZE as usize..].starts_with(&pax_record("path", &path)));
+        // the ustar header contains the truncated path.
+        let ustar_block = &blocks[2 * TAR_BLOCK_SIZE as usize..];
+        assert_eq!(&ustar_block[..NAME_FIELD.1], &path[..NAME_FIELD.1]);
+    }
+
+    #[test]
+    fn names_of_100_bytes_fit_into_the_ustar_header() {
+        let blocks = file_header(&[b'n'; 100], 0).blocks();
+        assert_eq!(blocks.len() as u64, TAR_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn padded_size_rounds_up_to_whole_blocks() {
+        assert_eq!(padded_size(0), 0);
+        assert_eq!(padded_size(1), TAR_BLOCK_SIZE);
+        assert_eq!(padded_size(TAR_BLOCK_SIZE), TAR_BLOCK_SIZE);
+        assert_eq!(padded_size(TAR_BLOCK_SIZE + 1), 2 * TAR_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn read_the_whole_archive() {
+        let layout = test_layout();
+        let expected = expected_archive(&layout);
+        assert_eq!(layout.size(), expected.len() as u64);
+        assert_eq!(read_archive_with(&layout, 0, layout.size() as u32, read_test_file).unwrap(), expected);
+    }
+
+    #[test]
+    fn read_the_archive_at_any_offset() {
+        let layout = test_layout();
+        let expected = expected_archive(&layout);
+        let second_entry = layout.entries[1].offset as usize;
+        // in the middle of a header, in the middle of the padding and across the boundary of two entries.
+        let ranges = [(100, 50), (TAR_BLOCK_SIZE as usize + 5 + 10, 100), (second_entry - 7, 20), (second_entry - 300, 2000)];
+        for (offset, size) in ranges {
+            let data = read_archive_with(&layout, offset as u64, size as u32, read_test_file).unwrap();
+            assert_eq!(data, &expected[offset..offset + size], "offset {offset}, size {size}");
+        }
+        for offset in (0..expected.len()).step_by(7) {
+            let data = read_archive_with(&layout, offset as u64, 700, read_test_file).unwrap();
+            assert_eq!(data, &expected[offset..min(offset + 700, expected.len())], "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn read_beyond_the_end_of_the_archive() {
+        let layout = test_layout();
+        assert!(read_archive_with(&layout, layout.size(), 512, read_test_file).unwrap().is_empty());
+        assert!(read_archive_with(&layout, u64::MAX, 512, read_test_file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_a_file_which_ends_too_early() {
+        let layout = test_layout();
+        let error = read_archive_with(&layout, 0, layout.size() as u32, |_, _, _| Ok(Cow::Owned(Vec::new())));
+        assert_eq!(error, Err(EIO));
+    }
+}
@@ -0,0 +1,104 @@
+// - STD
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// - internal
+use crate::constants::*;
+
+// - external
+use fuser::{FileAttr, FileType};
+
+// the indices of the timestamps in the compact attributes.
+const ATIME_INDEX: usize = 0;
+const MTIME_INDEX: usize = 1;
+const CTIME_INDEX: usize = 2;
+const CRTIME_INDEX: usize = 3;
+
+/// The attributes of a single inode, as they are stored in the attributes map.
+/// The fields, which are shared by all inodes (the inode itself, the owner, the block size and the flags) are not stored,
+/// but filled in by [CompactAttr::to_file_attr] while replying.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct CompactAttr {
+    pub size: u64,
+    pub blocks: u64,
+    // the seconds (negative before 1970-01-01) and the nanoseconds of the atime, mtime, ctime and crtime.
+    secs: [i64; 4],
+    nanos: [u32; 4],
+    pub nlink: u32,
+    pub rdev: u32,
+    pub perm: u16,
+    pub kind: FileType,
+}
+
+impl CompactAttr {
+    /// Returns the full attributes of the given inode with the given owner.
+    pub(crate) fn to_file_attr(&self, ino: u64, uid: u32, gid: u32) -> FileAttr {
+        FileAttr {
+            ino,
+            size: self.size,
+            blocks: self.blocks,
+            atime: self.time(ATIME_INDEX),
+            mtime: self.time(MTIME_INDEX),
+            ctime: self.time(CTIME_INDEX),
+            crtime: self.time(CRTIME_INDEX),
+            kind: self.kind,
+            perm: self.perm,
+            nlink: self.nlink,
+            uid,
+            gid,
+            rdev: self.rdev,
+            flags: 0,
+            blksize: DEFAULT_BLOCKSIZE,
+        }
+    }
+
+    /// Sets the size (and the appropriate number of blocks) of the file.
+    pub(crate) fn set_size(&mut self, size: u64) {
+        self.size = size;
+        self.blocks = size / DEFAULT_BLOCKSIZE as u64 + 1;
+    }
+
+    // returns the timestamp with the given index.
+    fn time(&self, index: usize) -> SystemTime {
+        let secs = self.secs[index];
+        let nanos = Duration::from_nanos(self.nanos[index] as u64);
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64) + nanos
+        } else {
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + nanos
+        }
+    }
+}
+
+impl From<&FileAttr> for CompactAttr {
+    fn from(attr: &FileAttr) -> Self {
+        let mut secs = [0; 4];
+        let mut nanos = [0; 4];
+        for (index, time) in [attr.atime, attr.mtime, attr.ctime, attr.crtime].into_iter().enumerate() {
+            (secs[index], nanos[index]) = split_time(time);
+        }
+        Self {
+            size: attr.size,
+            blocks: attr.blocks,
+            secs,
+            nanos,
+            nlink: attr.nlink,
+            rdev: attr.rdev,
+            perm: attr.perm,
+            kind: attr.kind,
+        }
+    }
+}
+
+// splits the given timestamp into the seconds since the epoch (rounded down) and the remaining nanoseconds.
+fn split_time(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => (duration.as_secs() as i64, duration.subsec_nanos()),
+        Err(e) => {
+            let duration = e.duration();
+            match duration.subsec_nanos() {
+                0 => (-(duration.as_secs() as i64), 0),
+                nanos => (-(duration.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        },
+    }
+}
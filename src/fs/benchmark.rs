@@ -0,0 +1,258 @@
+// - STD
+use std::cmp::min;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// - internal
+use crate::constants::*;
+use zff::{
+    Result,
+    footer::ObjectFooter,
+    io::zffreader::ZffReader,
+    ZffError,
+    ZffErrorKind,
+};
+
+// - external
+use log::info;
+use serde::Serialize;
+
+/// The configuration of the read benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    /// reads only the given number of bytes of each object sequentially (the whole object, if None).
+    pub size: Option<u64>,
+    /// the number of random reads per object.
+    pub random_reads: u64,
+}
+
+/// The measured values of a single read pattern (sequential or random).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadMeasurement {
+    pub bytes: u64,
+    pub reads: u64,
+    pub chunks: u64,
+    pub duration_ms: f64,
+    pub throughput_mb_per_s: f64,
+    pub average_latency_us: f64,
+}
+
+impl ReadMeasurement {
+    fn new(bytes: u64, reads: u64, chunks: u64, duration: Duration) -> Self {
+        let seconds = duration.as_secs_f64();
+        Self {
+            bytes,
+            reads,
+            chunks,
+            duration_ms: seconds * 1000.0,
+            throughput_mb_per_s: if seconds > 0.0 { bytes as f64 / 1_000_000.0 / seconds } else { 0.0 },
+            average_latency_us: if reads > 0 { seconds * 1_000_000.0 / reads as f64 } else { 0.0 },
+        }
+    }
+}
+
+/// The benchmark result of a single physical object.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>, // only set, if multiple containers are benchmarked.
+    pub object_number: u64,
+    pub sequential: ReadMeasurement,
+    pub random: ReadMeasurement,
+}
+
+/// The measured throughput of the cached metadata lookups of a container.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetadataMeasurement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>, // only set, if multiple containers are benchmarked.
+    pub getattr_lookups: u64,
+    pub getattr_lookups_per_s: f64,
+    pub name_lookups: u64,
+    pub name_lookups_per_s: f64,
+}
+
+impl MetadataMeasurement {
+    pub(crate) fn new(getattr_lookups: u64, getattr_duration: Duration, name_lookups: u64, name_duration: Duration) -> Self {
+        let per_second = |lookups: u64, duration: Duration| {
+            let seconds = duration.as_secs_f64();
+            if seconds > 0.0 { lookups as f64 / seconds } else { 0.0 }
+        };
+        Self {
+            container: None,
+            getattr_lookups,
+            getattr_lookups_per_s: per_second(getattr_lookups, getattr_duration),
+            name_lookups,
+            name_lookups_per_s: per_second(name_lookups, name_duration),
+        }
+    }
+}
+
+impl fmt::Display for MetadataMeasurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "METADATA ({}): getattr lookups={} ({:.0}/s) name lookups={} ({:.0}/s)",
+            self.container.as_deref().unwrap_or("-"),
+            self.getattr_lookups,
+            self.getattr_lookups_per_s,
+            self.name_lookups,
+            self.name_lookups_per_s)
+    }
+}
+
+/// The measured random read throughput of a single input file with the standard and the io_uring read path.
+#[derive(Debug, Clone, Serialize)]
+pub struct InputMeasurement {
+    pub path: String,
+    pub standard: ReadMeasurement,
+    pub uring: ReadMeasurement,
+}
+
+impl fmt::Display for InputMeasurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "INPUT ({}): standard {:.1} MB/s ({:.1} µs/read) io_uring {:.1} MB/s ({:.1} µs/read)",
+            self.path,
+            self.standard.throughput_mb_per_s,
+            self.standard.average_latency_us,
+            self.uring.throughput_mb_per_s,
+            self.uring.average_latency_us)
+    }
+}
+
+/// Prints the benchmark results as table.
+pub struct BenchmarkTable<'a>(pub &'a [BenchmarkResult]);
+
+impl fmt::Display for BenchmarkTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>12} | {:>8} | {:>10} | {:>10} | {:>12} | {:>11} | {:>10} | {:>12} | {:>12}",
+            "container", "object", "seq. MB", "seq. MB/s", "seq. avg µs", "seq. chunks", "rand. MB/s", "rand. avg µs", "rand. chunks")?;
+        for result in self.0 {
+            writeln!(f, "{:>12} | {:>8} | {:>10.1} | {:>10.1} | {:>12.1} | {:>11} | {:>10.1} | {:>12.1} | {:>12}",
+                result.container.as_deref().unwrap_or("-"),
+                result.object_number,
+                result.sequential.bytes as f64 / 1_000_000.0,
+                result.sequential.throughput_mb_per_s,
+                result.sequential.average_latency_us,
+                result.sequential.chunks,
+                result.random.throughput_mb_per_s,
+                result.random.average_latency_us,
+                result.random.chunks)?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the sequential and random read throughput of the given physical object.
+pub(crate) fn benchmark_object<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    config: &BenchmarkConfig) -> Result<BenchmarkResult> {
+    zffreader.set_active_object(object_number)?;
+    let chunk_size = zffreader.active_object_header_ref()?.chunk_size;
+    let length_of_data = match zffreader.active_object_footer()? {
+        ObjectFooter::Physical(footer) => footer.length_of_data,
+        _ => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, "logical")),
+    };
+
+    // sequential scan
+    info!("Benchmark: sequential read of object {object_number} ...");
+    let sequential_size = match config.size {
+        Some(size) => min(size, length_of_data),
+        None => length_of_data,
+    };
+    let mut buffer = vec![0u8; BENCHMARK_SEQUENTIAL_READ_SIZE];
+    let mut position = 0;
+    let mut reads = 0;
+    zffreader.rewind()?;
+    let start = Instant::now();
+    while position < sequential_size {
+        let to_read = min(BENCHMARK_SEQUENTIAL_READ_SIZE as u64, sequential_size - position) as usize;
+        zffreader.read_exact(&mut buffer[..to_read])?;
+        position += to_read as u64;
+        reads += 1;
+    }
+    let sequential = ReadMeasurement::new(
+        sequential_size,
+        reads,
+        chunks_of_range(0, sequential_size, chunk_size),
+        start.elapsed());
+
+    // random reads
+    info!("Benchmark: {} random reads of object {object_number} ...", config.random_reads);
+    let mut rng = XorShift::from_time();
+    let mut buffer = vec![0u8; BENCHMARK_RANDOM_READ_SIZE];
+    let mut bytes = 0;
+    let mut chunks = 0;
+    let mut duration = Duration::ZERO;
+    let max_offset = length_of_data.saturating_sub(BENCHMARK_RANDOM_READ_SIZE as u64);
+    for _ in 0..config.random_reads {
+        let offset = if max_offset > 0 { rng.next() % max_offset } else { 0 };
+        let to_read = min(BENCHMARK_RANDOM_READ_SIZE as u64, length_of_data - offset) as usize;
+        let start = Instant::now();
+        zffreader.seek(SeekFrom::Start(offset))?;
+        zffreader.read_exact(&mut buffer[..to_read])?;
+        duration += start.elapsed();
+        bytes += to_read as u64;
+        chunks += chunks_of_range(offset, to_read as u64, chunk_size);
+    }
+    let random = ReadMeasurement::new(bytes, config.random_reads, chunks, duration);
+
+    Ok(BenchmarkResult {
+        container: None,
+        object_number,
+        sequential,
+        random,
+    })
+}
+
+/// Measures the given number of random reads of the raw input file (without decompression or decryption).
+/// The offsets are generated from the given seed, so different read paths could be compared with the same offsets.
+#[cfg(feature = "uring")]
+pub(crate) fn benchmark_input_reads<R: Read + Seek>(reader: &mut R, random_reads: u64, seed: u64) -> std::io::Result<ReadMeasurement> {
+    let length = reader.seek(SeekFrom::End(0))?;
+    let mut rng = XorShift(seed | 1);
+    let mut buffer = vec![0u8; BENCHMARK_RANDOM_READ_SIZE];
+    let mut bytes = 0;
+    let max_offset = length.saturating_sub(BENCHMARK_RANDOM_READ_SIZE as u64);
+    let start = Instant::now();
+    for _ in 0..random_reads {
+        let offset = if max_offset > 0 { rng.next() % max_offset } else { 0 };
+        let to_read = min(BENCHMARK_RANDOM_READ_SIZE as u64, length - offset) as usize;
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut buffer[..to_read])?;
+        bytes += to_read as u64;
+    }
+    Ok(ReadMeasurement::new(bytes, random_reads, 0, start.elapsed()))
+}
+
+/// Returns a seed for the random offsets of the benchmark.
+#[cfg(feature = "uring")]
+pub(crate) fn benchmark_seed() -> u64 {
+    XorShift::from_time().0
+}
+
+// returns the number of chunks, which have to be read (and decompressed) to read the given range.
+fn chunks_of_range(offset: u64, length: u64, chunk_size: u64) -> u64 {
+    if length == 0 || chunk_size == 0 {
+        return 0;
+    }
+    (offset + length - 1) / chunk_size - offset / chunk_size + 1
+}
+
+// a simple xorshift pseudo random number generator. The random offsets don't need to be cryptographically secure.
+struct XorShift(u64);
+
+impl XorShift {
+    fn from_time() -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        // the state must not be zero.
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
@@ -0,0 +1,178 @@
+// - STD
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+// - external
+use zff::{Result, io::zffreader::ZffReader};
+
+/// The decompressed data of the chunks of a file (or a physical object) are stored under the chunk numbers
+/// first_chunk_number..first_chunk_number + number of chunks (the chunk numbers are unique in a container).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkRange {
+    pub first_chunk_number: u64,
+    pub chunk_size: u64,
+    pub length_of_data: u64,
+}
+
+impl ChunkRange {
+    /// Returns the number of chunks, which contain the data of the given range of the file.
+    pub(crate) fn chunks_of(&self, offset: u64, size: u64) -> u64 {
+        let end = min(offset.saturating_add(size), self.length_of_data);
+        if offset >= end || self.chunk_size == 0 {
+            return 0;
+        }
+        (end - 1) / self.chunk_size - offset / self.chunk_size + 1
+    }
+}
+
+/// A LRU cache of decompressed chunks, keyed by the canonical chunk number (the number of the original chunk of a deduplicated chunk).
+/// The size of the cache is limited by the total size of the cached chunks.
+#[derive(Debug)]
+pub(crate) struct ChunkCache {
+    capacity: u64,
+    // resolves the deduplicated chunks to their original chunks (only if the flags map is preloaded, as the flags of every
+    // chunk would have to be read from the container otherwise).
+    resolve_duplicates: bool,
+    size: u64,
+    // the chunk data and the stamp of the last access.
+    chunks: HashMap<u64, (Arc<[u8]>, u64)>,
+    // the chunk numbers in the order of their last access (the oldest first).
+    access_order: BTreeMap<u64, u64>,
+    next_stamp: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ChunkCache {
+    pub(crate) fn new(capacity: u64, resolve_duplicates: bool) -> Self {
+        Self {
+            capacity,
+            resolve_duplicates,
+            size: 0,
+            chunks: HashMap::new(),
+            access_order: BTreeMap::new(),
+            next_stamp: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of hits and misses of the cache lookups.
+    pub(crate) fn lookups(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    // returns the number of the chunk, under which the data of the given chunk is cached. The content of a deduplicated chunk
+    // is the content of its original chunk, so both chunks share a single cache entry.
+    fn key<R: Read + Seek>(&self, zffreader: &mut ZffReader<R>, chunk_number: u64) -> Result<u64> {
+        if self.resolve_duplicates && zffreader.get_chunk_flags(chunk_number)?.duplicate {
+            zffreader.get_chunk_deduplication(chunk_number)
+        } else {
+            Ok(chunk_number)
+        }
+    }
+
+    fn get(&mut self, chunk_number: u64) -> Option<Arc<[u8]>> {
+        let stamp = self.next_stamp;
+        match self.chunks.get_mut(&chunk_number) {
+            Some((data, last_access)) => {
+                self.access_order.remove(last_access);
+                self.access_order.insert(stamp, chunk_number);
+                *last_access = stamp;
+                self.next_stamp += 1;
+                self.hits += 1;
+                Some(Arc::clone(data))
+            },
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    // chunks, which are larger than the whole cache, will not be cached.
+    fn insert(&mut self, chunk_number: u64, data: Arc<[u8]>) {
+        let length = data.len() as u64;
+        if length > self.capacity {
+            return;
+        }
+        while self.size + length > self.capacity {
+            let (_, oldest) = match self.access_order.pop_first() {
+                Some(entry) => entry,
+                None => break,
+            };
+            if let Some((data, _)) = self.chunks.remove(&oldest) {
+                self.size -= data.len() as u64;
+            }
+        }
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.access_order.insert(stamp, chunk_number);
+        if let Some((previous, last_access)) = self.chunks.insert(chunk_number, (data, stamp)) {
+            self.access_order.remove(&last_access);
+            self.size -= previous.len() as u64;
+        }
+        self.size += length;
+    }
+}
+
+impl fmt::Display for ChunkCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let requests = self.hits + self.misses;
+        let hit_ratio = if requests == 0 { 0.0 } else { self.hits as f64 * 100.0 / requests as f64 };
+        write!(f, "CHUNK CACHE: hits={} misses={} hit_ratio={hit_ratio:.1}% cached_chunks={} cached_bytes={}",
+            self.hits, self.misses, self.chunks.len(), self.size)
+    }
+}
+
+/// Reads the given range of the active file (or physical object) of the reader chunk by chunk, using the cached chunks.
+pub(crate) fn read_cached<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    cache: &mut ChunkCache,
+    range: ChunkRange,
+    offset: u64,
+    size: u32) -> Result<Vec<u8>> {
+    let end = min(offset.saturating_add(size as u64), range.length_of_data);
+    let mut data = Vec::with_capacity(end.saturating_sub(offset) as usize);
+    let mut position = offset;
+    while position < end {
+        let index = position / range.chunk_size;
+        let chunk_start = index * range.chunk_size;
+        let key = cache.key(zffreader, range.first_chunk_number + index)?;
+        let chunk_length = min(range.chunk_size, range.length_of_data - chunk_start) as usize;
+        let chunk = match cache.get(key) {
+            Some(chunk) if chunk.len() == chunk_length => chunk,
+            _ => {
+                let mut chunk = vec![0; chunk_length];
+                zffreader.seek(SeekFrom::Start(chunk_start))?;
+                zffreader.read_exact(&mut chunk)?;
+                let chunk: Arc<[u8]> = chunk.into();
+                cache.insert(key, Arc::clone(&chunk));
+                chunk
+            }
+        };
+        let chunk_offset = (position - chunk_start) as usize;
+        let length = min(chunk.len() - chunk_offset, (end - position) as usize);
+        data.extend_from_slice(&chunk[chunk_offset..chunk_offset + length]);
+        position += length as u64;
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_of_a_range() {
+        let range = ChunkRange { first_chunk_number: 1, chunk_size: 100, length_of_data: 250 };
+        assert_eq!(range.chunks_of(0, 100), 1);
+        assert_eq!(range.chunks_of(50, 100), 2);
+        assert_eq!(range.chunks_of(0, 1000), 3);
+        assert_eq!(range.chunks_of(250, 10), 0);
+        assert_eq!(range.chunks_of(10, 0), 0);
+    }
+}
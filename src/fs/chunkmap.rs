@@ -0,0 +1,274 @@
+// - STD
+use std::cmp::{min, max};
+use std::io::{Read, Seek};
+
+// - internal
+use crate::constants::*;
+use zff::{
+    Result,
+    header::ChunkFlags,
+    footer::ObjectFooter,
+    io::zffreader::ZffReader,
+    ZffError,
+    ZffErrorKind,
+};
+
+/// The information about a single chunk, which is needed by zffmount.
+/// The ZffReader uses the preloaded chunkmaps (if available) or reads the appropriate chunk header on demand.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkInfo {
+    pub flags: ChunkFlags,
+    pub compressed_size: u64,
+}
+
+pub(crate) fn chunk_info<R: Read + Seek>(zffreader: &mut ZffReader<R>, chunk_number: u64) -> Result<ChunkInfo> {
+    let flags = zffreader.get_chunk_flags(chunk_number)?;
+    let compressed_size = zffreader.get_chunk_size(chunk_number)?;
+    Ok(ChunkInfo {
+        flags,
+        compressed_size,
+    })
+}
+
+/// Returns the byte of the given chunk, if it is a samebytes chunk (i.e. the chunk only contains this byte).
+pub(crate) fn samebyte_of_chunk<R: Read + Seek>(zffreader: &mut ZffReader<R>, chunk_number: u64) -> Result<Option<u8>> {
+    if !zffreader.get_chunk_flags(chunk_number)?.same_bytes {
+        return Ok(None);
+    }
+    Ok(Some(zffreader.get_chunk_samebytes(chunk_number)?))
+}
+
+/// Returns the byte ranges (start, exclusive end) of the given data, which are stored in chunks with the error flag
+/// (i.e. the chunks could not be read while acquiring the data). Adjacent ranges are merged.
+pub(crate) fn damaged_ranges<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    first_chunk_number: u64,
+    chunk_size: u64,
+    length_of_data: u64) -> Result<Vec<(u64, u64)>> {
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    if chunk_size == 0 {
+        return Ok(ranges);
+    }
+    let number_of_chunks = (length_of_data + chunk_size - 1) / chunk_size;
+    for index in 0..number_of_chunks {
+        if !zffreader.get_chunk_flags(first_chunk_number + index)?.error {
+            continue;
+        }
+        let start = index * chunk_size;
+        let end = min(start + chunk_size, length_of_data);
+        match ranges.last_mut() {
+            Some((_, last_end)) if *last_end == start => *last_end = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    Ok(ranges)
+}
+
+/// Returns true, if the given chunk only contains zeros (a samebytes chunk with the byte 0x00).
+pub(crate) fn chunk_is_zero<R: Read + Seek>(zffreader: &mut ZffReader<R>, chunk_number: u64) -> Result<bool> {
+    Ok(samebyte_of_chunk(zffreader, chunk_number)? == Some(0))
+}
+
+/// Returns the offset of the next hole (if find_hole is true) or of the next data (if find_hole is false),
+/// starting at the given offset. Runs of zero chunks are handled as holes, the end of the data is an implicit hole.
+/// Returns None, if there is no more data after the given offset.
+pub(crate) fn seek_hole_or_data<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    layout: &ChunkmapLayout,
+    offset: u64,
+    find_hole: bool) -> Result<Option<u64>> {
+    find_hole_or_data(layout, offset, find_hole, |chunk_number| chunk_is_zero(zffreader, chunk_number))
+}
+
+// searches the next hole or data by using the given zero chunk lookup.
+fn find_hole_or_data<F: FnMut(u64) -> Result<bool>>(
+    layout: &ChunkmapLayout,
+    offset: u64,
+    find_hole: bool,
+    mut is_zero: F) -> Result<Option<u64>> {
+    if offset >= layout.length_of_data {
+        return Ok(None);
+    }
+    let mut index = offset / layout.chunk_size;
+    while index < layout.number_of_chunks {
+        if is_zero(layout.first_chunk_number + index)? == find_hole {
+            return Ok(Some(max(offset, index * layout.chunk_size)));
+        }
+        index += 1;
+    }
+    if find_hole {
+        Ok(Some(layout.length_of_data))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the number of bytes of the given chunk range, which are not located in zero chunks.
+pub(crate) fn allocated_bytes<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    first_chunk_number: u64,
+    number_of_chunks: u64,
+    chunk_size: u64,
+    length_of_data: u64) -> Result<u64> {
+    let mut allocated_bytes = 0;
+    for index in 0..number_of_chunks {
+        if !chunk_is_zero(zffreader, first_chunk_number + index)? {
+            let start_offset = index * chunk_size;
+            allocated_bytes += min(chunk_size, length_of_data.saturating_sub(start_offset));
+        }
+    }
+    Ok(allocated_bytes)
+}
+
+/// The layout of the virtual chunkmap csv file of a physical object.
+/// All rows have the same length, so the rows of a read request can be generated directly by using the offset,
+/// without generating the whole file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct ChunkmapLayout {
+    pub first_chunk_number: u64,
+    pub number_of_chunks: u64,
+    pub chunk_size: u64,
+    pub length_of_data: u64,
+    chunk_number_width: usize,
+    offset_width: usize,
+    size_width: usize,
+}
+
+impl ChunkmapLayout {
+    pub(crate) fn new(first_chunk_number: u64, number_of_chunks: u64, chunk_size: u64, length_of_data: u64) -> Self {
+        let last_chunk_number = first_chunk_number + number_of_chunks.saturating_sub(1);
+        Self {
+            first_chunk_number,
+            number_of_chunks,
+            chunk_size,
+            length_of_data,
+            chunk_number_width: last_chunk_number.to_string().len(),
+            offset_width: length_of_data.to_string().len(),
+            size_width: chunk_size.to_string().len() + 1, // + 1 as the stored chunk could be slightly larger than the chunk size.
+        }
+    }
+
+    fn row_len(&self) -> u64 {
+        // chunk number, start offset, end offset, 4 flags, compressed size - each followed by a comma or the newline.
+        (self.chunk_number_width + 2 * self.offset_width + 4 + self.size_width + 8) as u64
+    }
+
+    /// Returns the exact size of the virtual chunkmap file.
+    pub(crate) fn size(&self) -> u64 {
+        CHUNKMAP_CSV_HEADER.len() as u64 + self.number_of_chunks * self.row_len()
+    }
+
+    fn row(&self, index: u64, chunk_info: &ChunkInfo) -> String {
+        let start_offset = index * self.chunk_size;
+        let end_offset = min(start_offset + self.chunk_size, self.length_of_data);
+        format!("{:0cw$},{:0ow$},{:0ow$},{},{},{},{},{:0sw$}\n",
+            self.first_chunk_number + index,
+            start_offset,
+            end_offset,
+            chunk_info.flags.same_bytes as u8,
+            chunk_info.flags.duplicate as u8,
+            chunk_info.flags.encryption as u8,
+            chunk_info.flags.error as u8,
+            chunk_info.compressed_size,
+            cw = self.chunk_number_width,
+            ow = self.offset_width,
+            sw = self.size_width)
+    }
+}
+
+/// Returns the chunkmap layout of the given physical object.
+pub(crate) fn chunkmap_layout_of_object<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64) -> Result<ChunkmapLayout> {
+    zffreader.set_active_object(object_number)?;
+    let chunk_size = zffreader.active_object_header_ref()?.chunk_size;
+    match zffreader.active_object_footer()? {
+        ObjectFooter::Physical(footer) => Ok(ChunkmapLayout::new(
+            footer.first_chunk_number,
+            footer.number_of_chunks,
+            chunk_size,
+            footer.length_of_data)),
+        _ => Err(ZffError::new(ZffErrorKind::MismatchObjectType, "logical")),
+    }
+}
+
+/// Generates the requested range of the virtual chunkmap file of the given object.
+pub(crate) fn read_chunkmap<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    layout: &ChunkmapLayout,
+    offset: u64,
+    size: u32) -> Result<Vec<u8>> {
+    let header = CHUNKMAP_CSV_HEADER.as_bytes();
+    let header_len = header.len() as u64;
+    let row_len = layout.row_len();
+    let end = min(offset + size as u64, layout.size());
+
+    let mut buffer = Vec::with_capacity(end.saturating_sub(offset) as usize);
+    let mut position = offset;
+    if position < header_len {
+        let header_end = min(header_len, end);
+        buffer.extend_from_slice(&header[position as usize..header_end as usize]);
+        position = header_end;
+    }
+    while position < end {
+        let index = (position - header_len) / row_len;
+        let row_start = header_len + index * row_len;
+        let chunk_info = chunk_info(zffreader, layout.first_chunk_number + index)?;
+        let row = layout.row(index, &chunk_info);
+        let from = position - row_start;
+        let to = min(row_len, end - row_start);
+        buffer.extend_from_slice(&row.as_bytes()[from as usize..to as usize]);
+        position = row_start + to;
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a sparse object of 6 chunks of 4096 bytes (the last one is partial): data, zero, zero, data, zero, data.
+    const CHUNK_SIZE: u64 = 4096;
+    const FIRST_CHUNK_NUMBER: u64 = 10;
+    const ZERO_CHUNKS: [bool; 6] = [false, true, true, false, true, false];
+
+    fn sparse_layout() -> ChunkmapLayout {
+        ChunkmapLayout::new(FIRST_CHUNK_NUMBER, ZERO_CHUNKS.len() as u64, CHUNK_SIZE, 5 * CHUNK_SIZE + 100)
+    }
+
+    fn seek(offset: u64, find_hole: bool) -> Option<u64> {
+        find_hole_or_data(&sparse_layout(), offset, find_hole, |chunk_number| Ok(ZERO_CHUNKS[(chunk_number - FIRST_CHUNK_NUMBER) as usize])).unwrap()
+    }
+
+    #[test]
+    fn seek_hole_finds_the_next_zero_chunk() {
+        assert_eq!(seek(0, true), Some(CHUNK_SIZE));
+        assert_eq!(seek(CHUNK_SIZE + 10, true), Some(CHUNK_SIZE + 10));
+        assert_eq!(seek(3 * CHUNK_SIZE, true), Some(4 * CHUNK_SIZE));
+    }
+
+    #[test]
+    fn seek_hole_returns_the_end_of_data_after_the_last_hole() {
+        assert_eq!(seek(5 * CHUNK_SIZE, true), Some(5 * CHUNK_SIZE + 100));
+    }
+
+    #[test]
+    fn seek_data_finds_the_next_data_chunk() {
+        assert_eq!(seek(0, false), Some(0));
+        assert_eq!(seek(CHUNK_SIZE, false), Some(3 * CHUNK_SIZE));
+        assert_eq!(seek(4 * CHUNK_SIZE + 1, false), Some(5 * CHUNK_SIZE));
+    }
+
+    #[test]
+    fn seek_beyond_the_data_returns_none() {
+        assert_eq!(seek(5 * CHUNK_SIZE + 100, true), None);
+        assert_eq!(seek(5 * CHUNK_SIZE + 100, false), None);
+    }
+
+    #[test]
+    fn seek_data_in_a_trailing_hole_returns_none() {
+        let layout = ChunkmapLayout::new(0, 2, CHUNK_SIZE, 2 * CHUNK_SIZE);
+        let result = find_hole_or_data(&layout, 10, false, |chunk_number| Ok(chunk_number == 1)).unwrap();
+        assert_eq!(result, Some(10));
+        let result = find_hole_or_data(&layout, CHUNK_SIZE, false, |chunk_number| Ok(chunk_number == 1)).unwrap();
+        assert_eq!(result, None);
+    }
+}
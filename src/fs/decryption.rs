@@ -0,0 +1,60 @@
+// - STD
+use std::fmt;
+
+/// The source of the password, which was used to decrypt an object.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum PasswordSource {
+    /// The password was given by --decryption-passwords.
+    Flag,
+    /// The password was entered at the password prompt.
+    Prompt,
+}
+
+impl fmt::Display for PasswordSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasswordSource::Flag => write!(f, "--decryption-passwords"),
+            PasswordSource::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+/// The result of the decryption of a single encrypted object.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct DecryptionOutcome {
+    pub object_number: u64,
+    pub source: Option<PasswordSource>, // None, if no password was given.
+    pub decrypted: bool,
+}
+
+/// The results of the decryption of all encrypted objects, which are printed at the end of the initialization.
+pub(crate) struct DecryptionSummary<'a>(pub &'a [DecryptionOutcome]);
+
+impl DecryptionSummary<'_> {
+    /// Returns the object numbers of the objects, which could not be decrypted.
+    pub(crate) fn locked_objects(&self) -> Vec<u64> {
+        self.0.iter().filter(|outcome| !outcome.decrypted).map(|outcome| outcome.object_number).collect()
+    }
+}
+
+impl fmt::Display for DecryptionSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decrypted = self.0.iter().filter(|outcome| outcome.decrypted).count();
+        write!(f, "Decryption summary: {decrypted} of {} encrypted objects unlocked.", self.0.len())?;
+        for outcome in self.0 {
+            let state = if outcome.decrypted { "unlocked" } else { "LOCKED" };
+            let source = match outcome.source {
+                Some(source) => source.to_string(),
+                None => String::from("no password"),
+            };
+            write!(f, "\n  object {:<6} {state:<8} (password source: {source})", outcome.object_number)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true, if stdin is connected to a terminal (so the user could be asked for passwords).
+pub(crate) fn stdin_is_tty() -> bool {
+    // Safety: isatty only checks the given file descriptor.
+    unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
+}
@@ -0,0 +1,520 @@
+// - STD
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// - internal
+use super::ZffFs;
+use super::chunkmap::samebyte_of_chunk;
+use crate::constants::*;
+use crate::error::ZffMountError;
+use zff::{
+    footer::ObjectFooter,
+    header::HashValue,
+    hashing::{Hash, HashType},
+};
+
+// - external
+use log::{debug, error, info, warn};
+use digest::DynDigest;
+use fuser::{FileAttr, FileType};
+use nix::sys::stat::{utimensat, UtimensatFlags};
+use nix::sys::time::TimeSpec;
+use serde::Serialize;
+
+/// The handling of the entries, which already exist in the destination directory.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ConflictPolicy {
+    /// The existing entry is kept and the conflict is counted as error.
+    #[default]
+    Error,
+    /// The existing entry is kept.
+    Skip,
+    /// The existing file is replaced. Existing directories are merged.
+    Overwrite,
+    /// The entry is extracted with a numbered suffix (e.g. report.pdf.1).
+    Rename,
+}
+
+/// The options of an extraction.
+#[derive(Debug, Default, Clone)]
+pub struct ExtractOptions {
+    pub on_conflict: ConflictPolicy,
+    /// The hash values of the extracted files, which are computed while writing the files.
+    pub hash_types: Vec<HashType>,
+    /// The zero chunks of the physical objects are not written, so the images are created as sparse files.
+    pub sparse: bool,
+    /// The hash values of the extracted files (and images) are compared with the hash values stored in the container.
+    /// The stored hash types are computed in addition to the given hash types.
+    pub verify: bool,
+}
+
+/// The statistics of an extraction.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct ExtractStats {
+    pub directories: u64,
+    pub files: u64,
+    pub symlinks: u64,
+    pub bytes: u64,
+    /// The entries, which were not extracted (e.g. device files, sockets or existing entries).
+    pub skipped: u64,
+    /// The entries, which could not be extracted due to an error.
+    pub errors: u64,
+}
+
+impl fmt::Display for ExtractStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} directories, {} files ({} bytes), {} symlinks, {} skipped, {} errors",
+            self.directories, self.files, self.bytes, self.symlinks, self.skipped, self.errors)
+    }
+}
+
+/// The hash values of a single extracted file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExtractedFileHashes {
+    /// The path of the file, relative to the destination directory.
+    pub path: PathBuf,
+    pub hashes: Vec<(HashType, Vec<u8>)>,
+}
+
+/// The result of the comparison of the hash values of a single extracted file with the stored ones.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Match,
+    Mismatch,
+    /// The container contains no hash value of the file.
+    NoStoredHash,
+}
+
+/// The comparison of a single stored hash value with the computed one.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct VerifiedHash {
+    pub hash_type: String,
+    pub stored: String,
+    pub computed: String,
+}
+
+/// The verification of a single extracted file (or image of a physical object).
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct FileVerification {
+    /// The path of the file, relative to the destination directory.
+    pub path: String,
+    pub object: Option<u64>,
+    /// The file number in the logical object (None for the image of a physical object).
+    pub file_number: Option<u64>,
+    pub status: VerificationStatus,
+    pub hashes: Vec<VerifiedHash>,
+}
+
+/// The number of the verified files by their result.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct VerificationSummary {
+    pub matches: u64,
+    pub mismatches: u64,
+    pub without_stored_hash: u64,
+}
+
+impl fmt::Display for VerificationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} matches, {} mismatches, {} files without stored hash values", self.matches, self.mismatches, self.without_stored_hash)
+    }
+}
+
+/// The verification report of an extraction. Display prints the report as text, the JSON report is serialized.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationReport<'a> {
+    pub summary: VerificationSummary,
+    pub files: &'a [FileVerification],
+}
+
+impl fmt::Display for VerificationReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Verification of the extracted files")?;
+        writeln!(f, "Result: {}", if self.summary.mismatches == 0 { "successful" } else { "failed" })?;
+        writeln!(f, "Summary: {}", self.summary)?;
+        for file in self.files {
+            writeln!(f)?;
+            let status = match file.status {
+                VerificationStatus::Match => "match",
+                VerificationStatus::Mismatch => "MISMATCH",
+                VerificationStatus::NoStoredHash => "no stored hash",
+            };
+            writeln!(f, "{} ({status})", file.path)?;
+            for hash in &file.hashes {
+                writeln!(f, "  {}", hash.hash_type)?;
+                writeln!(f, "    stored:   {}", hash.stored)?;
+                writeln!(f, "    computed: {}", hash.computed)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The result of an extraction.
+#[derive(Debug, Default, Clone)]
+pub struct ExtractReport {
+    pub stats: ExtractStats,
+    pub files: Vec<ExtractedFileHashes>,
+    /// The verified files (only filled, if the extraction was verified).
+    pub verification: Vec<FileVerification>,
+}
+
+impl ExtractReport {
+    /// Writes the hash values of the given type of all extracted files to the given manifest file, in the format of the
+    /// coreutils (e.g. sha256sum -c), with the paths relative to the destination directory.
+    pub fn write_manifest(&self, hash_type: &HashType, path: &Path) -> io::Result<()> {
+        let mut manifest = io::BufWriter::new(File::create(path)?);
+        for file in &self.files {
+            if let Some((_, hash)) = file.hashes.iter().find(|(file_hash_type, _)| file_hash_type == hash_type) {
+                manifest.write_all(hex::encode(hash).as_bytes())?;
+                manifest.write_all(b"  ")?;
+                manifest.write_all(file.path.as_os_str().as_bytes())?;
+                manifest.write_all(b"\n")?;
+            }
+        }
+        manifest.flush()
+    }
+
+    /// Returns the verification report of the extracted files.
+    pub fn verification_report(&self) -> VerificationReport<'_> {
+        let mut summary = VerificationSummary::default();
+        for file in &self.verification {
+            match file.status {
+                VerificationStatus::Match => summary.matches += 1,
+                VerificationStatus::Mismatch => summary.mismatches += 1,
+                VerificationStatus::NoStoredHash => summary.without_stored_hash += 1,
+            }
+        }
+        VerificationReport { summary, files: &self.verification }
+    }
+}
+
+// the state of a running extraction.
+struct Extraction<'a> {
+    options: &'a ExtractOptions,
+    root: PathBuf,
+    report: ExtractReport,
+}
+
+impl<R: Read + Seek> ZffFs<R> {
+    /// Extracts the whole filesystem (the same tree, which would be presented at the mount point) to the given destination
+    /// directory, instead of mounting it. The data is read by the same read path as the mounted filesystem. The modification
+    /// and access times are preserved. The virtual files (e.g. the verification files) are not extracted.
+    /// The errors of single entries are logged and counted in the statistics, so as much as possible is extracted.
+    pub fn extract_all(&mut self, destination: &Path, options: &ExtractOptions) -> Result<ExtractReport, ZffMountError> {
+        let root_inode = self.local_root_inode();
+        let mut extraction = Extraction::new(destination, options)?;
+        info!("Extracting the filesystem to {} ...", destination.display());
+        self.extract_directory(root_inode, destination, &mut extraction);
+        Ok(extraction.report)
+    }
+
+    /// Extracts the file or directory at the given path of the given object to the given destination directory
+    /// (e.g. /Users/bob/Documents is extracted to <destination>/Documents). The path is resolved by the lookup tables of the
+    /// filesystem, so the presented (e.g. sanitized or renamed) names are used. The root path (/) extracts the content of the object.
+    pub fn extract_path(&mut self, object_number: u64, path: &Path, destination: &Path, options: &ExtractOptions) -> Result<ExtractReport, ZffMountError> {
+        let inode = self.resolve_path(object_number, path)?;
+        let mut extraction = Extraction::new(destination, options)?;
+        let name = match path.file_name() {
+            Some(name) if inode != object_number + 1 => name,
+            _ => {
+                info!("Extracting object {object_number} to {} ...", destination.display());
+                self.extract_directory(inode, destination, &mut extraction);
+                return Ok(extraction.report);
+            },
+        };
+        let file_type = self.file_attr(inode)
+            .ok_or_else(|| ZffMountError::PathNotFound { object: object_number, path: path.to_path_buf() })?
+            .kind;
+        info!("Extracting {} of object {object_number} to {} ...", path.display(), destination.display());
+        self.extract_entry(inode, file_type, &destination.join(name), &mut extraction);
+        Ok(extraction.report)
+    }
+
+    // extracts the content of the given directory to the given (existing) destination directory.
+    fn extract_directory(&mut self, ino: u64, destination: &Path, extraction: &mut Extraction) {
+        let entries = match self.directory_entries(ino) {
+            Ok(entries) => entries,
+            Err(errno) => {
+                error!("Could not list the directory {}: {}", destination.display(), io::Error::from_raw_os_error(errno));
+                extraction.report.stats.errors += 1;
+                return;
+            }
+        };
+        for (inode, file_type, name) in entries {
+            if *name == *CURRENT_DIR || *name == *PARENT_DIR {
+                continue;
+            }
+            if self.cache.virtual_files.contains_key(&inode) {
+                debug!("Skipping the virtual file {:?}.", name);
+                continue;
+            }
+            // the name of an entry must not escape the destination directory.
+            if name.as_bytes().contains(&b'/') {
+                warn!("Skipping {:?}, as the name contains a slash.", name);
+                extraction.report.stats.skipped += 1;
+                continue;
+            }
+            self.extract_entry(inode, file_type, &destination.join(&*name), extraction);
+        }
+    }
+
+    // extracts a single entry (and the content of directories) to the given path. The errors are logged and counted.
+    fn extract_entry(&mut self, ino: u64, file_type: FileType, path: &Path, extraction: &mut Extraction) {
+        let path = match extraction.resolve_conflict(path, file_type) {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                debug!("Skipping the existing entry {}.", path.display());
+                extraction.report.stats.skipped += 1;
+                return;
+            },
+            Err(e) => {
+                error!("Could not extract {}: {e}", path.display());
+                extraction.report.stats.errors += 1;
+                return;
+            }
+        };
+        if let Err(e) = self.extract_new_entry(ino, file_type, &path, extraction) {
+            error!("Could not extract {}: {e}", path.display());
+            extraction.report.stats.errors += 1;
+        }
+    }
+
+    fn extract_new_entry(&mut self, ino: u64, file_type: FileType, path: &Path, extraction: &mut Extraction) -> io::Result<()> {
+        let attr = self.file_attr(ino);
+        match file_type {
+            FileType::Directory => {
+                if !path.is_dir() {
+                    fs::create_dir(path)?;
+                }
+                self.extract_directory(ino, path, extraction);
+                extraction.report.stats.directories += 1;
+            },
+            FileType::RegularFile => {
+                let size = attr.map(|attr| attr.size).unwrap_or_default();
+                let stored_hashes = if extraction.options.verify { self.stored_hashes(ino) } else { Vec::new() };
+                let mut hash_types = extraction.options.hash_types.clone();
+                for hash_value in &stored_hashes {
+                    if !hash_types.contains(hash_value.hash_type()) {
+                        hash_types.push(hash_value.hash_type().clone());
+                    }
+                }
+                // the hash values are computed while the data is written, so the data is read only once.
+                let mut hashers: Vec<(HashType, Box<dyn DynDigest>)> = hash_types.iter()
+                    .map(|hash_type| (hash_type.clone(), Hash::new_hasher(hash_type)))
+                    .collect();
+                extraction.report.stats.bytes += self.extract_file(ino, size, path, extraction.options.sparse, &mut hashers)?;
+                extraction.report.stats.files += 1;
+                let relative_path = path.strip_prefix(&extraction.root).unwrap_or(path).to_path_buf();
+                let hashes: Vec<(HashType, Vec<u8>)> = hashers.into_iter()
+                    .map(|(hash_type, hasher)| (hash_type, hasher.finalize().to_vec()))
+                    .collect();
+                if extraction.options.verify {
+                    let verification = self.verify_file(ino, &relative_path, &stored_hashes, &hashes);
+                    if verification.status == VerificationStatus::Mismatch {
+                        error!("The hash values of {} do not match the stored hash values.", path.display());
+                    }
+                    extraction.report.verification.push(verification);
+                }
+                if !hashes.is_empty() {
+                    extraction.report.files.push(ExtractedFileHashes { path: relative_path, hashes });
+                }
+            },
+            FileType::Symlink => {
+                let target = self.symlink_target(ino).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+                symlink(OsStr::from_bytes(&target), path)?;
+                extraction.report.stats.symlinks += 1;
+            },
+            _ => {
+                warn!("Skipping {}, as {file_type:?} entries are not extracted.", path.display());
+                extraction.report.stats.skipped += 1;
+                return Ok(());
+            },
+        }
+        // the times of a directory are set after its content was extracted, which would change them otherwise.
+        if let Some(attr) = attr {
+            if let Err(e) = set_times(path, &attr) {
+                warn!("Could not set the times of {}: {e}", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    // copies the data of the given file to the given path, feeds the given hashers and returns the number of written bytes.
+    // If sparse is set, the zero chunks of a physical object are skipped instead of written.
+    fn extract_file(&mut self, ino: u64, size: u64, path: &Path, sparse: bool, hashers: &mut [(HashType, Box<dyn DynDigest>)]) -> io::Result<u64> {
+        let mut file = File::create(path)?;
+        let sparse_chunks = if sparse { self.physical_chunk_range(ino) } else { None };
+        let buffer_size = match sparse_chunks {
+            // the zero chunks are detected chunkwise.
+            Some((_, chunk_size)) => chunk_size.clamp(1, EXTRACT_BUFFER_SIZE as u64),
+            None => EXTRACT_BUFFER_SIZE as u64,
+        };
+        let mut position = 0;
+        while position < size {
+            let length = (size - position).min(buffer_size) as u32;
+            if let Some((first_chunk_number, chunk_size)) = sparse_chunks {
+                let chunk_number = first_chunk_number + position / chunk_size;
+                let whole_chunk = position % chunk_size == 0 && length as u64 == chunk_size.min(size - position);
+                if whole_chunk && matches!(samebyte_of_chunk(&mut self.zffreader, chunk_number), Ok(Some(0))) {
+                    for (_, hasher) in hashers.iter_mut() {
+                        hasher.update(&vec![0; length as usize]);
+                    }
+                    file.seek(SeekFrom::Current(length as i64))?;
+                    position += length as u64;
+                    continue;
+                }
+            }
+            let data = self.read_data(ino, position, length).map_err(io::Error::from_raw_os_error)?;
+            let data = &data[..data.len().min(length as usize)];
+            if data.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("the data ends at offset {position} of {size} bytes")));
+            }
+            for (_, hasher) in hashers.iter_mut() {
+                hasher.update(data);
+            }
+            file.write_all(data)?;
+            position += data.len() as u64;
+        }
+        // the skipped zero chunks at the end of the file.
+        file.set_len(position)?;
+        Ok(position)
+    }
+
+    // returns the hash values of the given file (or image of a physical object), which are stored in the container.
+    fn stored_hashes(&mut self, ino: u64) -> Vec<HashValue> {
+        let (object_number, file_number) = match self.cache.inode_reverse_map.get(&ino) {
+            Some(entry) => *entry,
+            None => return Vec::new(),
+        };
+        let stored_hashes = self.zffreader.set_active_object(object_number).and_then(|_| {
+            if file_number == 0 {
+                match self.zffreader.active_object_footer()? {
+                    ObjectFooter::Physical(footer) => Ok(footer.hash_header.hashes),
+                    _ => Ok(Vec::new()),
+                }
+            } else {
+                self.zffreader.set_active_file(file_number)?;
+                Ok(self.zffreader.current_filefooter()?.hash_header.hashes)
+            }
+        });
+        match stored_hashes {
+            Ok(stored_hashes) => stored_hashes,
+            Err(e) => {
+                warn!(object = object_number, inode = ino; "Could not read the stored hash values of inode {ino}.");
+                debug!("{e}");
+                Vec::new()
+            }
+        }
+    }
+
+    // compares the computed hash values of the given file with the stored ones.
+    fn verify_file(&self, ino: u64, path: &Path, stored_hashes: &[HashValue], computed_hashes: &[(HashType, Vec<u8>)]) -> FileVerification {
+        let mut hashes = Vec::new();
+        let mut status = VerificationStatus::NoStoredHash;
+        for stored in stored_hashes {
+            let computed = match computed_hashes.iter().find(|(hash_type, _)| hash_type == stored.hash_type()) {
+                Some((_, computed)) => computed,
+                None => continue,
+            };
+            if *computed == stored.hash().to_vec() {
+                if status == VerificationStatus::NoStoredHash {
+                    status = VerificationStatus::Match;
+                }
+            } else {
+                status = VerificationStatus::Mismatch;
+            }
+            hashes.push(VerifiedHash {
+                hash_type: stored.hash_type().to_string(),
+                stored: hex::encode(stored.hash()),
+                computed: hex::encode(computed),
+            });
+        }
+        let (object, file_number) = match self.cache.inode_reverse_map.get(&ino) {
+            Some((object_number, file_number)) => (Some(*object_number), Some(*file_number).filter(|file_number| *file_number > 0)),
+            None => (None, None),
+        };
+        FileVerification {
+            path: path.to_string_lossy().into_owned(),
+            object,
+            file_number,
+            status,
+            hashes,
+        }
+    }
+
+    // returns the first chunk number and the chunk size of the given physical object file.
+    fn physical_chunk_range(&self, ino: u64) -> Option<(u64, u64)> {
+        match self.cache.inode_reverse_map.get(&ino) {
+            Some((object_number, 0)) => self.cache.physical_objects.get(object_number)
+                .filter(|physical_object| physical_object.chunk_size > 0)
+                .map(|physical_object| (physical_object.first_chunk_number, physical_object.chunk_size)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Extraction<'a> {
+    fn new(destination: &Path, options: &'a ExtractOptions) -> Result<Self, ZffMountError> {
+        fs::create_dir_all(destination)
+            .map_err(|e| ZffMountError::io(format!("Could not create the destination directory {}.", destination.display()), e))?;
+        Ok(Self {
+            options,
+            root: destination.to_path_buf(),
+            report: ExtractReport::default(),
+        })
+    }
+
+    // returns the path, where the given entry should be extracted to, or None, if the entry should be skipped.
+    fn resolve_conflict(&self, path: &Path, file_type: FileType) -> io::Result<Option<PathBuf>> {
+        let existing = match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Some(path.to_path_buf())),
+            Err(e) => return Err(e),
+        };
+        let both_directories = existing.is_dir() && file_type == FileType::Directory;
+        match self.options.on_conflict {
+            ConflictPolicy::Error => Err(io::Error::new(io::ErrorKind::AlreadyExists, "the entry already exists")),
+            ConflictPolicy::Skip => Ok(None),
+            ConflictPolicy::Overwrite if both_directories => Ok(Some(path.to_path_buf())),
+            ConflictPolicy::Overwrite => {
+                if existing.is_dir() {
+                    fs::remove_dir_all(path)?;
+                } else {
+                    fs::remove_file(path)?;
+                }
+                Ok(Some(path.to_path_buf()))
+            },
+            ConflictPolicy::Rename => {
+                for suffix in 1.. {
+                    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+                    name.push(format!(".{suffix}"));
+                    let renamed = path.with_file_name(name);
+                    if fs::symlink_metadata(&renamed).is_err() {
+                        return Ok(Some(renamed));
+                    }
+                }
+                unreachable!()
+            },
+        }
+    }
+}
+
+// sets the access and modification time of the given path (of the symlink itself, not of its target).
+fn set_times(path: &Path, attr: &FileAttr) -> nix::Result<()> {
+    utimensat(None, path, &timespec(attr.atime), &timespec(attr.mtime), UtimensatFlags::NoFollowSymlink)
+}
+
+fn timespec(time: SystemTime) -> TimeSpec {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => TimeSpec::from(duration),
+        // the times before 1970.
+        Err(e) => -TimeSpec::from(e.duration()),
+    }
+}
@@ -0,0 +1,32 @@
+// - external
+use fuser::{ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry};
+use libc::c_int;
+
+/// The replies of the operations, which are guarded against panics (see ZffFs::guarded).
+pub(crate) trait ErrorReply {
+    fn error(self, err: c_int);
+}
+
+impl ErrorReply for ReplyData {
+    fn error(self, err: c_int) {
+        ReplyData::error(self, err)
+    }
+}
+
+impl ErrorReply for ReplyDirectory {
+    fn error(self, err: c_int) {
+        ReplyDirectory::error(self, err)
+    }
+}
+
+impl ErrorReply for ReplyEntry {
+    fn error(self, err: c_int) {
+        ReplyEntry::error(self, err)
+    }
+}
+
+impl ErrorReply for ReplyAttr {
+    fn error(self, err: c_int) {
+        ReplyAttr::error(self, err)
+    }
+}
@@ -0,0 +1,45 @@
+// - STD
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::sync::Arc;
+
+/// A filename, which is stored only once in the [FilenameInterner] (a clone is only a reference count increment).
+pub(crate) type Filename = Arc<OsStr>;
+
+/// A pool of the filenames of all objects. Equal filenames (e.g. the many "index.html" or ".git" of a container)
+/// are stored only once and shared by the lookup tables, the renamed and original names and the directory listings.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(crate) struct FilenameInterner {
+    filenames: HashSet<Filename>,
+    bytes: u64,
+}
+
+impl FilenameInterner {
+    /// Returns the interned handle of the given filename and adds the filename to the pool, if necessary.
+    pub(crate) fn intern<S: AsRef<OsStr>>(&mut self, filename: S) -> Filename {
+        let filename = filename.as_ref();
+        if let Some(interned) = self.filenames.get(filename) {
+            return Arc::clone(interned);
+        }
+        let interned: Filename = Arc::from(filename);
+        self.bytes += filename.len() as u64;
+        self.filenames.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Returns the interned handle of the given filename, if it is already in the pool. Otherwise, a new handle is
+    /// returned without adding it to the pool (e.g. for the names of a directory listing, which are dropped at releasedir).
+    pub(crate) fn share(&self, filename: OsString) -> Filename {
+        match self.filenames.get(filename.as_os_str()) {
+            Some(interned) => Arc::clone(interned),
+            None => Arc::from(filename),
+        }
+    }
+}
+
+impl fmt::Display for FilenameInterner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} unique filenames with {} bytes", self.filenames.len(), self.bytes)
+    }
+}
@@ -0,0 +1,220 @@
+// - STD
+use std::fmt;
+use std::io::{self, Read, Seek};
+use std::path::Path;
+use std::time::SystemTime;
+
+// - internal
+use super::ZffFs;
+use crate::constants::*;
+use crate::error::ZffMountError;
+use crate::timestamps;
+use zff::io::zffreader::ObjectType as ZffReaderObjectType;
+
+// - external
+use fuser::FileType;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// The type of a listed entry.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListEntryType {
+    Directory,
+    File,
+    Symlink,
+    NamedPipe,
+    CharDevice,
+    BlockDevice,
+    Socket,
+    /// An encrypted object, which could not be decrypted (the content can not be listed).
+    Locked,
+}
+
+impl From<FileType> for ListEntryType {
+    fn from(file_type: FileType) -> Self {
+        match file_type {
+            FileType::Directory => ListEntryType::Directory,
+            FileType::RegularFile => ListEntryType::File,
+            FileType::Symlink => ListEntryType::Symlink,
+            FileType::NamedPipe => ListEntryType::NamedPipe,
+            FileType::CharDevice => ListEntryType::CharDevice,
+            FileType::BlockDevice => ListEntryType::BlockDevice,
+            FileType::Socket => ListEntryType::Socket,
+        }
+    }
+}
+
+impl fmt::Display for ListEntryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ListEntryType::Directory => "directory",
+            ListEntryType::File => "file",
+            ListEntryType::Symlink => "symlink",
+            ListEntryType::NamedPipe => "named_pipe",
+            ListEntryType::CharDevice => "char_device",
+            ListEntryType::BlockDevice => "block_device",
+            ListEntryType::Socket => "socket",
+            ListEntryType::Locked => "locked",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A single entry of a listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListEntry {
+    /// The path of the entry, as it would be presented at the mount point.
+    pub path: String,
+    #[serde(rename = "type")]
+    pub entry_type: ListEntryType,
+    pub size: u64,
+    /// The modification time in RFC 3339 format in the configured timezone (None, if the attributes are not available, e.g. for locked objects).
+    pub mtime: Option<String>,
+    pub object: Option<u64>,
+    /// The file number of the entry in the logical object (None for object directories, images and virtual files).
+    pub file_number: Option<u64>,
+    /// The entry contains data, which could not be read while acquiring it (only detected with a preloaded chunk flags map).
+    pub acquisition_error: bool,
+}
+
+/// Prints the listing as table.
+pub struct ListingTable<'a>(pub &'a [ListEntry]);
+
+impl fmt::Display for ListingTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<12} | {:>14} | {:<25} | {:>6} | {:>11} | {:<7} | path", "type", "size", "mtime", "object", "file number", "damaged")?;
+        for entry in self.0 {
+            writeln!(f, "{:<12} | {:>14} | {:<25} | {:>6} | {:>11} | {:<7} | {}",
+                entry.entry_type.to_string(),
+                entry.size,
+                entry.mtime.as_deref().unwrap_or("-"),
+                entry.object.map(|object| object.to_string()).as_deref().unwrap_or("-"),
+                entry.file_number.map(|file_number| file_number.to_string()).as_deref().unwrap_or("-"),
+                if entry.acquisition_error { "yes" } else { "-" },
+                entry.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the listing as CSV (with a header line).
+pub struct ListingCsv<'a>(pub &'a [ListEntry]);
+
+impl fmt::Display for ListingCsv<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "path,type,size,mtime,object,file_number,acquisition_error")?;
+        for entry in self.0 {
+            writeln!(f, "{},{},{},{},{},{},{}",
+                csv_field(&entry.path),
+                entry.entry_type,
+                entry.size,
+                entry.mtime.as_deref().unwrap_or_default(),
+                entry.object.map(|object| object.to_string()).unwrap_or_default(),
+                entry.file_number.map(|file_number| file_number.to_string()).unwrap_or_default(),
+                entry.acquisition_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> ZffFs<R> {
+    /// Lists the content of the presented root directory (the same tree, which would be presented at the mount point)
+    /// without mounting the filesystem. If recursive is set, the content of all subdirectories is listed, too.
+    /// The encrypted objects, which could not be decrypted, are listed as locked entries.
+    pub fn list_all(&mut self, recursive: bool) -> Result<Vec<ListEntry>, ZffMountError> {
+        let root_inode = self.local_root_inode();
+        let mut listing = Vec::new();
+        self.list_directory(root_inode, Path::new("/"), recursive, &mut listing)?;
+        Ok(listing)
+    }
+
+    /// Lists the content of the directory at the given path of the given object (a file is listed as single entry).
+    /// The path is resolved in the same way as by [ZffFs::extract_path].
+    pub fn list_path(&mut self, object_number: u64, path: &Path, recursive: bool) -> Result<Vec<ListEntry>, ZffMountError> {
+        let inode = self.resolve_path(object_number, path)?;
+        let path = Path::new("/").join(path);
+        let mut listing = Vec::new();
+        // an object directory is listed, even if its attributes are missing.
+        let file_type = self.file_attr(inode).map(|attr| attr.kind).unwrap_or(FileType::Directory);
+        if file_type == FileType::Directory {
+            self.list_directory(inode, &path, recursive, &mut listing)?;
+        } else {
+            listing.push(self.list_entry(inode, file_type, &path));
+        }
+        Ok(listing)
+    }
+
+    // appends the entries of the given directory (and of its subdirectories, if recursive is set) to the given listing.
+    fn list_directory(&mut self, ino: u64, path: &Path, recursive: bool, listing: &mut Vec<ListEntry>) -> Result<(), ZffMountError> {
+        let entries = self.directory_entries(ino)
+            .map_err(|errno| ZffMountError::io(format!("Could not list the directory {}.", path.display()), io::Error::from_raw_os_error(errno)))?;
+        for (inode, file_type, name) in entries {
+            if *name == *CURRENT_DIR || *name == *PARENT_DIR {
+                continue;
+            }
+            let entry_path = path.join(&*name);
+            listing.push(self.list_entry(inode, file_type, &entry_path));
+            if recursive && file_type == FileType::Directory {
+                self.list_directory(inode, &entry_path, recursive, listing)?;
+            }
+        }
+        // the encrypted objects are not part of the directory entries, if their placeholders are hidden.
+        if ino == SPECIAL_INODE_ROOT_DIR && self.options.hide_encrypted {
+            let locked_objects: Vec<u64> = self.cache.object_list.iter()
+                .filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Encrypted)
+                .map(|(&object_number, _)| object_number)
+                .collect();
+            for object_number in locked_objects {
+                listing.push(ListEntry {
+                    path: path.join(self.object_path(object_number)).to_string_lossy().into_owned(),
+                    entry_type: ListEntryType::Locked,
+                    size: 0,
+                    mtime: None,
+                    object: Some(object_number),
+                    file_number: None,
+                    acquisition_error: false,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // returns the listing entry of the given inode.
+    fn list_entry(&mut self, ino: u64, file_type: FileType, path: &Path) -> ListEntry {
+        let attr = self.file_attr(ino);
+        let locked_object = self.locked_object_number(ino);
+        let (object, file_number) = if ino > SPECIAL_INODE_ROOT_DIR && ino <= self.shift_value {
+            (Some(ino - 1), None)
+        } else if locked_object.is_some() {
+            (locked_object, None)
+        } else if let Some((object_number, file_number)) = self.cache.inode_reverse_map.get(&ino) {
+            // the file number 0 is the image of a physical object.
+            (Some(*object_number), Some(*file_number).filter(|file_number| *file_number > 0))
+        } else {
+            (self.cache.virtual_files.get(&ino).map(|file| file.object_number), None)
+        };
+        ListEntry {
+            path: path.to_string_lossy().into_owned(),
+            entry_type: if locked_object.is_some() { ListEntryType::Locked } else { file_type.into() },
+            size: attr.map(|attr| attr.size).unwrap_or_default(),
+            mtime: attr.map(|attr| format_time(attr.mtime)),
+            object,
+            file_number,
+            acquisition_error: self.cache.acquisition_errors.contains_key(&ino),
+        }
+    }
+}
+
+fn format_time(time: SystemTime) -> String {
+    timestamps::format(OffsetDateTime::from(time))
+}
+
+// quotes the given CSV field, if necessary.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,18 +1,66 @@
 // - STD
-use std::collections::BTreeMap;
-use std::process::exit;
-use std::ffi::OsStr;
-
-
-use std::time::UNIX_EPOCH;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{OsStr, OsString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::hint::black_box;
 use std::io::{Read, Seek, SeekFrom};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::cmp::{min, max};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+
+// - modules
+mod virtual_files;
+mod verification;
+mod chunkmap;
+mod stats;
+mod benchmark;
+mod multi;
+mod activity;
+mod owners;
+mod chunk_cache;
+mod interner;
+mod samebytes;
+mod guard;
+mod extract;
+mod listing;
+mod report;
+mod attr;
+mod decryption;
+mod summary;
+mod access;
+mod archive;
 
 // - internal
 use super::constants::*;
+use crate::progress::{self, Phase};
+use crate::panic_hook;
+use crate::error::ZffMountError;
+use virtual_files::*;
+pub use virtual_files::hash_file_extension;
+use verification::*;
+use chunkmap::*;
+use stats::*;
+pub use benchmark::*;
+pub use extract::*;
+pub use listing::*;
+pub use report::*;
+pub use summary::*;
+pub use multi::*;
+pub use activity::*;
+pub use owners::*;
+pub use access::*;
+use chunk_cache::*;
+use interner::*;
+use samebytes::*;
+use guard::*;
+use attr::*;
+use decryption::*;
 use zff::{
     Result,
-    header::{FileType as ZffFileType, SpecialFileType as ZffSpecialFileType},
+    header::{FileType as ZffFileType, SpecialFileType as ZffSpecialFileType, MetadataExtendedValue},
     footer::ObjectFooter,
     ValueDecoder,
     io::zffreader::{ZffReader, ObjectType as ZffReaderObjectType, FileMetadata},
@@ -26,12 +74,15 @@ use log::{error, debug, info, warn};
 // - external
 use fuser::{
     FileAttr, FileType, Filesystem, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
-    Request,
+    ReplyOpen, ReplyLseek, ReplyXattr, ReplyEmpty, Request, KernelConfig,
+    consts::{FOPEN_CACHE_DIR, FOPEN_DIRECT_IO, FOPEN_KEEP_CACHE, FUSE_ASYNC_READ, FUSE_CACHE_SYMLINKS, FUSE_PARALLEL_DIROPS},
 };
 use nix::unistd::{Uid, Gid};
-use libc::ENOENT;
+use libc::{c_int, ENOENT, EACCES, EIO, EINVAL, ENXIO, ENODATA, ERANGE, SEEK_DATA, SEEK_HOLE};
 use time::OffsetDateTime;
-use dialoguer::{theme::ColorfulTheme, Password as PasswordDialog};
+use dialoguer::{theme::ColorfulTheme, Confirm as ConfirmDialog, Password as PasswordDialog};
+use unicode_normalization::UnicodeNormalization;
+use smallvec::{SmallVec, smallvec};
 
 #[derive(Debug)]
 pub enum PreloadChunkmapsMode {
@@ -46,31 +97,237 @@ pub struct PreloadChunkmaps {
     pub sizes: bool,
     pub flags: bool,
     pub samebytes: bool,
-    pub mode: PreloadChunkmapsMode
+    pub mode: PreloadChunkmapsMode,
+    /// set by the SIGINT/SIGTERM handler to abort the preloading before the filesystem is mounted.
+    pub abort: Arc<AtomicBool>,
+    /// the redb database file, if it was newly created for this preload (and should be removed, if the preload is aborted).
+    pub created_database: Option<PathBuf>,
+}
+
+/// returns an error, if the preloading was aborted by a signal. A newly created (and therefore incomplete) redb database will be removed.
+fn check_preload_aborted(abort: &AtomicBool, created_database: Option<&Path>) -> std::result::Result<(), ZffMountError> {
+    if !abort.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    warn!("Preloading of the chunkmaps was aborted.");
+    if let Some(path) = created_database {
+        match std::fs::remove_file(path) {
+            Ok(_) => info!("Removed incomplete preload chunkmap database {}.", path.display()),
+            Err(e) => {
+                warn!("Could not remove the incomplete preload chunkmap database {}.", path.display());
+                debug!("{e}");
+            }
+        }
+    }
+    Err(ZffMountError::Aborted)
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum LookupNormalization {
+    #[default]
+    None,
+    Nfc,
+    Nfd,
+}
+
+/// The owner of the files and directories in the replies of lookup and getattr.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum OwnerSquashing {
+    /// The acquired owner (or the owner of the mount).
+    #[default]
+    None,
+    /// The uid and gid of the caller of the request.
+    Caller,
 }
 
+impl LookupNormalization {
+    // returns the normalized form of the given filename. Filenames which are not valid UTF-8 are returned unchanged
+    // (as without a normalization, without an allocation).
+    fn normalize<'a>(&self, filename: &'a OsStr) -> Cow<'a, OsStr> {
+        match (self, filename.to_str()) {
+            (LookupNormalization::Nfc, Some(filename)) => Cow::Owned(OsString::from(filename.nfc().collect::<String>())),
+            (LookupNormalization::Nfd, Some(filename)) => Cow::Owned(OsString::from(filename.nfd().collect::<String>())),
+            _ => Cow::Borrowed(filename),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ZffFsOptions {
+    pub verify_at_mount: bool,
+    pub logical_blocks: bool,
+    pub direct_io: bool,
+    pub keep_page_cache: bool,
+    pub cache_forever: bool, // the kernel caches the entries, attributes, directory listings and symlink targets for the mount time.
+    pub case_insensitive: bool,
+    pub normalize_lookup: LookupNormalization,
+    pub sanitize_names: bool,
+    pub latency_stats: bool,
+    pub split_raw: Option<u64>, // the segment size, if the physical objects should be presented as split raw images.
+    pub image_name: Option<String>, // the filename template of the physical object file (zff_image.dd, if None).
+    pub object: Option<u64>, // only this object will be initialized and presented directly in the root directory.
+    pub object_dir_prefix: Option<String>, // the prefix of the object directories (object_, if None).
+    pub flatten_single: bool, // presents the content of the object directly in the root directory, if the container has only one object.
+    pub epoch_for_missing_times: bool, // presents missing file timestamps as UNIX_EPOCH (instead of the acquisition times of the object).
+    pub owner_map: Option<OwnerMap>, // maps the uids and gids of the acquired system to the local ids.
+    pub rebase_symlinks: bool, // rewrites absolute symlink targets to relative targets inside the object.
+    pub max_readahead: Option<u32>, // the maximum readahead (default: a multiple of the largest chunk size).
+    pub max_background: Option<u16>, // the maximum number of pending background requests (default: kernel default).
+    pub congestion_threshold: Option<u16>, // the congestion threshold of the background requests (default: kernel default).
+    pub cache_size: u64, // the maximum size of the decompressed chunks in the chunk cache (0 disables the cache).
+    pub hash_sidecars: bool, // presents the stored hash values of the physical objects as <image name>.<hash type> files.
+    pub report_format: ReportFormat, // the format of the acquisition report files.
+    pub fail_damaged_reads: bool, // the reads of files with acquisition errors fail with EIO (instead of serving the partial data).
+    pub virtual_archives: bool, // places a tar archive of the whole content into the directory of each logical object.
+    pub hide_encrypted: bool, // hides the encrypted objects, which could not be decrypted (instead of presenting locked placeholders).
+    pub require_all_decrypted: bool, // the initialization fails, if an encrypted object could not be decrypted.
+    pub access_restriction: Option<AccessRestriction>, // only these users are permitted to access the filesystem (all users, if None).
+    pub squash_owner: OwnerSquashing, // the owner of all files and directories in the replies.
+}
+
+
+// the data of the footer of a physical object, which is needed by readdir, lookup and read.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct PhysicalObjectData {
+    first_chunk_number: u64,
+    chunk_size: u64,
+    length_of_data: u64,
+}
+
+// the encryption state of an encrypted object.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ObjectEncryption {
+    decrypted: bool, // the object was decrypted for this mount.
+    algorithm: Option<String>, // the encryption algorithm, if exposed by the object header.
+}
+
+// the (parent inode, inode) pairs of a filename in the lookup tables. Nearly all filenames are unique in their object,
+// so the single entry is stored inline instead of in a separate allocation.
+type LookupEntries = SmallVec<[(u64, u64); 1]>;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct ZffFsCache {
     pub object_list: BTreeMap<u64, ZffReaderObjectType>,
-    pub inode_reverse_map: BTreeMap<u64, (u64, u64)>, //<Inode, (object number, file number)
-    pub filename_lookup_table: BTreeMap<String, Vec<(u64, u64)>>, //<Filename, Vec<Parent-Inode, Self-Inode>>
-    pub inode_attributes_map: BTreeMap<u64, FileAttr>,
+    pub inode_reverse_map: HashMap<u64, (u64, u64)>, //<Inode, (object number, file number) - only used for point lookups, so no ordering is needed.
+    pub filenames: FilenameInterner, // the filenames of the lookup tables, the original names and the renamed files.
+    pub filename_lookup_table: BTreeMap<u64, BTreeMap<Filename, LookupEntries>>, //<Object number, <Filename, [(Parent-Inode, Self-Inode)]>>
+    pub case_folded_lookup_table: Option<BTreeMap<u64, BTreeMap<Filename, LookupEntries>>>, //<Object number, <lowercase Filename, [(Parent-Inode, Self-Inode)]>>
+    pub inode_attributes_map: HashMap<u64, CompactAttr>, //<Inode, CompactAttr> - only used for point lookups, so no ordering is needed.
+    pub virtual_files: BTreeMap<u64, VirtualFile>, //<Inode, VirtualFile>
+    pub lookup_normalization: LookupNormalization,
+    pub original_names: BTreeMap<u64, Filename>, //<Inode, original filename> - only for sanitized or renamed filenames.
+    pub renamed_files: BTreeMap<u64, BTreeMap<u64, Filename>>, //<Object number, <File number, presented filename>> - only for duplicate filenames.
+    pub image_names: BTreeMap<u64, OsString>, //<Object number, filename of the physical object file>
+    pub physical_objects: BTreeMap<u64, PhysicalObjectData>, //<Object number, footer data of the physical object>
+    pub source_owners: BTreeMap<u64, (u32, u32)>, //<Inode, (uid, gid) of the acquired system> - only for files with an acquired owner.
+    pub unresolvable_hardlinks: BTreeSet<u64>, //<Inode> - hardlinks, which are presented as empty regular files.
+    pub acquisition_errors: BTreeMap<u64, Vec<(u64, u64)>>, //<Inode, damaged byte ranges> - only for files with chunks, which could not be acquired.
+    pub owner_map: Option<OwnerMap>, // maps the acquired owners to the local ids.
+    pub default_owner: (u32, u32), // the (uid, gid) of all files without an acquired owner.
+    pub encrypted_objects: BTreeMap<u64, ObjectEncryption>, //<Object number, encryption state> - only for encrypted objects.
 }
 
 impl ZffFsCache {
-    fn with_data(
-        object_list: BTreeMap<u64, ZffReaderObjectType>,
-        inode_reverse_map: BTreeMap<u64, (u64, u64)>,
-        filename_lookup_table: BTreeMap<String, Vec<(u64, u64)>>,
-        inode_attributes_map: BTreeMap<u64, FileAttr>) -> Self 
-    {
-        Self {
-            object_list,
-            inode_reverse_map,
-            filename_lookup_table,
-            inode_attributes_map,
+    // returns the object number, if the given inode is an object directory or the placeholder directory of a locked object.
+    fn object_of_object_dir(&self, inode: u64) -> Option<u64> {
+        if let Some(object_number) = inode.checked_sub(SPECIAL_INODE_LOCKED_OBJECTS_START) {
+            return Some(object_number).filter(|object_number| self.object_list.contains_key(object_number));
         }
+        if inode <= SPECIAL_INODE_ROOT_DIR || self.inode_reverse_map.contains_key(&inode) || self.virtual_files.contains_key(&inode) {
+            return None;
+        }
+        Some(inode - 1).filter(|object_number| self.object_list.contains_key(object_number))
+    }
+
+    // returns the attributes of the given inode with the owner filled in (the mapped acquired owner, if available).
+    fn attr(&self, inode: u64) -> Option<FileAttr> {
+        let attr = self.inode_attributes_map.get(&inode)?;
+        let (uid, gid) = match (self.source_owners.get(&inode), &self.owner_map) {
+            (Some((uid, gid)), Some(owner_map)) => (owner_map.map_uid(*uid), owner_map.map_gid(*gid)),
+            (Some(owner), None) => *owner,
+            (None, _) => self.default_owner,
+        };
+        Some(attr.to_file_attr(inode, uid, gid))
+    }
+
+    // returns the extended attributes of the given inode.
+    fn xattrs(&self, inode: u64) -> Vec<(&'static str, Vec<u8>)> {
+        let mut xattrs = Vec::new();
+        if let Some(original_name) = self.original_names.get(&inode) {
+            xattrs.push((XATTR_ORIGINAL_NAME, original_name.as_bytes().to_vec()));
+        }
+        if let Some((uid, gid)) = self.source_owners.get(&inode) {
+            xattrs.push((XATTR_SOURCE_UID, uid.to_string().into_bytes()));
+            xattrs.push((XATTR_SOURCE_GID, gid.to_string().into_bytes()));
+        }
+        if let Some(ranges) = self.acquisition_errors.get(&inode) {
+            let ranges: Vec<String> = ranges.iter().map(|(start, end)| format!("{start}-{}", end - 1)).collect();
+            xattrs.push((XATTR_ACQUISITION_ERROR, ranges.join(",").into_bytes()));
+        }
+        if let Some(object_number) = self.object_of_object_dir(inode) {
+            let encryption = self.encrypted_objects.get(&object_number);
+            xattrs.push((XATTR_ENCRYPTED, encryption.is_some().to_string().into_bytes()));
+            xattrs.push((XATTR_DECRYPTED, encryption.map_or(false, |encryption| encryption.decrypted).to_string().into_bytes()));
+            if let Some(algorithm) = encryption.and_then(|encryption| encryption.algorithm.as_ref()) {
+                xattrs.push((XATTR_ENCRYPTION_ALGORITHM, algorithm.as_bytes().to_vec()));
+            }
+        }
+        xattrs
+    }
+
+    // returns the inode of the virtual file with the given name in the given directory (if exists).
+    fn virtual_file_inode(&self, parent_inode: u64, name: &OsStr) -> Option<u64> {
+        self.virtual_files.iter()
+            .find(|(_, file)| file.parent_inode == parent_inode && name == file.name.as_str())
+            .map(|(&inode, _)| inode)
+    }
+
+    // returns the inode of the file with the given name in the given parent directory of the given object (logical objects only).
+    // The lookup never crosses the object boundaries.
+    // Exact matches are preferred, so all files are reachable by their exact names, even if the case-insensitive names collide.
+    fn lookup_inode(&self, object_number: u64, parent_inode: u64, name: &OsStr) -> Option<u64> {
+        let name = self.lookup_normalization.normalize(name);
+        let find_in_parent = |entries: &LookupEntries| entries.iter()
+            .find(|(entry_parent_inode, _)| *entry_parent_inode == parent_inode)
+            .map(|(_, inode)| *inode);
+        let exact_match = self.filename_lookup_table.get(&object_number)
+            .and_then(|lookup_table| lookup_table.get(&*name))
+            .and_then(find_in_parent);
+        if exact_match.is_some() {
+            return exact_match;
+        }
+        self.case_folded_lookup_table.as_ref()?
+            .get(&object_number)?
+            .get(fold_case(&name).as_os_str())
+            .and_then(find_in_parent)
+    }
+
+    // returns the inodes and names of the split raw segments of the given physical object (in the appropriate order).
+    fn raw_segments(&self, object_number: u64) -> Vec<(u64, OsString)> {
+        let mut segments: Vec<(u64, u64, OsString)> = self.virtual_files.iter()
+            .filter_map(|(&inode, file)| match file.file_type {
+                VirtualFileType::RawSegment { offset, .. } if file.object_number == object_number => Some((offset, inode, OsString::from(&file.name))),
+                _ => None,
+            })
+            .collect();
+        segments.sort();
+        segments.into_iter().map(|(_, inode, name)| (inode, name)).collect()
+    }
+
+    // returns the filename of the physical object file of the given object.
+    fn image_name(&self, object_number: u64) -> OsString {
+        match self.image_names.get(&object_number) {
+            Some(image_name) => image_name.clone(),
+            None => OsString::from(ZFF_PHYSICAL_OBJECT_NAME),
+        }
+    }
+
+    // returns the chunkmap layout of the given physical object.
+    fn chunkmap_layout(&self, object_number: u64) -> Option<&ChunkmapLayout> {
+        self.virtual_files.values().find_map(|file| match &file.file_type {
+            VirtualFileType::Chunkmap(layout) if file.object_number == object_number => Some(layout),
+            _ => None,
+        })
     }
 }
 
@@ -79,29 +336,39 @@ pub struct ZffFs<R: Read + Seek> {
     zffreader: ZffReader<R>,
     shift_value: u64,
     cache: ZffFsCache,
+    samebytes_preloaded: bool,
+    flags_preloaded: bool,
+    open_flags: u32,
+    opendir_flags: u32,
+    options: ZffFsOptions,
+    latency_stats: Option<Arc<Mutex<LatencyStats>>>,
+    panic_stats: PanicStats,
+    chunk_cache: Option<ChunkCache>,
+    read_chunks: u64, // the number of chunks of the last read request (for the trace of the read operation).
+    activity: Arc<ActivityTracker>,
+    directory_listings: HashMap<u64, Vec<(u64, FileType, Filename)>>, //<directory handle, entries> - the listings of the open directories.
+    file_handles: HashMap<u64, FileAttr>, //<file handle, attributes> - the attributes of the open files (without virtual files).
+    next_handle: u64, // the next file or directory handle.
+    warned_symlink_targets: HashSet<Vec<u8>>, // the absolute symlink targets outside of the acquired paths, which were already reported.
+    owner: Option<(u32, u32)>, // the uid and gid of all files without an acquired owner (e.g. after dropping the privileges).
+    inode_offset: u64, // the offset of all inodes presented to the kernel (only used if multiple containers are mounted).
+    root_object: Option<u64>, // the object, which content is presented directly in the root directory (instead of the object_N directories).
+    root_dir_attr: FileAttr, // the attributes of the root directory, which are built at mount time.
+    preload: PreloadSummary, // the preloaded chunkmaps (for the mount summary).
+    object_list_xattr: Vec<u8>, // the object list of the root directory (as JSON array), which is generated at mount time.
 }
 
 impl<R: Read + Seek> ZffFs<R> {
     pub fn new(
         inputfiles: Vec<R>, 
         decryption_passwords: &HashMap<u64, String>, 
-        preload_chunkmaps: PreloadChunkmaps) -> Self {
+        preload_chunkmaps: PreloadChunkmaps,
+        options: ZffFsOptions) -> std::result::Result<Self, ZffMountError> {
         info!("Reading segment files to create initial ZffReader.");
-        let mut zffreader = match ZffReader::with_reader(inputfiles) {
-            Ok(reader) => reader,
-            Err(e) => {
-                error!("An error occurred while trying to create the ZffReader: {e}");
-                exit(EXIT_STATUS_ERROR);
-            }
-        };
+        let number_of_segments = inputfiles.len() as u64;
+        let mut zffreader = ZffReader::with_reader(inputfiles).map_err(|e| ZffMountError::zff(format!("An error occurred while trying to create the ZffReader: {e}"), e))?;
 
-        let mut object_list = match zffreader.list_objects() {
-            Ok(list) => list,
-            Err(e) => {
-                error!("An error occurred while trying to get the ZffReader object list: {e}");
-                exit(EXIT_STATUS_ERROR);
-            }
-        };
+        let mut object_list = zffreader.list_objects().map_err(|e| ZffMountError::zff(format!("An error occurred while trying to get the ZffReader object list: {e}"), e))?;
         let (phy, log, enc) = object_list.values().fold((0, 0, 0), |(phy, log, enc), val| {
             match val {
                 ZffReaderObjectType::Physical => (phy + 1, log, enc),
@@ -112,33 +379,91 @@ impl<R: Read + Seek> ZffFs<R> {
         });
         info!("ZffReader created successfully. Found {phy} physical, {log} logical and {enc} encrypted objects.");
 
+        // only the selected object will be initialized.
+        if let Some(object_number) = options.object {
+            if !object_list.contains_key(&object_number) {
+                return Err(ZffMountError::ObjectNotFound { object: object_number });
+            }
+            object_list.retain(|number, _| *number == object_number);
+        }
+
         //initialize and decrypt objects
-        for (object_number, obj_type) in &object_list {
+        let mut decryption_outcomes = Vec::new();
+        let number_of_objects = object_list.len() as u64;
+        progress::report(Phase::InitializeObjects, Some(0), Some(number_of_objects));
+        for (index, (object_number, obj_type)) in object_list.iter().enumerate() {
             match zffreader.initialize_object(*object_number) {
-                Ok(_) => info!("Successfully initialized {obj_type} object {object_number}"),
+                Ok(_) => info!(object = *object_number; "Successfully initialized {obj_type} object {object_number}"),
                 Err(e) => error!("Could not inititalize object {object_number} due following error: {e}"),
             }
 
             if obj_type == &ZffReaderObjectType::Encrypted {
-                let pw = match decryption_passwords.get(object_number) {
-                    Some(pw) => pw.clone(),
+                let (pw, source) = match decryption_passwords.get(object_number) {
+                    Some(pw) => (pw.clone(), Some(PasswordSource::Flag)),
                     None => match enter_password_dialog(*object_number)  {
-                        Some(pw) => pw,
+                        Some(pw) => (pw, Some(PasswordSource::Prompt)),
                         None => {
                             info!("No password entered for encrypted object {object_number}.");
-                            String::new()
+                            (String::new(), None)
                         }
                     }
                 };
-                match zffreader.decrypt_object(*object_number, pw) {
-                    Ok(o_type) => info!("Object {object_number} ({o_type} object) decrypted successfully"),
-                    Err(e) => warn!("Could not decrypt object {object_number}: {e}"),
+                let decrypted = decrypt_object(&mut zffreader, *object_number, pw);
+                decryption_outcomes.push(DecryptionOutcome { object_number: *object_number, source, decrypted });
+            }
+            progress::report(Phase::InitializeObjects, Some(index as u64 + 1), Some(number_of_objects));
+        }
+
+        // in interactive mode, the passwords of the objects, which could not be decrypted, could be entered again.
+        if decryption_outcomes.iter().any(|outcome| !outcome.decrypted) && stdin_is_tty() {
+            for outcome in decryption_outcomes.iter_mut().filter(|outcome| !outcome.decrypted) {
+                while confirm_retry_dialog(outcome.object_number) {
+                    let pw = match enter_password_dialog(outcome.object_number) {
+                        Some(pw) => pw,
+                        None => break,
+                    };
+                    outcome.source = Some(PasswordSource::Prompt);
+                    if decrypt_object(&mut zffreader, outcome.object_number, pw) {
+                        outcome.decrypted = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if !decryption_outcomes.is_empty() {
+            let summary = DecryptionSummary(&decryption_outcomes);
+            let locked_objects = summary.locked_objects();
+            if locked_objects.is_empty() {
+                info!("{summary}");
+            } else {
+                warn!("{summary}");
+                if options.require_all_decrypted {
+                    return Err(ZffMountError::NotAllDecrypted { objects: locked_objects });
                 }
             }
         }
 
+        // the decrypted objects are noted in their acquisition reports.
+        let encrypted_objects: BTreeSet<u64> = object_list.iter()
+            .filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Encrypted)
+            .map(|(object_number, _)| *object_number)
+            .collect();
+
         // from here, we can work with unencrypted/decrypted objects.
         object_list = zffreader.list_decrypted_objects();
+        let encryption_states = encrypted_objects.iter()
+            .map(|object_number| (*object_number, ObjectEncryption {
+                decrypted: object_list.get(object_number).map_or(false, |obj_type| obj_type != &ZffReaderObjectType::Encrypted),
+                algorithm: encryption_algorithm(&mut zffreader, *object_number),
+            }))
+            .collect();
+        if let Some(object_number) = options.object {
+            object_list.retain(|number, _| *number == object_number);
+            match object_list.get(&object_number) {
+                Some(ZffReaderObjectType::Encrypted) | None => return Err(ZffMountError::DecryptionFailed { object: object_number }),
+                Some(_) => (),
+            }
+        }
 
         // set object inodes and shift value
         let numbers_of_decrypted_objects: Vec<u64> = object_list.iter().map(|(&k, _)| k).collect();
@@ -147,390 +472,1227 @@ impl<R: Read + Seek> ZffFs<R> {
             None => 1,
         };
 
-        let mut inode_reverse_map = BTreeMap::new();
-        let mut filename_lookup_table = BTreeMap::new();
-        let mut inode_attributes_map = BTreeMap::new();
-
-        for (object_number, obj_type) in &object_list {
-            //setup inode reverse map
-            match inode_reverse_map_add_object(&mut zffreader, &mut inode_reverse_map, *object_number, shift_value) {
-                Ok(noe) => debug!("{noe} entries for object {object_number} added to inode reverse map."),
-                Err(e) => {
-                    error!("An error occurred while trying to fill the inode reverse map.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                }
-            };  
-
-            //setup inode attributes map
-            match inode_attributes_map_add_object(&mut zffreader, &mut inode_attributes_map, *object_number, shift_value) {
-                Ok(noe) => debug!("{noe} entries for object {object_number} added to inode attributes map."),
-                Err(e) => {
-                    error!("An error occurred while trying to fill the inode attributes map.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                }
-            };
-
-            // only for logical objects
-            if obj_type == &ZffReaderObjectType::Logical {
-                //setup lookup table
-                match filename_lookup_table_add_object(&mut zffreader, &mut filename_lookup_table, *object_number, shift_value) {
-                    Ok(noe) => debug!("{noe} entries for object {object_number} added to lookup table."),
-                    Err(e) => {
-                        error!("An error occurred while trying to fill the lookup table.");
-                        debug!("{e}");
-                        exit(EXIT_STATUS_ERROR);
-                    }
-                };
-            }
-        }
-        let cache = ZffFsCache::with_data(object_list, inode_reverse_map, filename_lookup_table, inode_attributes_map);
-
+        // the chunkmaps have to be preloaded before the caches are built, as the samebytes map
+        // is used to calculate the allocated blocks of each file.
+        let created_database = preload_chunkmaps.created_database.as_deref();
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
+        let number_of_chunks = number_of_chunks(&mut zffreader, &object_list);
         // setup mode
+        let preload_mode = match &preload_chunkmaps.mode {
+            PreloadChunkmapsMode::None => "none",
+            PreloadChunkmapsMode::InMemory => "in-memory",
+            PreloadChunkmapsMode::Redb(_) => "redb",
+        };
         match preload_chunkmaps.mode {
             PreloadChunkmapsMode::None => (),
             PreloadChunkmapsMode::InMemory => {
                 info!("Set preload chunkmap mode to in-memory ...");
-                if let Err(e) = zffreader.set_preload_chunkmaps_mode_in_memory() {
-                    error!("An error occurred while trying to create the in memory preload chunkmap.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                };
-                if let Err(e) = zffreader.preload_chunk_offset_map_full() {
-                    error!("An error occurred while trying to preload chunkmap.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                };
+                zffreader.set_preload_chunkmaps_mode_in_memory().map_err(|e| ZffMountError::zff("An error occurred while trying to create the in memory preload chunkmap.", e))?;
+                progress::report(Phase::PreloadChunkOffsetMap, Some(0), number_of_chunks);
+                zffreader.preload_chunk_offset_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+                progress::report(Phase::PreloadChunkOffsetMap, number_of_chunks, number_of_chunks);
             }
             PreloadChunkmapsMode::Redb(db) => {
                 info!("Set preload chunkmap mode to redb ...");
-                if let Err(e) = zffreader.set_preload_chunkmap_mode_redb(db) {
-                    error!("An error occurred while trying to create the redb preload chunkmap.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                };
-                if let Err(e) = zffreader.preload_chunk_offset_map_full() {
-                    error!("An error occurred while trying to preload chunkmap.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                };
+                zffreader.set_preload_chunkmap_mode_redb(db).map_err(|e| ZffMountError::zff("An error occurred while trying to create the redb preload chunkmap.", e))?;
+                progress::report(Phase::PreloadChunkOffsetMap, Some(0), number_of_chunks);
+                zffreader.preload_chunk_offset_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+                progress::report(Phase::PreloadChunkOffsetMap, number_of_chunks, number_of_chunks);
             }
         }
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
 
         // preload appropriate chunkmaps
 
         if preload_chunkmaps.offsets {
             info!("Preload chunkmap offsets ...");
-            if let Err(e) = zffreader.preload_chunk_offset_map_full() {
-                error!("An error occurred while trying to preload chunkmap.");
-                debug!("{e}");
-                exit(EXIT_STATUS_ERROR);
-            };
+            progress::report(Phase::PreloadChunkOffsetMap, Some(0), number_of_chunks);
+            zffreader.preload_chunk_offset_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+            progress::report(Phase::PreloadChunkOffsetMap, number_of_chunks, number_of_chunks);
             info!("Chunkmap offsets successfully preloaded ...");
         }
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
 
         if preload_chunkmaps.sizes {
             info!("Preload chunkmap sizes ...");
-            if let Err(e) = zffreader.preload_chunk_size_map_full() {
-                error!("An error occurred while trying to preload chunkmap.");
-                debug!("{e}");
-                exit(EXIT_STATUS_ERROR);
-            };
+            progress::report(Phase::PreloadChunkSizeMap, Some(0), number_of_chunks);
+            zffreader.preload_chunk_size_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+            progress::report(Phase::PreloadChunkSizeMap, number_of_chunks, number_of_chunks);
             info!("Chunkmap sizes successfully preloaded ...");
         }
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
 
         if preload_chunkmaps.flags {
             info!("Preload chunkmap flags ...");
-            if let Err(e) = zffreader.preload_chunk_flags_map_full() {
-                error!("An error occurred while trying to preload chunkmap.");
-                debug!("{e}");
-                exit(EXIT_STATUS_ERROR);
-            };
+            progress::report(Phase::PreloadChunkFlagsMap, Some(0), number_of_chunks);
+            zffreader.preload_chunk_flags_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+            progress::report(Phase::PreloadChunkFlagsMap, number_of_chunks, number_of_chunks);
             info!("Chunkmap flags successfully preloaded ...");
         }
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
 
         if preload_chunkmaps.samebytes {
             info!("Preload chunkmap samebytes ...");
-            if let Err(e) = zffreader.preload_chunk_samebytes_map_full() {
-                error!("An error occurred while trying to preload chunkmap.");
-                debug!("{e}");
-                exit(EXIT_STATUS_ERROR);
-            };
+            progress::report(Phase::PreloadChunkSamebytesMap, Some(0), number_of_chunks);
+            zffreader.preload_chunk_samebytes_map_full().map_err(|e| ZffMountError::zff("An error occurred while trying to preload chunkmap.", e))?;
+            progress::report(Phase::PreloadChunkSamebytesMap, number_of_chunks, number_of_chunks);
             info!("Chunkmap samebytes successfully preloaded ...");
         }
+        check_preload_aborted(&preload_chunkmaps.abort, created_database)?;
 
-        info!("ZffFs successfully initialized and can be used now.");
+        let mut inode_reverse_map = HashMap::new();
+        let mut unresolvable_hardlinks = BTreeSet::new();
+        let mut filenames = FilenameInterner::default();
+        let mut filename_lookup_table = BTreeMap::new();
+        let mut inode_attributes_map = HashMap::new();
+        let mut original_names = BTreeMap::new();
+        let mut renamed_files = BTreeMap::new();
+        let mut source_owners = BTreeMap::new();
+        // the allocated blocks are counted by the zero chunks, which would need a chunk header read per chunk without both maps.
+        let sparse_blocks = preload_chunkmaps.samebytes && preload_chunkmaps.flags && !options.logical_blocks;
+
+        let number_of_objects = object_list.len() as u64;
+        progress::report(Phase::BuildCaches, Some(0), Some(number_of_objects));
+        for (index, (object_number, obj_type)) in object_list.iter().enumerate() {
+            //setup inode reverse map
+            let noe = inode_reverse_map_add_object(&mut zffreader, &mut inode_reverse_map, &mut unresolvable_hardlinks, *object_number, shift_value).map_err(|e| ZffMountError::zff("An error occurred while trying to fill the inode reverse map.", e))?;
+            debug!("{noe} entries for object {object_number} added to inode reverse map.");
 
-        Self {
-            zffreader,
-            shift_value,
-            cache,
-        }
-    }
-}
+            //setup inode attributes map
+            let noe = inode_attributes_map_add_object(&mut zffreader, &mut inode_attributes_map, &mut source_owners, *object_number, shift_value, sparse_blocks, options.epoch_for_missing_times).map_err(|e| ZffMountError::zff("An error occurred while trying to fill the inode attributes map.", e))?;
+            debug!("{noe} entries for object {object_number} added to inode attributes map.");
 
-impl<R: Read + Seek> Filesystem for ZffFs<R> {
-    fn read(
-        &mut self,
-        _req: &Request,
-        ino: u64,
-        _fh: u64,
-        offset: i64,
-        size: u32,
-        _flags: i32,
-        _lock: Option<u64>,
-        reply: ReplyData,
-    ) {
-        if offset < 0 {
-            error!("READ: offset >= 0 -> offset = {offset}");
-            reply.error(ENOENT);
-            return;
+            // only for logical objects
+            if obj_type == &ZffReaderObjectType::Logical {
+                //setup lookup table
+                match filename_lookup_table_add_object(
+                    &mut zffreader,
+                    &mut filenames,
+                    filename_lookup_table.entry(*object_number).or_default(),
+                    &mut original_names,
+                    renamed_files.entry(*object_number).or_default(),
+                    *object_number,
+                    shift_value,
+                    &options) {
+                    Ok(noe) => debug!("{noe} entries for object {object_number} added to lookup table."),
+                    Err(e) => return Err(ZffMountError::zff("An error occurred while trying to fill the lookup table.", e)),
+                };
+            }
+            progress::report(Phase::BuildCaches, Some(index as u64 + 1), Some(number_of_objects));
         }
-        if ino < self.shift_value {
-            unreachable!()
-        } else {
-            let (object_no, file_no) = match self.cache.inode_reverse_map.get(&ino) {
-                Some(data) => data,
-                None => {
-                    error!("Error while trying to read data from inode {ino}: Inode not found in inode reverse map.");
-                    reply.error(ENOENT);
-                    return;
-                }
-            };
 
-            //check if this is a physical object.
-            // we've stored inodes to physical objects in inode map by using the file number 0 as placeholder earlier.
-            if *file_no == 0 {
-                if let Err(e) = self.zffreader.set_active_object(*object_no) {
-                    error!("An error occurred while trying to set object {object_no} as active.");
-                    debug!("{e}");
-                    reply.error(ENOENT);
-                    return;
+        //setup the filenames of the physical object files
+        let mut image_names = BTreeMap::new();
+        if let Some(template) = &options.image_name {
+            for (object_number, _) in object_list.iter().filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Physical) {
+                match image_name_of_object(&mut zffreader, *object_number, template) {
+                    Ok(image_name) => { image_names.insert(*object_number, image_name); },
+                    Err(e) => return Err(ZffMountError::zff(format!("An error occurred while trying to generate the image name of object {object_number}."), e)),
                 }
-            } else {
-                // if the object is a logical object, we have to do some more stuff.
-                // sets the appropriate object and file active and returns the appropriate file-  
-                // metadata (which is not needed at this point).
-                let _ = match prepare_zffreader_logical_file(&mut self.zffreader, *object_no, *file_no) {
-                    Err(e) => {
-                        error!("Error while trying to set file {file_no} of object {object_no} active.");
-                        debug!("{e}");
-                        reply.error(ENOENT);
-                        return;
-                    },
-                    Ok(metadata) => metadata
-                };
             }
-            
-            match self.zffreader.seek(SeekFrom::Start(offset as u64)) {
-                Ok(_) => (),
+        }
+
+        //setup virtual files
+        let mut virtual_files = BTreeMap::new();
+        let mut physical_objects = BTreeMap::new();
+        let mut next_virtual_inode = SPECIAL_INODE_VIRTUAL_FILES_START;
+        for (object_number, obj_type) in &object_list {
+            let object_dir_attr = match inode_attributes_map.get(&(object_number+1)) {
+                Some(attr) => *attr,
+                None => continue,
+            };
+            let inode = next_virtual_inode;
+            next_virtual_inode += 1;
+            let verification_file = VirtualFile::new(*object_number, object_number+1, VERIFICATION_FILE_NAME, VirtualFileType::Verification);
+            inode_attributes_map.insert(inode, virtual_file_attr(VERIFICATION_FILE_SIZE_ESTIMATE, &object_dir_attr));
+            virtual_files.insert(inode, verification_file);
+
+            // the acquisition report is built once, so the size and the content are consistent.
+            match acquisition_report_of_object(&mut zffreader, *object_number, number_of_segments, encrypted_objects.contains(object_number)) {
+                Ok(report) => {
+                    let content = report.render(options.report_format);
+                    let inode = next_virtual_inode;
+                    next_virtual_inode += 1;
+                    inode_attributes_map.insert(inode, virtual_file_attr(content.len() as u64, &object_dir_attr));
+                    let mut report_file = VirtualFile::new(*object_number, object_number+1, options.report_format.file_name(ACQUISITION_REPORT_FILE_STEM), VirtualFileType::AcquisitionReport);
+                    report_file.content = Some(content);
+                    virtual_files.insert(inode, report_file);
+                },
                 Err(e) => {
-                    error!("read error 0x1 for inode {ino}.");
+                    warn!(object = *object_number; "Could not build the acquisition report of object {object_number}.");
                     debug!("{e}");
-                    reply.error(ENOENT);
-                    return;
                 }
             }
-            let mut buffer = vec![0u8; size as usize];
-            debug!("Fill buffer by reading data at offset {offset} with buffer size of {size}.");
-            match self.zffreader.read(&mut buffer) {
-                Ok(_) => (),
-                Err(e) => {
-                    error!("read error 0x2 for inode {ino}.");
-                    debug!("{e}");
-                    reply.error(ENOENT);
-                    return
+
+            // only for physical objects
+            if obj_type == &ZffReaderObjectType::Physical {
+                let layout = chunkmap_layout_of_object(&mut zffreader, *object_number).map_err(|e| ZffMountError::zff(format!("An error occurred while trying to setup the chunkmap of object {object_number}."), e))?;
+                let inode = next_virtual_inode;
+                next_virtual_inode += 1;
+                inode_attributes_map.insert(inode, virtual_file_attr(layout.size(), &object_dir_attr));
+                let length_of_data = layout.length_of_data;
+                physical_objects.insert(*object_number, PhysicalObjectData {
+                    first_chunk_number: layout.first_chunk_number,
+                    chunk_size: layout.chunk_size,
+                    length_of_data,
+                });
+                virtual_files.insert(inode, VirtualFile::new(*object_number, object_number+1, CHUNKMAP_FILE_NAME, VirtualFileType::Chunkmap(layout)));
+
+                // the content is generated on the first access, as the chunk flags of the whole object are needed.
+                let inode = next_virtual_inode;
+                next_virtual_inode += 1;
+                inode_attributes_map.insert(inode, virtual_file_attr(UNREADABLE_RANGES_FILE_SIZE_ESTIMATE, &object_dir_attr));
+                let name = options.report_format.file_name(UNREADABLE_RANGES_FILE_STEM);
+                virtual_files.insert(inode, VirtualFile::new(*object_number, object_number+1, name, VirtualFileType::UnreadableRanges));
+
+                // one sidecar file per stored hash value (none, if the container stores no hash values).
+                if options.hash_sidecars {
+                    let image_name = image_names.get(object_number).cloned().unwrap_or_else(|| OsString::from(ZFF_PHYSICAL_OBJECT_NAME));
+                    let sidecars = hash_sidecars_of_object(&mut zffreader, *object_number, &image_name)
+                        .map_err(|e| ZffMountError::zff(format!("An error occurred while trying to read the stored hash values of object {object_number}."), e))?;
+                    for (name, content) in sidecars {
+                        let inode = next_virtual_inode;
+                        next_virtual_inode += 1;
+                        inode_attributes_map.insert(inode, virtual_file_attr(content.len() as u64, &object_dir_attr));
+                        let mut sidecar = VirtualFile::new(*object_number, object_number+1, name, VirtualFileType::HashSidecar);
+                        sidecar.content = Some(content);
+                        virtual_files.insert(inode, sidecar);
+                    }
+                }
+
+                // the split raw segments replace the zff_image.dd.
+                if let Some(segment_size) = options.split_raw {
+                    let number_of_segments = max(1, (length_of_data + segment_size - 1) / segment_size);
+                    let width = max(SPLIT_RAW_MIN_EXTENSION_WIDTH, number_of_segments.to_string().len());
+                    for segment_number in 0..number_of_segments {
+                        let offset = segment_number * segment_size;
+                        let length = min(segment_size, length_of_data - offset);
+                        let inode = next_virtual_inode;
+                        next_virtual_inode += 1;
+                        let name = format!("{SPLIT_RAW_FILE_PREFIX}.{:0width$}", segment_number + 1);
+                        inode_attributes_map.insert(inode, virtual_file_attr(length, &object_dir_attr));
+                        virtual_files.insert(inode, VirtualFile::new(*object_number, object_number+1, name, VirtualFileType::RawSegment { offset, length }));
+                    }
                 }
             }
-            reply.data(&buffer);
-        }            
-    }
+        }
+        // the encrypted objects, which could not be decrypted, are presented as locked placeholder directories.
+        if !options.hide_encrypted {
+            for object_number in object_list.iter().filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Encrypted).map(|(&k, _)| k) {
+                let dir_attr = CompactAttr::from(&locked_object_dir_attr(&mut zffreader, object_number));
+                inode_attributes_map.insert(SPECIAL_INODE_LOCKED_OBJECTS_START + object_number, dir_attr);
+                let content = locked_notice(object_number).into_bytes();
+                let inode = next_virtual_inode;
+                next_virtual_inode += 1;
+                inode_attributes_map.insert(inode, virtual_file_attr(content.len() as u64, &dir_attr));
+                let mut notice = VirtualFile::new(object_number, SPECIAL_INODE_LOCKED_OBJECTS_START + object_number, LOCKED_NOTICE_FILE_NAME, VirtualFileType::LockedNotice);
+                notice.content = Some(content);
+                virtual_files.insert(inode, notice);
+            }
+        }
+        debug!("{} virtual files added.", virtual_files.len());
 
-    fn readdir(
-    &mut self,
-    _req: &Request,
-    ino: u64,
-    _fh: u64,
-    offset: i64,
-    mut reply: ReplyDirectory,
-    ) {
-        let mut entries = Vec::new();
-        debug!("READDIR: Start readdir of inode {ino}");
+        let case_folded_lookup_table = if options.case_insensitive {
+            Some(filename_lookup_table.iter()
+                .map(|(object_number, lookup_table)| (*object_number, case_folded_lookup_table(lookup_table, &mut filenames)))
+                .collect())
+        } else {
+            None
+        };
+        debug!("Filename pool: {filenames}.");
+
+        // the acquisition errors are detected by the error flags of the chunks. Without the preloaded flags map, the header
+        // of every chunk would have to be read, so the files are only checked, if the damaged reads should fail.
+        let acquisition_errors = if preload_chunkmaps.flags || options.fail_damaged_reads {
+            info!("Checking the chunks for acquisition errors ...");
+            let acquisition_errors = acquisition_errors(&mut zffreader, &inode_reverse_map, &physical_objects)
+                .map_err(|e| ZffMountError::zff("An error occurred while trying to check the chunks for acquisition errors.", e))?;
+            if !acquisition_errors.is_empty() {
+                warn!("{} files contain data, which could not be read while acquiring it (see the xattr {XATTR_ACQUISITION_ERROR}).", acquisition_errors.len());
+            }
+            acquisition_errors
+        } else {
+            info!("Use --preload-chunk-flags-map to detect the files with acquisition errors.");
+            BTreeMap::new()
+        };
 
-        // sets the . directory which is always = ino
-        entries.push((ino, FileType::Directory, String::from(CURRENT_DIR)));
-        
-        // check if we are in root - directory and list objects
-        if ino == SPECIAL_INODE_ROOT_DIR {
-            // sets the parent directory
-            entries.push((SPECIAL_INODE_ROOT_DIR, FileType::Directory, String::from(PARENT_DIR)));
+        let cache = ZffFsCache {
+            object_list,
+            inode_reverse_map,
+            filenames,
+            filename_lookup_table,
+            case_folded_lookup_table,
+            inode_attributes_map,
+            virtual_files,
+            lookup_normalization: options.normalize_lookup,
+            original_names,
+            renamed_files,
+            image_names,
+            physical_objects,
+            source_owners,
+            unresolvable_hardlinks,
+            acquisition_errors,
+            owner_map: options.owner_map.clone(),
+            default_owner: (Uid::effective().into(), Gid::effective().into()),
+            encrypted_objects: encryption_states,
+        };
 
-            // append appropriate objects
-            for obj_number in self.cache.object_list.iter().filter(|(_, v)| v != &&ZffReaderObjectType::Encrypted).map(|(&k, _)| k) {
-                let object_inode = obj_number + 1; //+ 1 while inode 1 is the root dir
-                let name = format!("{OBJECT_PATH_PREFIX}{obj_number}");
-                entries.push((object_inode, FileType::Directory, name));
-            }
+        let root_object = root_object(&cache.object_list, &options);
+        let root_dir_attr = root_dir_attr(cache.object_list.keys().filter_map(|object_number| cache.attr(object_number+1)));
+        let preload = PreloadSummary {
+            mode: preload_mode.to_string(),
+            offsets: preload_chunkmaps.offsets,
+            sizes: preload_chunkmaps.sizes,
+            flags: preload_chunkmaps.flags,
+            samebytes: preload_chunkmaps.samebytes,
+        };
+        let mut zff_fs = Self {
+            zffreader,
+            shift_value,
+            cache,
+            samebytes_preloaded: preload_chunkmaps.samebytes,
+            flags_preloaded: preload_chunkmaps.flags,
+            open_flags: open_flags(&options),
+            opendir_flags: opendir_flags(&options),
+            latency_stats: if options.latency_stats { Some(Arc::new(Mutex::new(LatencyStats::default()))) } else { None },
+            panic_stats: PanicStats::default(),
+            chunk_cache: if options.cache_size > 0 { Some(ChunkCache::new(options.cache_size, preload_chunkmaps.flags)) } else { None },
+            read_chunks: 0,
+            activity: Arc::new(ActivityTracker::new()),
+            directory_listings: HashMap::new(),
+            file_handles: HashMap::new(),
+            next_handle: FIRST_HANDLE,
+            warned_symlink_targets: HashSet::new(),
+            owner: None,
+            inode_offset: 0,
+            root_object,
+            root_dir_attr,
+            preload,
+            object_list_xattr: Vec::new(),
+            options,
+        };
+        zff_fs.object_list_xattr = zff_fs.object_list_json();
 
-        } else if ino <= self.shift_value { //checks if the inode is a object folder
-            // sets the parent directory
-            entries.push((SPECIAL_INODE_ROOT_DIR, FileType::Directory, String::from(PARENT_DIR)));
+        if zff_fs.options.virtual_archives {
+            zff_fs.add_virtual_archives();
+        }
 
-            // set active object reader to appropriate inode
-            if let Err(e) = self.zffreader.set_active_object(ino-1) {
-                error!("An error occured while trying to readdir for inode {ino}: {e}");
-                reply.error(ENOENT);
-                return;
+        if zff_fs.options.verify_at_mount {
+            let verification_inodes: Vec<u64> = zff_fs.cache.virtual_files.iter()
+                .filter(|(_, file)| file.file_type == VirtualFileType::Verification)
+                .map(|(&inode, _)| inode)
+                .collect();
+            for inode in verification_inodes {
+                zff_fs.generate_virtual_file_content(inode).map_err(|e| ZffMountError::zff("An error occurred while trying to verify the objects.", e))?;
             }
-            //check object type and use the appropriate fn
-            match self.cache.object_list.get(&(ino-1)) {
-                Some(ZffReaderObjectType::Encrypted) | None => {
-                    error!("Could not find undecrypted object reader for object {}", ino-1);
-                    reply.error(ENOENT);
-                    return;
-                },
-                Some(ZffReaderObjectType::Physical) => match readdir_physical_object_root(&mut self.zffreader, self.shift_value) {
-                    Ok(mut content) => entries.append(&mut content),
-                    Err(e) => {
-                        error!("Error while trying to read content of object directory of object {}: {e}", ino-1);
-                        reply.error(ENOENT);
-                        return;
-                    }
-                },
-                Some(ZffReaderObjectType::Logical) => match readdir_logical_object_root(&mut self.zffreader, self.shift_value) {
-                    Ok(mut content) => entries.append(&mut content),
-                    Err(e) => {
-                        error!("Error while trying to read content of object directory of object {}: {e}", ino-1);
-                        reply.error(ENOENT);
-                        return;
+        }
+
+        info!("ZffFs successfully initialized and can be used now.");
+
+        Ok(zff_fs)
+    }
+
+    // returns true, if the samebytes of the chunks could be looked up without reading the chunk headers. The samebytes
+    // are only stored for chunks with the samebytes flag, so the flags map has to be preloaded, too.
+    fn samebytes_lookup_preloaded(&self) -> bool {
+        self.samebytes_preloaded && self.flags_preloaded
+    }
+
+    // returns the extended attributes of the given inode. The root directory contains the object list of the container.
+    fn xattrs(&self, inode: u64) -> Vec<(&'static str, Vec<u8>)> {
+        let mut xattrs = self.cache.xattrs(inode);
+        if inode == self.local_root_inode() {
+            xattrs.push((XATTR_OBJECT_COUNT, self.cache.object_list.len().to_string().into_bytes()));
+            xattrs.push((XATTR_OBJECTS, self.object_list_xattr.clone()));
+        }
+        xattrs
+    }
+
+    // returns true, if the given (internal) inode is a symlink.
+    fn is_symlink(&self, inode: u64) -> bool {
+        matches!(self.cache.inode_attributes_map.get(&inode), Some(attr) if attr.kind == FileType::Symlink)
+    }
+
+    // returns the original (not rebased) target of the given symlink.
+    fn symlink_target(&mut self, inode: u64) -> Result<Vec<u8>> {
+        let (object_no, file_no) = match self.cache.inode_reverse_map.get(&inode) {
+            Some(data) => *data,
+            None => return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+        };
+        prepare_zffreader_logical_file(&mut self.zffreader, object_no, file_no)?;
+        self.zffreader.seek(SeekFrom::Start(0))?;
+        let mut buffer = Vec::new();
+        self.zffreader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    // rewrites the given absolute symlink target to a target relative to the directory of the symlink, which points
+    // to the appropriate path inside the object. Returns None (and warns once per target), if the target was not acquired.
+    fn rebase_symlink_target(&mut self, object_no: u64, file_no: u64, parent_file_number: u64, target: &[u8]) -> Result<Option<Vec<u8>>> {
+        let object_dir_inode = object_no + 1;
+        let mut inodes = vec![object_dir_inode];
+        for component in target.split(|byte| *byte == b'/') {
+            match component {
+                b"" | b"." => (),
+                b".." => if inodes.len() > 1 { inodes.pop(); },
+                name => match self.cache.lookup_inode(object_no, *inodes.last().unwrap_or(&object_dir_inode), OsStr::from_bytes(name)) {
+                    Some(inode) => inodes.push(inode),
+                    None => {
+                        if self.warned_symlink_targets.insert(target.to_vec()) {
+                            warn!(object = object_no; "The symlink target {:?} of file {file_no} of object {object_no} was not acquired. The target will not be rebased.",
+                                String::from_utf8_lossy(target));
+                        }
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+
+        // the depth of the directory of the symlink below the object root.
+        let mut depth = 0;
+        let mut visited = HashSet::new();
+        let mut parent_file_number = parent_file_number;
+        while parent_file_number > 0 && visited.insert(parent_file_number) {
+            depth += 1;
+            self.zffreader.set_active_file(parent_file_number)?;
+            parent_file_number = self.zffreader.current_filemetadata()?.parent_file_number;
+        }
+
+        let mut rebased_target = if depth == 0 {
+            CURRENT_DIR.as_bytes().to_vec()
+        } else {
+            vec![PARENT_DIR; depth].join("/").into_bytes()
+        };
+        rebased_target.extend_from_slice(target);
+        Ok(Some(rebased_target))
+    }
+
+    /// Returns the tracker of the filesystem activity (e.g. to unmount an idle filesystem).
+    pub fn activity_tracker(&self) -> Arc<ActivityTracker> {
+        Arc::clone(&self.activity)
+    }
+
+    /// Sets the owner of all files and directories without an acquired owner (e.g. the identity after dropping the privileges).
+    pub fn set_owner(&mut self, uid: u32, gid: u32) {
+        self.owner = Some((uid, gid));
+    }
+
+    // returns the largest chunk size of all (decrypted) objects.
+    fn max_chunk_size(&mut self) -> u64 {
+        let object_numbers: Vec<u64> = self.cache.object_list.iter()
+            .filter(|(_, obj_type)| obj_type != &&ZffReaderObjectType::Encrypted)
+            .map(|(object_number, _)| *object_number)
+            .collect();
+        let mut max_chunk_size = 0;
+        for object_number in object_numbers {
+            if let Err(e) = self.zffreader.set_active_object(object_number) {
+                debug!("{e}");
+                continue;
+            }
+            if let Ok(header) = self.zffreader.active_object_header_ref() {
+                max_chunk_size = max(max_chunk_size, header.chunk_size);
+            }
+        }
+        max_chunk_size
+    }
+
+    // returns true, if the user of the given request is permitted to access the filesystem (see --restrict-to-uid).
+    pub(crate) fn access_permitted(&self, req: &Request<'_>, operation: &str) -> bool {
+        match &self.options.access_restriction {
+            Some(restriction) if !restriction.permits(req.uid()) => {
+                debug!("{operation}: access denied for uid {} (pid {}).", req.uid(), req.pid());
+                false
+            },
+            _ => true,
+        }
+    }
+
+    // returns a new (unique) file or directory handle.
+    fn next_handle(&mut self) -> u64 {
+        let fh = self.next_handle;
+        self.next_handle += 1;
+        fh
+    }
+
+    // replaces the activity tracker, so multiple containers share the same tracker.
+    pub(crate) fn set_activity_tracker(&mut self, activity: Arc<ActivityTracker>) {
+        self.activity = activity;
+    }
+
+    // sets the offset of all inodes presented to the kernel, so multiple containers never share inodes.
+    pub(crate) fn set_inode_offset(&mut self, inode_offset: u64) {
+        self.inode_offset = inode_offset;
+    }
+
+    // converts the inode presented to the kernel to the internal inode of this container.
+    // If an object is presented directly in the root directory, the root directory is the appropriate object directory.
+    fn local_inode(&self, inode: u64) -> u64 {
+        let inode = inode - self.inode_offset;
+        match self.root_object {
+            Some(object_number) if inode == SPECIAL_INODE_ROOT_DIR => object_number + 1,
+            _ => inode,
+        }
+    }
+
+    // converts the internal inode of this container to the inode presented to the kernel.
+    fn global_inode(&self, inode: u64) -> u64 {
+        let inode = match self.root_object {
+            Some(object_number) if inode == object_number + 1 => SPECIAL_INODE_ROOT_DIR,
+            _ => inode,
+        };
+        inode + self.inode_offset
+    }
+
+    // returns the internal inode of the presented root directory.
+    fn local_root_inode(&self) -> u64 {
+        self.local_inode(SPECIAL_INODE_ROOT_DIR + self.inode_offset)
+    }
+
+    // returns the given attributes with the inode (and the owner) presented to the kernel.
+    fn global_attr(&self, attr: &FileAttr) -> FileAttr {
+        let mut attr = *attr;
+        // the acquired owners are kept.
+        if let (Some((uid, gid)), false) = (self.owner, self.cache.source_owners.contains_key(&attr.ino)) {
+            attr.uid = uid;
+            attr.gid = gid;
+        }
+        attr.ino = self.global_inode(attr.ino);
+        attr
+    }
+
+    // returns the given attributes as replied to the given caller (uid, gid), i.e. with the owner of the caller (see --squash-owner).
+    // The cached attributes stay unchanged, the owner of the caller is only substituted into the reply.
+    pub(crate) fn caller_attr(&self, mut attr: FileAttr, caller: (u32, u32)) -> FileAttr {
+        if self.options.squash_owner == OwnerSquashing::Caller {
+            (attr.uid, attr.gid) = caller;
+        }
+        attr
+    }
+
+    // returns the given attributes as presented to the kernel (see global_attr) and replied to the given caller.
+    fn reply_attr(&self, attr: &FileAttr, caller: (u32, u32)) -> FileAttr {
+        self.caller_attr(self.global_attr(attr), caller)
+    }
+
+    // returns the TTL of the replied entries and attributes. The kernel caches the attributes per inode (and not per caller),
+    // so the squashed attributes of one caller must not be served to another caller from the cache.
+    pub(crate) fn attr_ttl(&self) -> Duration {
+        match (self.options.squash_owner, self.options.cache_forever) {
+            (OwnerSquashing::Caller, _) => Duration::ZERO,
+            (OwnerSquashing::None, true) => CACHE_FOREVER_TTL,
+            (OwnerSquashing::None, false) => TTL,
+        }
+    }
+
+    // returns the TTL of the replied entry and attributes of the given (internal) inode. The size of a virtual file is only
+    // an estimate until its content was generated, so these attributes are never cached longer than the default TTL.
+    fn inode_ttl(&self, inode: u64) -> Duration {
+        match self.cache.virtual_files.get(&inode) {
+            Some(file) if file.size_is_estimated() => min(self.attr_ttl(), TTL),
+            _ => self.attr_ttl(),
+        }
+    }
+
+    // returns the attributes of the root directory of this container (with the inode presented to the kernel).
+    pub(crate) fn root_attr(&self) -> FileAttr {
+        match self.cache.attr(self.local_root_inode()) {
+            Some(attr) => self.global_attr(&attr),
+            None => self.global_attr(&self.root_dir_attr),
+        }
+    }
+
+    /// Returns the object numbers of all (decrypted) physical objects.
+    pub fn physical_object_numbers(&self) -> Vec<u64> {
+        self.cache.object_list.iter()
+            .filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Physical)
+            .map(|(&object_number, _)| object_number)
+            .collect()
+    }
+
+    /// Returns the path of the directory of the given object, relative to the root directory of this container.
+    pub fn object_path(&self, object_number: u64) -> PathBuf {
+        if self.root_object == Some(object_number) {
+            PathBuf::new()
+        } else {
+            PathBuf::from(format!("{}{object_number}", self.object_dir_prefix()))
+        }
+    }
+
+    // returns the object numbers of the encrypted objects, which are presented as locked placeholder directories.
+    fn locked_object_numbers(&self) -> Vec<u64> {
+        if self.options.hide_encrypted {
+            return Vec::new();
+        }
+        self.cache.object_list.iter()
+            .filter(|(_, obj_type)| obj_type == &&ZffReaderObjectType::Encrypted)
+            .map(|(&object_number, _)| object_number)
+            .collect()
+    }
+
+    // returns the object number, if the given inode is the placeholder directory of a locked object.
+    fn locked_object_number(&self, inode: u64) -> Option<u64> {
+        if inode >= 1 << CONTAINER_INODE_SHIFT || !self.cache.inode_attributes_map.contains_key(&inode) {
+            return None;
+        }
+        let object_number = inode.checked_sub(SPECIAL_INODE_LOCKED_OBJECTS_START)?;
+        match self.cache.object_list.get(&object_number) {
+            Some(ZffReaderObjectType::Encrypted) => Some(object_number),
+            _ => None,
+        }
+    }
+
+    // returns the prefix of the object directories.
+    fn object_dir_prefix(&self) -> &str {
+        self.options.object_dir_prefix.as_deref().unwrap_or(OBJECT_PATH_PREFIX)
+    }
+
+    /// Returns the filename of the physical object file (e.g. zff_image.dd) of the given object.
+    pub fn image_name(&self, object_number: u64) -> OsString {
+        self.cache.image_name(object_number)
+    }
+
+    /// Measures the sequential and random read throughput of all physical objects by using the configured
+    /// preload settings. Objects which could not be read are skipped.
+    pub fn benchmark(&mut self, config: &BenchmarkConfig) -> Vec<BenchmarkResult> {
+        let mut results = Vec::new();
+        for object_number in self.physical_object_numbers() {
+            match benchmark_object(&mut self.zffreader, object_number, config) {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    error!(object = object_number; "An error occurred while trying to benchmark object {object_number}.");
+                    debug!("{e}");
+                }
+            }
+        }
+        results
+    }
+
+    /// Measures the throughput of the cached metadata lookups, which are used by getattr (by inode) and lookup (by name).
+    /// Every inode and every filename of the lookup tables is looked up once per round.
+    pub fn benchmark_metadata(&self, rounds: u64) -> MetadataMeasurement {
+        info!("Benchmark: {rounds} rounds of metadata lookups ...");
+        let inodes: Vec<u64> = self.cache.inode_attributes_map.keys().copied().collect();
+        let start = Instant::now();
+        for _ in 0..rounds {
+            for inode in &inodes {
+                black_box(self.cache.inode_attributes_map.get(inode));
+                black_box(self.cache.inode_reverse_map.get(inode));
+            }
+        }
+        let getattr_duration = start.elapsed();
+
+        let mut name_lookups = 0;
+        let start = Instant::now();
+        for _ in 0..rounds {
+            for (object_number, lookup_table) in &self.cache.filename_lookup_table {
+                for (filename, entries) in lookup_table {
+                    for (parent_inode, _) in entries {
+                        black_box(self.cache.lookup_inode(*object_number, *parent_inode, filename));
+                        name_lookups += 1;
+                    }
+                }
+            }
+        }
+        MetadataMeasurement::new(inodes.len() as u64 * rounds, getattr_duration, name_lookups, start.elapsed())
+    }
+
+    // generates the content of the given virtual file (if not already done) and updates the appropriate file attributes.
+    fn generate_virtual_file_content(&mut self, inode: u64) -> Result<()> {
+        let (object_number, file_type) = match self.cache.virtual_files.get(&inode) {
+            Some(file) if file.content.is_none() => (file.object_number, file.file_type.clone()),
+            _ => return Ok(()),
+        };
+        let content = match file_type {
+            VirtualFileType::Verification => {
+                warn!(object = object_number; "Verifying object {object_number}. This could take a long time on large objects.");
+                verify_object(&mut self.zffreader, object_number)?.to_string().into_bytes()
+            },
+            VirtualFileType::UnreadableRanges => {
+                let ranges = match self.cache.physical_objects.get(&object_number) {
+                    Some(physical_object) => damaged_ranges(
+                        &mut self.zffreader,
+                        physical_object.first_chunk_number,
+                        physical_object.chunk_size,
+                        physical_object.length_of_data)?,
+                    None => Vec::new(),
+                };
+                render_unreadable_ranges(&ranges, self.options.report_format)
+            },
+            // will be generated on the fly by each read operation.
+            VirtualFileType::Chunkmap(_) | VirtualFileType::RawSegment { .. } | VirtualFileType::Archive(_) => return Ok(()),
+            // the content is set while creating the file.
+            VirtualFileType::HashSidecar | VirtualFileType::AcquisitionReport | VirtualFileType::LockedNotice => return Ok(()),
+        };
+        if let Some(file_attr) = self.cache.inode_attributes_map.get_mut(&inode) {
+            file_attr.set_size(content.len() as u64);
+        }
+        if let Some(file) = self.cache.virtual_files.get_mut(&inode) {
+            file.content = Some(content);
+        }
+        Ok(())
+    }
+
+    // replies the entry of the file with the given name in the given parent directory of the given object (logical objects only).
+    // returns the attributes of the given inode. If the inode is missing in the attributes map, the attributes are built
+    // by using the object and file of the inode reverse map and inserted into the attributes map.
+    fn file_attr(&mut self, inode: u64) -> Option<FileAttr> {
+        if let Some(file_attr) = self.cache.attr(inode) {
+            return Some(file_attr);
+        }
+        let (object_number, file_number) = *self.cache.inode_reverse_map.get(&inode)?;
+        match self.build_file_attr(object_number, file_number) {
+            Ok(file_attr) => {
+                debug!("The attributes of inode {inode} were missing in the attributes map and have been rebuilt.");
+                self.cache.inode_attributes_map.insert(inode, CompactAttr::from(&file_attr));
+                self.cache.attr(inode)
+            },
+            Err(e) => {
+                warn!(object = object_number, inode = inode; "The attributes of inode {inode} could not be built.");
+                debug!("{e}");
+                None
+            }
+        }
+    }
+
+    // returns the inode of the file or directory at the given path of the given object (the object directory for the root path).
+    // The path is resolved by the lookup tables, so the presented (e.g. sanitized or renamed) names are used.
+    fn resolve_path(&self, object_number: u64, path: &Path) -> std::result::Result<u64, ZffMountError> {
+        let not_found = || ZffMountError::PathNotFound { object: object_number, path: path.to_path_buf() };
+        if !self.cache.object_list.contains_key(&object_number) {
+            return Err(ZffMountError::ObjectNotFound { object: object_number });
+        }
+        let mut components = Vec::new();
+        for component in path.components() {
+            match component {
+                Component::RootDir | Component::CurDir => (),
+                Component::Normal(name) => components.push(name),
+                Component::ParentDir | Component::Prefix(_) => return Err(not_found()),
+            }
+        }
+        let mut inode = object_number + 1;
+        if components.is_empty() {
+            return Ok(inode);
+        }
+        // the files of physical objects (the image and the virtual files) are not part of the lookup tables.
+        if self.cache.object_list.get(&object_number) != Some(&ZffReaderObjectType::Logical) {
+            return Err(not_found());
+        }
+        for component in components {
+            inode = self.cache.lookup_inode(object_number, inode, component).ok_or_else(not_found)?;
+        }
+        Ok(inode)
+    }
+
+    // builds the attributes of the given file (or of the data file of the given physical object, if the file number is 0)
+    // in the same way as while building the attributes map.
+    fn build_file_attr(&mut self, object_number: u64, file_number: u64) -> Result<FileAttr> {
+        self.zffreader.set_active_object(object_number)?;
+        let sparse_chunk_size = if self.samebytes_lookup_preloaded() && !self.options.logical_blocks {
+            Some(self.zffreader.active_object_header_ref()?.chunk_size)
+        } else {
+            None
+        };
+        let object_footer = self.zffreader.active_object_footer()?;
+        if file_number == 0 {
+            return file_attr_of_physical_object(&mut self.zffreader, &object_footer, self.shift_value, sparse_chunk_size);
+        }
+        let object_dir_attr = match self.cache.attr(object_number+1) {
+            Some(object_dir_attr) => object_dir_attr,
+            None => file_attr_of_object_footer(&object_footer),
+        };
+        let missing_times_fallback = if self.options.epoch_for_missing_times { None } else { Some(&object_dir_attr) };
+        let (inode, file_attr, has_source_owner) = file_attr_entry(
+            &mut self.zffreader, object_number, file_number, self.shift_value, sparse_chunk_size, missing_times_fallback)?;
+        if has_source_owner {
+            self.cache.source_owners.insert(inode, (file_attr.uid, file_attr.gid));
+        }
+        Ok(file_attr)
+    }
+
+    fn reply_lookup_entry(&mut self, object_number: u64, parent: u64, name: &OsStr, caller: (u32, u32), reply: ReplyEntry) {
+        let inode = match self.cache.lookup_inode(object_number, parent, name) {
+            Some(inode) => inode,
+            None => {
+                debug!("Error while trying to lookup for {:?} in directory with inode {parent}", name);
+                reply.error(ENOENT);
+                return;
+            }
+        };
+        match self.file_attr(inode) {
+            Some(attr) => {
+                debug!("LOOKUP: returned entry attr: {:?}", &attr);
+                reply.entry(&self.inode_ttl(attr.ino), &self.reply_attr(&attr, caller), DEFAULT_ENTRY_GENERATION);
+            },
+            None => {
+                error!(operation = "lookup", object = object_number, parent = parent, inode = inode; "An error occurred while trying to get file attributes of inode {inode}.");
+                reply.error(ENOENT);
+            }
+        }
+    }
+
+    // returns the entries of the given directory (with the internal inodes) in a deterministic order.
+    fn directory_entries(&mut self, ino: u64) -> std::result::Result<Vec<(u64, FileType, Filename)>, c_int> {
+        let mut entries = Vec::new();
+        debug!("READDIR: Start readdir of inode {ino}");
+
+        // sets the . directory which is always = ino
+        entries.push((ino, FileType::Directory, OsString::from(CURRENT_DIR)));
+        
+        // check if we are in root - directory and list objects
+        if ino == SPECIAL_INODE_ROOT_DIR {
+            // sets the parent directory
+            entries.push((SPECIAL_INODE_ROOT_DIR, FileType::Directory, OsString::from(PARENT_DIR)));
+
+            // append appropriate objects
+            for obj_number in self.cache.object_list.iter().filter(|(_, v)| v != &&ZffReaderObjectType::Encrypted).map(|(&k, _)| k) {
+                let object_inode = obj_number + 1; //+ 1 while inode 1 is the root dir
+                let name = OsString::from(format!("{}{obj_number}", self.object_dir_prefix()));
+                entries.push((object_inode, FileType::Directory, name));
+            }
+
+            // append the placeholders of the locked objects
+            for obj_number in self.locked_object_numbers() {
+                let name = OsString::from(format!("{}{obj_number}{LOCKED_OBJECT_DIR_SUFFIX}", self.object_dir_prefix()));
+                entries.push((SPECIAL_INODE_LOCKED_OBJECTS_START + obj_number, FileType::Directory, name));
+            }
+
+        } else if ino <= self.shift_value { //checks if the inode is a object folder
+            // sets the parent directory
+            entries.push((SPECIAL_INODE_ROOT_DIR, FileType::Directory, OsString::from(PARENT_DIR)));
+
+            // set active object reader to appropriate inode
+            if let Err(e) = self.zffreader.set_active_object(ino-1) {
+                error!(operation = "readdir", inode = ino; "An error occured while trying to readdir for inode {ino}: {e}");
+                return Err(ENOENT);
+            }
+            //check object type and use the appropriate fn
+            match self.cache.object_list.get(&(ino-1)) {
+                Some(ZffReaderObjectType::Encrypted) | None => {
+                    error!(operation = "readdir", object = ino-1, inode = ino; "Could not find undecrypted object reader for object {}", ino-1);
+                    return Err(ENOENT);
+                },
+                Some(ZffReaderObjectType::Physical) => match self.cache.physical_objects.get(&(ino-1)) {
+                    Some(physical_object) => entries.append(&mut readdir_physical_object_root(
+                        physical_object,
+                        self.shift_value,
+                        self.cache.image_name(ino-1),
+                        self.cache.raw_segments(ino-1))),
+                    None => {
+                        error!(operation = "readdir", object = ino-1, inode = ino; "Could not find the footer data of the physical object {}", ino-1);
+                        return Err(ENOENT);
+                    }
+                },
+                Some(ZffReaderObjectType::Logical) => match readdir_logical_object_root(
+                    &mut self.zffreader,
+                    ino-1,
+                    self.shift_value,
+                    self.cache.renamed_files.get(&(ino-1)),
+                    self.options.sanitize_names) {
+                    Ok(mut content) => entries.append(&mut content),
+                    Err(e) => {
+                        error!(operation = "readdir", object = ino-1, inode = ino; "Error while trying to read content of object directory of object {}: {e}", ino-1);
+                        return Err(ENOENT);
                     },
                 },
                 Some(ZffReaderObjectType::Virtual) => todo!(), //TODO
             }
+            // append the virtual files of this object (the split raw segments are already listed above).
+            for (inode, file) in self.cache.virtual_files.iter()
+                .filter(|(_, file)| file.parent_inode == ino && !matches!(file.file_type, VirtualFileType::RawSegment { .. })) {
+                entries.push((*inode, FileType::RegularFile, OsString::from(&file.name)));
+            }
+        } else if self.locked_object_number(ino).is_some() {
+            // sets the parent directory
+            entries.push((SPECIAL_INODE_ROOT_DIR, FileType::Directory, OsString::from(PARENT_DIR)));
+            for (inode, file) in self.cache.virtual_files.iter().filter(|(_, file)| file.parent_inode == ino) {
+                entries.push((*inode, FileType::RegularFile, OsString::from(&file.name)));
+            }
         //the following should only affect logical objects.
         } else {
             // setup self ino file
             let (object_no, file_no) = match self.cache.inode_reverse_map.get(&ino) {
                 Some(x) => x,
                 None =>  {
-                    error!("Could not find inode {ino} in inode reverse map.");
-                    reply.error(ENOENT);
-                    return;
+                    error!(operation = "readdir", inode = ino; "Could not find inode {ino} in inode reverse map.");
+                    return Err(ENOENT);
                 }
             };
             let filemetadata_ref = match prepare_zffreader_logical_file(&mut self.zffreader, *object_no, *file_no) {
                 Ok(fm) => fm,
                 Err(e) =>  {
-                    error!("An error occurred while trying to prepare zffreader: {e}");
-                    reply.error(ENOENT);
-                    return;
+                    error!(operation = "readdir", object = *object_no, inode = ino; "An error occurred while trying to prepare zffreader: {e}");
+                    return Err(ENOENT);
                 },
             };
 
             //set parent directory entry
-            entries.push((filemetadata_ref.parent_file_number+self.shift_value, FileType::Directory, String::from(PARENT_DIR)));
+            entries.push((filemetadata_ref.parent_file_number+self.shift_value, FileType::Directory, OsString::from(PARENT_DIR)));
             let children = {
                 let mut buffer = Vec::new();
                 //seeks the reader to start position to read all content of the directory (again)
                 if let Err(e) = self.zffreader.rewind() {
-                    error!("Error while trying to seek the children-list of file {file_no} / object {object_no}.");
+                    error!(operation = "readdir", object = *object_no, inode = ino; "Error while trying to seek the children-list of file {file_no} / object {object_no}.");
                     debug!("{e}");
-                    reply.error(ENOENT);
-                    return;
+                    return Err(ENOENT);
                 }
                 if let Err(e) = self.zffreader.read_to_end(&mut buffer) {
-                    error!("Error while trying to read children list of file {file_no} / object {object_no}.");
+                    error!(operation = "readdir", object = *object_no, inode = ino; "Error while trying to read children list of file {file_no} / object {object_no}.");
                     debug!("{e}");
-                    reply.error(ENOENT);
-                    return;
+                    return Err(ENOENT);
                 };
                 match Vec::<u64>::decode_directly(&mut buffer.as_slice()) {
                     Ok(vec) => vec,
                     Err(e) => {
-                        error!("An error occurred while decoding list of files of file {file_no} / object {object_no}.");
+                        error!(operation = "readdir", object = *object_no, inode = ino; "An error occurred while decoding list of files of file {file_no} / object {object_no}.");
                         debug!("{e}");
-                        reply.error(ENOENT);
-                        return;
+                        return Err(ENOENT);
                     }
                 }
             };
 
             //set children entries.
-            let mut children_entries = match readdir_entries_file(&mut self.zffreader, self.shift_value, &children) {
+            let mut children_entries = match readdir_entries_file(
+                &mut self.zffreader,
+                *object_no,
+                self.shift_value,
+                &children,
+                self.cache.renamed_files.get(object_no),
+                self.options.sanitize_names) {
                 Ok(entries) => entries,
                 Err(e) => {
-                    error!("An error occurred while reading directory of file {file_no} / object {object_no}.");
+                    error!(operation = "readdir", object = *object_no, inode = ino; "An error occurred while reading directory of file {file_no} / object {object_no}.");
                     debug!("{e}");
-                    reply.error(ENOENT);
-                    return;
+                    return Err(ENOENT);
                 }
             };
             entries.append(&mut children_entries);
         };
-
-        for (index, entry) in entries.into_iter().skip(offset as usize).enumerate() {
-            let (inode, file_type, name) = entry;
-            debug!("READDIR entry added: inode: {inode}, index: {}, file_type: {:?}, name: {name}", offset + index as i64 + 1, file_type);
-            if reply.add(inode, offset + index as i64 + 1, file_type, name) {
+        // the names of the listing share the interned filenames, so an open directory does not hold a copy of every name.
+        Ok(entries.into_iter().map(|(inode, file_type, name)| (inode, file_type, self.cache.filenames.share(name))).collect())
+    }
+
+    fn read_virtual_file(&mut self, inode: u64, offset: u64, size: u32, reply: ReplyData) {
+        let (object_number, file_type) = match self.cache.virtual_files.get(&inode) {
+            Some(file) => (file.object_number, file.file_type.clone()),
+            None => {
+                error!("Error while trying to read data from virtual file {inode}: file not found.");
+                reply.error(ENOENT);
+                return;
+            }
+        };
+        match file_type {
+            VirtualFileType::Chunkmap(layout) => {
+                match read_chunkmap(&mut self.zffreader, &layout, offset, size) {
+                    Ok(data) => reply.data(&data),
+                    Err(e) => {
+                        error!("An error occurred while trying to read the chunkmap of virtual file {inode}.");
+                        debug!("{e}");
+                        reply.error(EIO);
+                    }
+                }
+                return;
+            },
+            VirtualFileType::RawSegment { offset: segment_offset, length } => {
+                // the read is limited to the window of the segment, the following data belongs to the next segment.
+                let size = min(size as u64, length.saturating_sub(offset));
+                match read_object_data(&mut self.zffreader, object_number, segment_offset + offset, size) {
+                    Ok(data) => reply.data(&data),
+                    Err(e) => {
+                        error!(object = object_number, inode = inode; "An error occurred while trying to read the split raw segment {inode}.");
+                        debug!("{e}");
+                        reply.error(EIO);
+                    }
+                }
+                return;
+            },
+            VirtualFileType::Archive(layout) => {
+                match self.read_archive(&layout, offset, size) {
+                    Ok(data) => reply.data(&data),
+                    Err(errno) => {
+                        error!(object = object_number, inode = inode; "An error occurred while trying to read the virtual archive {inode}.");
+                        reply.error(errno);
+                    }
+                }
+                return;
+            },
+            VirtualFileType::Verification | VirtualFileType::UnreadableRanges | VirtualFileType::HashSidecar | VirtualFileType::AcquisitionReport
+            | VirtualFileType::LockedNotice => (),
+        }
+        if let Err(e) = self.generate_virtual_file_content(inode) {
+            error!("An error occurred while trying to generate the content of virtual file {inode}.");
+            debug!("{e}");
+            reply.error(EIO);
+            return;
+        }
+        let content = match self.cache.virtual_files.get(&inode).and_then(|file| file.content.as_ref()) {
+            Some(content) => content,
+            None => {
+                error!("Error while trying to read data from virtual file {inode}: content not available.");
+                reply.error(EIO);
+                return;
+            }
+        };
+        let start = min(offset, content.len() as u64) as usize;
+        let end = min(start + size as usize, content.len());
+        reply.data(&content[start..end]);
+    }
+
+    // executes the given operation of the given (kernel) inode and catches its panics, so a single corrupted file does not
+    // take down the whole filesystem. The reply is moved into the operation and is therefore dropped by the unwinding,
+    // which makes fuser reply with EIO. The operations of inodes, which panicked repeatedly, fail immediately with EIO.
+    // Each operation selects its object and file again and the caches are only filled with complete entries, so the state
+    // is still usable after a caught panic.
+    fn guarded<Rep: ErrorReply, F: FnOnce(&mut Self, Rep)>(&mut self, operation: Operation, ino: u64, reply: Rep, f: F) {
+        let local_ino = self.local_inode(ino);
+        if self.panic_stats.is_failing(local_ino) {
+            debug!("{operation}: inode {local_ino} panicked repeatedly before, failing fast.");
+            reply.error(EIO);
+            return;
+        }
+        if let Err(payload) = panic_hook::catch(|| f(self, reply)) {
+            error!("{operation} of inode {local_ino} panicked: {}. Replied with EIO.", panic_hook::panic_message(&*payload));
+            self.panic_stats.record(local_ino);
+        }
+    }
+
+    fn handle_read(&mut self, ino: u64, offset: i64, size: u32, reply: ReplyData) {
+        let ino = self.local_inode(ino);
+        self.activity.touch();
+        let mut timer = OperationTimer::start(Operation::Read, &self.latency_stats, || format!("ino={ino} off={offset} size={size}"));
+        if offset < 0 {
+            error!(operation = "read", inode = ino; "READ: offset >= 0 -> offset = {offset}");
+            reply.error(ENOENT);
+            return;
+        }
+        if self.cache.virtual_files.contains_key(&ino) {
+            self.read_virtual_file(ino, offset as u64, size, reply);
+            return;
+        }
+        let cache_lookups = self.chunk_cache.as_ref().map(ChunkCache::lookups);
+        self.read_chunks = 0;
+        let result = self.read_data(ino, offset as u64, size);
+        if let Some(timer) = &mut timer {
+            // a read is a cache hit, if all chunks were served by the chunk cache.
+            let cache_hit = match (cache_lookups, self.chunk_cache.as_ref().map(ChunkCache::lookups)) {
+                (Some((hits_before, misses_before)), Some((hits, misses))) => hits > hits_before && misses == misses_before,
+                _ => false,
+            };
+            timer.add_details(|| format!("chunks={} cache={}", self.read_chunks, if cache_hit { "hit" } else { "miss" }));
+        }
+        match result {
+            Ok(data) => reply.data(&data),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    // reads the data of the given (internal) inode of a file or physical object (the virtual files are read by read_virtual_file).
+    // Returns the errno, which should be replied, if the data could not be read.
+    fn read_data(&mut self, ino: u64, offset: u64, size: u32) -> std::result::Result<Cow<'static, [u8]>, c_int> {
+        if self.options.fail_damaged_reads && self.cache.acquisition_errors.contains_key(&ino) {
+            warn!(operation = "read", inode = ino; "Refusing to read inode {ino}, as it contains data, which could not be acquired.");
+            return Err(EIO);
+        }
+        // empty files (and unresolvable hardlinks) have no data, which could be read by the reader.
+        if self.cache.unresolvable_hardlinks.contains(&ino) || matches!(self.cache.inode_attributes_map.get(&ino), Some(attr) if attr.size == 0) {
+            return Ok(Cow::Borrowed(&[]));
+        }
+        if ino < self.shift_value {
+            unreachable!()
+        } else {
+            let (object_no, file_no) = match self.cache.inode_reverse_map.get(&ino) {
+                Some(data) => data,
+                None => {
+                    error!(operation = "read", inode = ino; "Error while trying to read data from inode {ino}: Inode not found in inode reverse map.");
+                    return Err(ENOENT);
+                }
+            };
+
+            //check if this is a physical object.
+            // we've stored inodes to physical objects in inode map by using the file number 0 as placeholder earlier.
+            let chunk_range = if *file_no == 0 {
+                if let Err(e) = self.zffreader.set_active_object(*object_no) {
+                    error!(operation = "read", object = *object_no, inode = ino; "An error occurred while trying to set object {object_no} as active.");
+                    debug!("{e}");
+                    return Err(ENOENT);
+                }
+                self.cache.physical_objects.get(object_no).map(|physical_object| ChunkRange {
+                    first_chunk_number: physical_object.first_chunk_number,
+                    chunk_size: physical_object.chunk_size,
+                    length_of_data: physical_object.length_of_data,
+                })
+            } else {
+                // if the object is a logical object, we have to do some more stuff.
+                // sets the appropriate object and file active and returns the appropriate file-  
+                // metadata (which is needed for the chunk cache).
+                let (first_chunk_number, length_of_data) = match prepare_zffreader_logical_file(&mut self.zffreader, *object_no, *file_no) {
+                    Err(e) => {
+                        error!(operation = "read", object = *object_no, inode = ino; "Error while trying to set file {file_no} of object {object_no} active.");
+                        debug!("{e}");
+                        return Err(ENOENT);
+                    },
+                    Ok(metadata) => (metadata.first_chunk_number, metadata.length_of_data)
+                };
+                self.zffreader.active_object_header_ref().ok().map(|header| ChunkRange {
+                    first_chunk_number,
+                    chunk_size: header.chunk_size,
+                    length_of_data,
+                })
+            };
+
+            self.read_chunks = chunk_range.map(|chunk_range| chunk_range.chunks_of(offset, size as u64)).unwrap_or(0);
+
+            // the samebytes chunks (e.g. the zero chunks of a sparse disk image) are served without reading them.
+            if let Some(chunk_range) = chunk_range.filter(|chunk_range| self.samebytes_lookup_preloaded() && chunk_range.chunk_size > 0) {
+                return read_samebytes_aware(&mut self.zffreader, self.chunk_cache.as_mut(), chunk_range, offset, size).map_err(|e| {
+                    error!(operation = "read", inode = ino; "An error occurred while trying to read the chunks of inode {ino}.");
+                    debug!("{e}");
+                    EIO
+                });
+            }
+
+            if let (Some(chunk_cache), Some(chunk_range)) = (&mut self.chunk_cache, chunk_range) {
+                if chunk_range.chunk_size > 0 {
+                    return match read_cached(&mut self.zffreader, chunk_cache, chunk_range, offset, size) {
+                        Ok(data) => Ok(Cow::Owned(data)),
+                        Err(e) => {
+                            error!(operation = "read", inode = ino; "An error occurred while trying to read the chunks of inode {ino}.");
+                            debug!("{e}");
+                            Err(EIO)
+                        }
+                    };
+                }
+            }
+            
+            match self.zffreader.seek(SeekFrom::Start(offset)) {
+                Ok(_) => (),
+                Err(e) => {
+                    error!(operation = "read", inode = ino; "read error 0x1 for inode {ino}.");
+                    debug!("{e}");
+                    return Err(EIO);
+                }
+            }
+            // the buffer ends at the end of the file, if the length is known.
+            let size = match chunk_range {
+                Some(chunk_range) => min(size as u64, chunk_range.length_of_data.saturating_sub(offset)) as usize,
+                None => size as usize,
+            };
+            let mut buffer = vec![0u8; size];
+            debug!("Fill buffer by reading data at offset {offset} with buffer size of {size}.");
+            match read_full(&mut self.zffreader, &mut buffer) {
+                Ok(bytes_read) => buffer.truncate(bytes_read),
+                Err(e) => {
+                    error!(operation = "read", inode = ino; "read error 0x2 for inode {ino}.");
+                    debug!("{e}");
+                    // e.g. a connection error of a remote input or a media error of a block device.
+                    return Err(EIO);
+                }
+            }
+            Ok(Cow::Owned(buffer))
+        }
+    }
+
+    fn handle_readdir(&mut self, ino: u64, fh: u64, offset: i64, mut reply: ReplyDirectory) {
+        let ino = self.local_inode(ino);
+        self.activity.touch();
+        let _timer = OperationTimer::start(Operation::Readdir, &self.latency_stats, || format!("ino={ino} off={offset}"));
+        // the directory listing of the open directory handle is used, so the offsets are stable between the readdir calls.
+        // The listing is only built here, if the directory was not opened by opendir.
+        let uncached_entries = if self.directory_listings.contains_key(&fh) {
+            Vec::new()
+        } else {
+            match self.directory_entries(ino) {
+                Ok(entries) => entries,
+                Err(errno) => {
+                    reply.error(errno);
+                    return;
+                }
+            }
+        };
+        let entries = self.directory_listings.get(&fh).unwrap_or(&uncached_entries);
+
+        // the entries are added lazily (without cloning the names), until the reply buffer is full.
+        let local_root_inode = self.local_root_inode();
+        for (index, (inode, file_type, name)) in entries.iter().enumerate().skip(offset as usize) {
+            // the parent of the root directory is the root directory of the mount point (if multiple containers are mounted).
+            let inode = if ino == local_root_inode && **name == *PARENT_DIR {
+                SPECIAL_INODE_ROOT_DIR
+            } else {
+                self.global_inode(*inode)
+            };
+            let next_offset = index as i64 + 1;
+            debug!("READDIR entry added: inode: {inode}, index: {next_offset}, file_type: {:?}, name: {:?}", file_type, name);
+            if reply.add(inode, next_offset, *file_type, name) {
                 break;
             }
         }
         reply.ok();
     }
 
-    fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+    fn handle_lookup(&mut self, parent: u64, name: &OsStr, caller: (u32, u32), reply: ReplyEntry) {
+        let parent = self.local_inode(parent);
+        self.activity.touch();
+        let _timer = OperationTimer::start(Operation::Lookup, &self.latency_stats, || format!("parent={parent} name={:?}", name));
         debug!("Starting LOOKUP request: parent inode: \"{parent}\"; name: {:?}.", name);
-        let name = match name.to_str() {
-            Some(name) => name,
-            None => {
-                error!("LOOKUP: Error while trying to convert name: {:?}", name);
-                reply.error(ENOENT);
-                return;
+        //handle virtual files
+        if let Some(inode) = self.cache.virtual_file_inode(parent, name) {
+            match self.cache.attr(inode) {
+                Some(attr) => {
+                    debug!("LOOKUP: returned entry attr: {:?}", &attr);
+                    reply.entry(&self.inode_ttl(attr.ino), &self.reply_attr(&attr, caller), DEFAULT_ENTRY_GENERATION);
+                },
+                None => {
+                    error!(operation = "lookup", parent = parent, inode = inode; "An error occurred while trying to get file attributes of virtual file {inode}.");
+                    reply.error(ENOENT);
+                }
             }
-        };
-        //handle root directory with the "object_" directories.
+            return;
+        }
+
+        //handle root directory with the object directories (object_N by default).
         if parent == SPECIAL_INODE_ROOT_DIR {
-            let mut split = name.rsplit(OBJECT_PREFIX);
-            let object_number = match split.next() {
+            // object directory names are always valid UTF-8.
+            let name = match name.to_str() {
+                Some(name) => name,
                 None => {
-                    error!("LOOKUP: object prefix not in filename. This is an application bug. The filename is {name}");
+                    debug!("LOOKUP: {:?} is not a valid object directory name.", name);
+                    reply.error(ENOENT);
+                    return;
+                }
+            };
+            // all other names (e.g. the probes of the shell completion) are no object directories.
+            let inode = object_dir_inode(name, self.object_dir_prefix())
+                .filter(|inode| !name.ends_with(LOCKED_OBJECT_DIR_SUFFIX) || self.locked_object_number(*inode).is_some());
+            let inode = match inode {
+                Some(inode) => inode,
+                None => {
+                    //This is a workaround: Some Desktop environments trying to lookup for folders like ".Trash" or ".Trash-1000", but these do not exist.
+                    if name == DEFAULT_TRASHFOLDER_NAME || name == format!("{DEFAULT_TRASHFOLDER_NAME}-{}", Uid::effective()) {
+                        debug!("Cannot access trashfolders.");
+                    } else {
+                        //this is only a debuggable message, as the bash/zsh completition could generate a huge number of those lookups.
+                        debug!("LOOKUP: {name} is not an object directory.");
+                    }
                     reply.error(ENOENT);
                     return;
-                },
-                Some(unparsed_object_number) => match unparsed_object_number.parse::<u64>() {
-                    Ok(object_number) => object_number,
-                    Err(e) => {
-                        //This is a workaround: Some Desktop environments trying to lookup for folders like ".Trash" or ".Trash-1000", but these do not exist.
-                        if  unparsed_object_number == DEFAULT_TRASHFOLDER_NAME || unparsed_object_number == format!("{DEFAULT_TRASHFOLDER_NAME}-{}", Uid::effective()) {
-                            debug!("Cannot access trashfolders.");
-                            reply.error(ENOENT);
-                            return;
-                        }
-                        //this is only a debuggable error, as the bash/zsh completition could generate a huge number of those messages.
-                        debug!("LOOKUP: Error while trying to parse the object: \"{unparsed_object_number}\" for original name: {name}; {e}");
-                        reply.error(ENOENT);
-                        return;
-                    },
                 },
             };
 
             // get the appropriate attributes of the object directory - by using object number +1 shift value.
-            let file_attr = match self.cache.inode_attributes_map.get(&(object_number+1)) {
+            let file_attr = match self.cache.attr(inode) {
                 Some(file_attr) => file_attr,
                 None => {
-                    debug!("GETATTR: unknown inode number: {}", object_number+1);
+                    debug!("GETATTR: unknown inode number: {inode}");
                     reply.error(ENOENT);
                     return;
                 },
             };
             debug!("LOOKUP: returned entry attr: {:?}", &file_attr);
-            reply.entry(&TTL, file_attr, DEFAULT_ENTRY_GENERATION);
+            reply.entry(&self.inode_ttl(file_attr.ino), &self.reply_attr(&file_attr, caller), DEFAULT_ENTRY_GENERATION);
 
         } else if parent <= self.shift_value { //checks if the parent is a object folder
             // set active object reader to appropriate parent
             if let Err(e) = self.zffreader.set_active_object(parent-1) {
-                error!("LOOKUP: An error occured while trying to lookup for inode {parent}.");
+                error!(operation = "lookup", parent = parent; "LOOKUP: An error occured while trying to lookup for inode {parent}.");
                 debug!("{e}");
                 reply.error(ENOENT);
                 return;
@@ -538,23 +1700,21 @@ impl<R: Read + Seek> Filesystem for ZffFs<R> {
             //check object type and use the appropriate fn
             match self.cache.object_list.get(&(parent-1)) {
                 Some(ZffReaderObjectType::Encrypted) | None => {
-                    error!("LOOKUP: Could not find undecrypted object reader for object {}", parent-1);
+                    error!(operation = "lookup", object = parent-1, parent = parent; "LOOKUP: Could not find undecrypted object reader for object {}", parent-1);
                     reply.error(ENOENT);
                     return;
                 },
-                Some(ZffReaderObjectType::Physical) => if name == ZFF_PHYSICAL_OBJECT_NAME {
-                    let object_footer = match self.zffreader.active_object_footer() {
-                        Ok(footer) => match footer { ObjectFooter::Physical(phy) => phy, _ => unreachable!() },
-                        Err(e) => {
-                            error!("LOOKUP: cannot find the object footer of object {}", parent-1);
-                            debug!("{e}");
+                Some(ZffReaderObjectType::Physical) => if name == self.cache.image_name(parent-1) && self.options.split_raw.is_none() {
+                    let ino = match self.cache.physical_objects.get(&(parent-1)) {
+                        Some(physical_object) => physical_object.first_chunk_number + self.shift_value,
+                        None => {
+                            error!(operation = "lookup", object = parent-1, parent = parent; "LOOKUP: cannot find the footer data of object {}", parent-1);
                             reply.error(ENOENT);
                             return;
                         }
                     };
-                    let ino = object_footer.first_chunk_number + self.shift_value;
                     // get the appropriate attributes of the object data file.
-                    let file_attr = match self.cache.inode_attributes_map.get(&ino) {
+                    let file_attr = match self.file_attr(ino) {
                         Some(file_attr) => file_attr,
                         None => {
                             debug!("GETATTR: unknown inode number: {}", ino);
@@ -563,61 +1723,33 @@ impl<R: Read + Seek> Filesystem for ZffFs<R> {
                         },
                     };
                     debug!("LOOKUP: returned entry attr: {:?}", &file_attr);
-                    reply.entry(&TTL, file_attr, DEFAULT_ENTRY_GENERATION);
+                    reply.entry(&self.inode_ttl(file_attr.ino), &self.reply_attr(&file_attr, caller), DEFAULT_ENTRY_GENERATION);
                 } else {
-                    debug!("Error while trying to lookup for {name} in object {}", parent-1);
+                    debug!("Error while trying to lookup for {:?} in object {}", name, parent-1);
                     reply.error(ENOENT);
                     return;
                 },
-                Some(ZffReaderObjectType::Logical) => if let Some(lookup_table_entries) = self.cache.filename_lookup_table.get(name) {
-                    for (parent_inode, inode) in lookup_table_entries {
-                        if parent == *parent_inode {
-                            match self.cache.inode_attributes_map.get(inode) {
-                                Some(attr) => {
-                                    debug!("LOOKUP: returned entry attr: {:?}", &attr);
-                                    reply.entry(&TTL, attr, DEFAULT_ENTRY_GENERATION);
-                                    return;
-                                },
-                                None => {
-                                    error!("An error occurred while trying to get file attributes of inode {inode}.");
-                                    reply.error(ENOENT);
-                                    return;
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    debug!("Error while trying to lookup for {name} in object {}", parent-1);
-                    reply.error(ENOENT);
-                    return;
-                }
+                Some(ZffReaderObjectType::Logical) => self.reply_lookup_entry(parent-1, parent, name, caller, reply),
                 Some(ZffReaderObjectType::Virtual) => todo!(), //TODO
             }
-        } else if let Some(lookup_table_entries) = self.cache.filename_lookup_table.get(name) {
-            for (parent_inode, inode) in lookup_table_entries {
-                if parent == *parent_inode {
-                    match self.cache.inode_attributes_map.get(inode) {
-                        Some(attr) => {
-                            debug!("LOOKUP: returned entry-attr: {:?}.", attr);
-                            reply.entry(&TTL, attr, DEFAULT_ENTRY_GENERATION);
-                            return;
-                        },
-                        None => {
-                            error!("An error occurred while trying to get file attributes of inode {inode}.");
-                            reply.error(ENOENT);
-                            return;
-                        }
-                    }
-                }
-            }
         } else {
-            debug!("Error while trying to lookup for {name} in object {}", parent-1);
-            reply.error(ENOENT);
-            return;
+            // the parent directory determines the object in which the lookup takes place.
+            let object_number = match self.cache.inode_reverse_map.get(&parent) {
+                Some((object_number, _)) => *object_number,
+                None => {
+                    debug!("LOOKUP: unknown parent inode number: {parent}");
+                    reply.error(ENOENT);
+                    return;
+                }
+            };
+            self.reply_lookup_entry(object_number, parent, name, caller, reply);
         }
     }
 
-    fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+    fn handle_readlink(&mut self, ino: u64, reply: ReplyData) {
+        let ino = self.local_inode(ino);
+        self.activity.touch();
+        let _timer = OperationTimer::start(Operation::Readlink, &self.latency_stats, || format!("ino={ino}"));
         if ino < self.shift_value {
             error!("Inode {ino} is not a link.");
            reply.error(ENOENT);
@@ -655,6 +1787,8 @@ impl<R: Read + Seek> Filesystem for ZffFs<R> {
                     reply.error(ENOENT);
                     return;
                 }
+                let parent_file_number = filemetadata.parent_file_number;
+                let (object_no, file_no) = (*object_no, *file_no);
                 
                 match self.zffreader.seek(SeekFrom::Start(0)) {
                     Ok(_) => (),
@@ -675,16 +1809,34 @@ impl<R: Read + Seek> Filesystem for ZffFs<R> {
                         return
                     }
                 }
+                if self.options.rebase_symlinks && buffer.starts_with(b"/") {
+                    match self.rebase_symlink_target(object_no, file_no, parent_file_number, &buffer) {
+                        Ok(Some(rebased_target)) => buffer = rebased_target,
+                        Ok(None) => (),
+                        Err(e) => {
+                            error!("An error occurred while trying to rebase the symlink target of inode {ino}.");
+                            debug!("{e}");
+                        }
+                    }
+                }
                 reply.data(&buffer);
             }
         }
     }
 
-    fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
-        match self.cache.inode_attributes_map.get(&ino) {
-            Some(file_attr) => reply.attr(&TTL, file_attr),
+    fn handle_getattr(&mut self, ino: u64, fh: Option<u64>, caller: (u32, u32), reply: ReplyAttr) {
+        let ino = self.local_inode(ino);
+        self.activity.touch();
+        let _timer = OperationTimer::start(Operation::Getattr, &self.latency_stats, || format!("ino={ino}"));
+        // fast path for open files (e.g. fstat).
+        if let Some(file_attr) = fh.and_then(|fh| self.file_handles.get(&fh)) {
+            reply.attr(&self.inode_ttl(file_attr.ino), &self.reply_attr(file_attr, caller));
+            return;
+        }
+        match self.file_attr(ino) {
+            Some(file_attr) => reply.attr(&self.inode_ttl(file_attr.ino), &self.reply_attr(&file_attr, caller)),
             None => if ino == SPECIAL_INODE_ROOT_DIR {
-                reply.attr(&TTL, &DEFAULT_ROOT_DIR_ATTR)
+                reply.attr(&self.inode_ttl(self.root_dir_attr.ino), &self.reply_attr(&self.root_dir_attr, caller))
             } else {
                 debug!("GETATTR: unknown inode number: {ino}");
                 reply.error(ENOENT);
@@ -693,65 +1845,569 @@ impl<R: Read + Seek> Filesystem for ZffFs<R> {
     }
 }
 
-fn enter_password_dialog(obj_no: u64) -> Option<String> {
-    match PasswordDialog::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("Enter the password for object {obj_no}"))
-        .interact() {
-            Ok(pw) => Some(pw),
-            Err(_) => None
+impl<R: Read + Seek> Filesystem for ZffFs<R> {
+    fn init(&mut self, _req: &Request<'_>, config: &mut KernelConfig) -> std::result::Result<(), c_int> {
+        let max_readahead = match self.options.max_readahead {
+            Some(max_readahead) => max_readahead,
+            None => {
+                let max_chunk_size = self.max_chunk_size();
+                u32::try_from(max_chunk_size.saturating_mul(DEFAULT_READAHEAD_CHUNKS)).unwrap_or(u32::MAX)
+            }
+        };
+        match config.set_max_readahead(max_readahead) {
+            Ok(_) => info!("Max readahead set to {max_readahead} bytes."),
+            Err(nearest) => {
+                warn!("The kernel does not accept a max readahead of {max_readahead} bytes, using {nearest} bytes instead.");
+                // the nearest value is always accepted.
+                let _ = config.set_max_readahead(nearest);
+            }
+        }
+
+        if let Some(max_background) = self.options.max_background {
+            match config.set_max_background(max_background) {
+                Ok(_) => info!("Max background requests set to {max_background}."),
+                Err(nearest) => {
+                    warn!("The kernel does not accept {max_background} max background requests, using {nearest} instead.");
+                    let _ = config.set_max_background(nearest);
+                }
+            }
+        }
+        if let Some(congestion_threshold) = self.options.congestion_threshold {
+            match config.set_congestion_threshold(congestion_threshold) {
+                Ok(_) => info!("Congestion threshold set to {congestion_threshold}."),
+                Err(nearest) => {
+                    warn!("The kernel does not accept a congestion threshold of {congestion_threshold}, using {nearest} instead.");
+                    let _ = config.set_congestion_threshold(nearest);
+                }
+            }
+        }
+
+        let mut capabilities = vec![(FUSE_ASYNC_READ, "async read"), (FUSE_PARALLEL_DIROPS, "parallel dirops")];
+        if self.options.cache_forever {
+            capabilities.push((FUSE_CACHE_SYMLINKS, "cache symlinks"));
+        }
+        for (capability, name) in capabilities {
+            match config.add_capabilities(capability) {
+                Ok(_) => info!("FUSE capability {name} enabled."),
+                Err(_) => info!("FUSE capability {name} is not supported by the kernel."),
+            }
+        }
+        Ok(())
+    }
+
+    fn destroy(&mut self) {
+        if let Some(latency_stats) = &self.latency_stats {
+            match latency_stats.lock() {
+                Ok(latency_stats) => info!("Latency statistics:\n{latency_stats}"),
+                Err(poisoned) => info!("Latency statistics:\n{}", poisoned.into_inner()),
+            }
+        }
+        if let Some(chunk_cache) = &self.chunk_cache {
+            info!("{chunk_cache}");
+        }
+        if self.panic_stats.caught() > 0 {
+            warn!("{}", self.panic_stats);
+        }
+    }
+
+    fn open(&mut self, req: &Request<'_>, ino: u64, _flags: i32, reply: ReplyOpen) {
+        if !self.access_permitted(req, "OPEN") {
+            reply.error(EACCES);
+            return;
+        }
+        self.activity.open_handle();
+        let ino = self.local_inode(ino);
+        if matches!(self.cache.virtual_files.get(&ino), Some(file) if file.size_is_estimated()) {
+            // the size of a virtual file is only an estimate until its content was generated, so we bypass the page cache.
+            reply.opened(0, FOPEN_DIRECT_IO);
+        } else if self.cache.virtual_files.contains_key(&ino) {
+            reply.opened(0, self.open_flags);
+        } else {
+            // the attributes of the file are cached by the handle, so getattr of open files needs no lookup.
+            let fh = match self.cache.attr(ino) {
+                Some(attr) => {
+                    let fh = self.next_handle();
+                    self.file_handles.insert(fh, attr);
+                    fh
+                },
+                None => 0,
+            };
+            reply.opened(fh, self.open_flags);
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.activity.release_handle();
+        self.file_handles.remove(&fh);
+        reply.ok();
+    }
+
+    fn read(
+        &mut self,
+        req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock: Option<u64>,
+        reply: ReplyData,
+    ) {
+        if !self.access_permitted(req, "READ") {
+            reply.error(EACCES);
+            return;
+        }
+        self.guarded(Operation::Read, ino, reply, |fs, reply| fs.handle_read(ino, offset, size, reply));
+    }
+
+    fn lseek(&mut self, req: &Request<'_>, ino: u64, _fh: u64, offset: i64, whence: i32, reply: ReplyLseek) {
+        if !self.access_permitted(req, "LSEEK") {
+            reply.error(EACCES);
+            return;
+        }
+        let ino = self.local_inode(ino);
+        if offset < 0 {
+            error!("LSEEK: offset >= 0 -> offset = {offset}");
+            reply.error(EINVAL);
+            return;
+        }
+        let offset = offset as u64;
+        let find_hole = match whence {
+            SEEK_HOLE => true,
+            SEEK_DATA => false,
+            _ => {
+                debug!("LSEEK: unsupported whence {whence} for inode {ino}.");
+                reply.error(EINVAL);
+                return;
+            }
+        };
+
+        // holes are only detectable for physical objects with preloaded flags and samebytes maps.
+        let layout = match self.cache.inode_reverse_map.get(&ino) {
+            Some((object_no, 0)) if self.samebytes_lookup_preloaded() => self.cache.chunkmap_layout(*object_no).cloned(),
+            _ => None,
+        };
+        let result = match layout {
+            Some(layout) => match seek_hole_or_data(&mut self.zffreader, &layout, offset, find_hole) {
+                Ok(result) => result,
+                Err(e) => {
+                    error!("LSEEK: An error occurred while trying to seek the next hole/data of inode {ino}.");
+                    debug!("{e}");
+                    reply.error(EIO);
+                    return;
+                }
+            },
+            None => {
+                // fallback: the whole file is handled as data.
+                let size = match self.cache.inode_attributes_map.get(&ino) {
+                    Some(attr) => attr.size,
+                    None => {
+                        debug!("LSEEK: unknown inode number: {ino}");
+                        reply.error(ENOENT);
+                        return;
+                    }
+                };
+                if offset >= size {
+                    None
+                } else if find_hole {
+                    Some(size)
+                } else {
+                    Some(offset)
+                }
+            }
+        };
+        match result {
+            Some(new_offset) => reply.offset(new_offset as i64),
+            None => reply.error(ENXIO),
+        }
+    }
+
+    fn opendir(&mut self, req: &Request<'_>, ino: u64, _flags: i32, reply: ReplyOpen) {
+        if !self.access_permitted(req, "OPENDIR") {
+            reply.error(EACCES);
+            return;
+        }
+        let ino = self.local_inode(ino);
+        self.activity.touch();
+        match self.directory_entries(ino) {
+            Ok(entries) => {
+                let fh = self.next_handle();
+                self.directory_listings.insert(fh, entries);
+                reply.opened(fh, self.opendir_flags);
+            },
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn releasedir(&mut self, _req: &Request<'_>, _ino: u64, fh: u64, _flags: i32, reply: ReplyEmpty) {
+        self.directory_listings.remove(&fh);
+        reply.ok();
+    }
+
+    fn readdir(
+    &mut self,
+    req: &Request,
+    ino: u64,
+    fh: u64,
+    offset: i64,
+    reply: ReplyDirectory,
+    ) {
+        if !self.access_permitted(req, "READDIR") {
+            reply.error(EACCES);
+            return;
+        }
+        self.guarded(Operation::Readdir, ino, reply, |fs, reply| fs.handle_readdir(ino, fh, offset, reply));
+    }
+
+    fn lookup(&mut self, req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if !self.access_permitted(req, "LOOKUP") {
+            reply.error(EACCES);
+            return;
+        }
+        let caller = (req.uid(), req.gid());
+        self.guarded(Operation::Lookup, parent, reply, |fs, reply| fs.handle_lookup(parent, name, caller, reply));
+    }
+
+    fn readlink(&mut self, req: &Request<'_>, ino: u64, reply: ReplyData) {
+        if !self.access_permitted(req, "READLINK") {
+            reply.error(EACCES);
+            return;
+        }
+        self.guarded(Operation::Readlink, ino, reply, |fs, reply| fs.handle_readlink(ino, reply));
+    }
+
+    fn getxattr(&mut self, req: &Request<'_>, ino: u64, name: &OsStr, size: u32, reply: ReplyXattr) {
+        if !self.access_permitted(req, "GETXATTR") {
+            reply.error(EACCES);
+            return;
+        }
+        self.activity.touch();
+        let ino = self.local_inode(ino);
+        if name == XATTR_SYMLINK_TARGET && self.is_symlink(ino) {
+            match self.symlink_target(ino) {
+                Ok(target) => reply_xattr_data(&target, size, reply),
+                Err(e) => {
+                    error!("An error occurred while trying to read the symlink target of inode {ino}.");
+                    debug!("{e}");
+                    reply.error(EIO);
+                }
+            }
+            return;
+        }
+        let xattrs = self.xattrs(ino);
+        let value = match xattrs.iter().find(|(xattr_name, _)| OsStr::new(xattr_name) == name) {
+            Some((_, value)) => value,
+            None => {
+                debug!("GETXATTR: attribute {:?} not found for inode {ino}.", name);
+                reply.error(ENODATA);
+                return;
+            }
+        };
+        reply_xattr_data(value, size, reply);
+    }
+
+    fn listxattr(&mut self, req: &Request<'_>, ino: u64, size: u32, reply: ReplyXattr) {
+        if !self.access_permitted(req, "LISTXATTR") {
+            reply.error(EACCES);
+            return;
+        }
+        self.activity.touch();
+        let ino = self.local_inode(ino);
+        let mut data = Vec::new();
+        for (xattr_name, _) in self.xattrs(ino) {
+            data.extend_from_slice(xattr_name.as_bytes());
+            data.push(0);
+        }
+        if self.is_symlink(ino) {
+            data.extend_from_slice(XATTR_SYMLINK_TARGET.as_bytes());
+            data.push(0);
+        }
+        reply_xattr_data(&data, size, reply);
+    }
+
+    fn getattr(&mut self, req: &Request, ino: u64, fh: Option<u64>, reply: ReplyAttr) {
+        if !self.access_permitted(req, "GETATTR") {
+            reply.error(EACCES);
+            return;
+        }
+        let caller = (req.uid(), req.gid());
+        self.guarded(Operation::Getattr, ino, reply, |fs, reply| fs.handle_getattr(ino, fh, caller, reply));
+    }
+}
+
+// returns the total number of chunks of the given objects. The number is only known (by the footers), if all objects are physical objects.
+fn number_of_chunks<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_list: &BTreeMap<u64, ZffReaderObjectType>) -> Option<u64> {
+    let mut number_of_chunks = 0;
+    for (object_number, obj_type) in object_list {
+        if obj_type != &ZffReaderObjectType::Physical {
+            return None;
+        }
+        number_of_chunks += chunkmap_layout_of_object(zffreader, *object_number).ok()?.number_of_chunks;
+    }
+    Some(number_of_chunks)
+}
+
+// returns the object, which content should be presented directly in the root directory (if any).
+fn root_object(object_list: &BTreeMap<u64, ZffReaderObjectType>, options: &ZffFsOptions) -> Option<u64> {
+    if options.object.is_some() {
+        return options.object;
+    }
+    if !options.flatten_single {
+        return None;
+    }
+    let decrypted_objects: Vec<u64> = object_list.iter()
+        .filter(|(_, obj_type)| obj_type != &&ZffReaderObjectType::Encrypted)
+        .map(|(&object_number, _)| object_number)
+        .collect();
+    match decrypted_objects[..] {
+        [object_number] => Some(object_number),
+        _ => {
+            warn!("The container contains {} decrypted objects, the layout will not be flattened.", decrypted_objects.len());
+            None
+        }
+    }
+}
+
+// replies the size of the data (if size is 0) or the data itself.
+fn reply_xattr_data(data: &[u8], size: u32, reply: ReplyXattr) {
+    if size == 0 {
+        reply.size(data.len() as u32);
+    } else if (size as usize) < data.len() {
+        reply.error(ERANGE);
+    } else {
+        reply.data(data);
+    }
+}
+
+// returns the FOPEN_* flags which are used to reply open requests.
+fn open_flags(options: &ZffFsOptions) -> u32 {
+    let mut flags = 0;
+    if options.direct_io {
+        flags |= FOPEN_DIRECT_IO;
+    }
+    if options.keep_page_cache {
+        flags |= FOPEN_KEEP_CACHE;
+    }
+    flags
+}
+
+// returns the FOPEN_* flags which are used to reply opendir requests.
+fn opendir_flags(options: &ZffFsOptions) -> u32 {
+    // the listings are cached by the kernel and kept on the next opendir, as a directory never changes.
+    if options.cache_forever {
+        FOPEN_CACHE_DIR | FOPEN_KEEP_CACHE
+    } else {
+        0
+    }
+}
+
+// reads into the given buffer until it is full or the end of the data is reached and returns the number of read bytes.
+// A single read call of the reader returns at most the data of one chunk, so a request which spans multiple chunks
+// needs multiple read calls.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut bytes_read = 0;
+    while bytes_read < buffer.len() {
+        match reader.read(&mut buffer[bytes_read..]) {
+            Ok(0) => break,
+            Ok(n) => bytes_read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(bytes_read)
+}
+
+// decrypts the given object by using the given password and returns true, if the object was decrypted successfully.
+fn decrypt_object<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64, pw: String) -> bool {
+    match zffreader.decrypt_object(object_number, pw) {
+        Ok(o_type) => {
+            info!(object = object_number; "Object {object_number} ({o_type} object) decrypted successfully");
+            true
+        },
+        Err(e) => {
+            warn!(object = object_number; "Could not decrypt object {object_number}: {e}");
+            false
+        },
+    }
+}
+
+// asks the user, if the password of the given object should be entered again.
+fn confirm_retry_dialog(obj_no: u64) -> bool {
+    ConfirmDialog::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Object {obj_no} could not be decrypted. Enter another password?"))
+        .default(false)
+        .interact()
+        .unwrap_or(false)
+}
+
+fn enter_password_dialog(obj_no: u64) -> Option<String> {
+    match PasswordDialog::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Enter the password for object {obj_no}"))
+        .interact() {
+            Ok(pw) => Some(pw),
+            Err(_) => None
+        }
+}
+
+// lists the zff_image.dd or the split raw segments (if given).
+fn readdir_physical_object_root(
+    physical_object: &PhysicalObjectData,
+    shift_value: u64,
+    image_name: OsString,
+    raw_segments: Vec<(u64, OsString)>) -> Vec<(u64, FileType, OsString)> {
+    if !raw_segments.is_empty() {
+        return raw_segments.into_iter().map(|(inode, name)| (inode, FileType::RegularFile, name)).collect();
+    }
+    vec![(
+        physical_object.first_chunk_number+shift_value, 
+        FileType::RegularFile, 
+        image_name
+        )]
+}
+
+fn readdir_logical_object_root<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    shift_value: u64,
+    renamed_files: Option<&BTreeMap<u64, Filename>>,
+    sanitize_names: bool) -> Result<Vec<(u64, FileType, OsString)>> {
+    if let ObjectFooter::Logical(footer) = zffreader.active_object_footer()? {
+        readdir_entries_file(zffreader, object_number, shift_value, footer.root_dir_filenumbers(), renamed_files, sanitize_names)
+    } else {
+        Err(ZffError::new(ZffErrorKind::MismatchObjectType, "physical"))
+    }
+}
+
+fn readdir_entries_file<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    shift_value: u64,
+    children: &Vec<u64>,
+    renamed_files: Option<&BTreeMap<u64, Filename>>, //<File number, presented filename>
+    sanitize_names: bool) -> Result<Vec<(u64, FileType, OsString)>> {
+    let mut entries = Vec::new();
+    for filenumber in children {
+        // a single malformed file should not hide the other files of the directory.
+        match readdir_entry_file(zffreader, object_number, shift_value, *filenumber, renamed_files, sanitize_names) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!("The file {filenumber} could not be listed and will be skipped.");
+                debug!("{e}");
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn readdir_entry_file<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    shift_value: u64,
+    filenumber: u64,
+    renamed_files: Option<&BTreeMap<u64, Filename>>, //<File number, presented filename>
+    sanitize_names: bool) -> Result<(u64, FileType, OsString)> {
+    zffreader.set_active_file(filenumber)?;
+    let filemetadata = zffreader.current_filemetadata()?;
+    let mut inode = file_inode(filemetadata, object_number, filenumber, shift_value);
+    let filetype = if filemetadata.file_type == ZffFileType::Hardlink {
+        match resolve_hardlink(zffreader, filenumber)? {
+            HardlinkTarget::Resolved(target_filenumber) => {
+                inode = file_inode(zffreader.current_filemetadata()?, object_number, target_filenumber, shift_value);
+                convert_filetype(zffreader)?
+            },
+            // unresolvable hardlinks are presented as empty regular files.
+            HardlinkTarget::Unresolvable(_) | HardlinkTarget::Missing(_) => FileType::RegularFile,
         }
+    } else {
+        convert_filetype(zffreader)?
+    };
+    // the filename of the active file, i.e. of the resolved file (or of the hardlink itself, if it could not be resolved).
+    let filename = current_filename(zffreader)?;
+    // duplicate filenames are presented by the name, which was determined while building the lookup table.
+    let filename = match renamed_files.and_then(|renamed_files| renamed_files.get(&filenumber)) {
+        Some(renamed_filename) => renamed_filename.to_os_string(),
+        None if sanitize_names => sanitize_filename(&filename),
+        None => filename,
+    };
+    Ok((inode, filetype, filename))
 }
 
-fn readdir_physical_object_root<R: Read + Seek>(zffreader: &mut ZffReader<R>, shift_value: u64) -> Result<Vec<(u64, FileType, String)>> {
-    let chunk_no = match zffreader.active_object_footer()? {
-        ObjectFooter::Physical(footer) => footer.first_chunk_number,
-        _ => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, "logical")),
-    };
-    Ok(vec![(
-        chunk_no+shift_value, 
-        FileType::RegularFile, 
-        ZFF_PHYSICAL_OBJECT_NAME.to_string()
-        )])
+// returns the filename of the active file (from the file metadata, or from the file header if the metadata does not contain it).
+// Note: zff stores the filenames as UTF-8 strings, so the original bytes of a non-UTF-8 name (e.g. latin-1) are already replaced
+// while acquiring the container and could not be restored here. The names are matched byte-wise against the names of the kernel,
+// so such a file is accessible by its (lossy) presented name.
+fn current_filename<R: Read + Seek>(zffreader: &mut ZffReader<R>) -> Result<OsString> {
+    match &zffreader.current_filemetadata()?.filename {
+        Some(filename) => Ok(OsString::from(filename)),
+        None => Ok(OsString::from(zffreader.current_fileheader()?.filename)),
+    }
 }
 
-fn readdir_logical_object_root<R: Read + Seek>(zffreader: &mut ZffReader<R>, shift_value: u64) -> Result<Vec<(u64, FileType, String)>> {
-    if let ObjectFooter::Logical(footer) = zffreader.active_object_footer()? {
-        readdir_entries_file(zffreader, shift_value, footer.root_dir_filenumbers())
+// returns the inode of the given file. Files without any chunk (e.g. empty files) have no own first chunk number,
+// so they are placed in a separate inode range by their object number and file number to get a unique inode.
+fn file_inode(filemetadata: &FileMetadata, object_number: u64, filenumber: u64, shift_value: u64) -> u64 {
+    if filemetadata.number_of_chunks == 0 {
+        SPECIAL_INODE_EMPTY_FILES_START + (object_number << EMPTY_FILE_INODE_OBJECT_SHIFT) + filenumber
     } else {
-        Err(ZffError::new(ZffErrorKind::MismatchObjectType, "physical"))
+        filemetadata.first_chunk_number + shift_value
     }
 }
 
-fn readdir_entries_file<R: Read + Seek>(zffreader: &mut ZffReader<R>, shift_value: u64, children: &Vec<u64>) -> Result<Vec<(u64, FileType, String)>> {
-    let mut entries = Vec::new();
-    for filenumber in children {
-        zffreader.set_active_file(*filenumber)?;
-        let mut filemetadata = zffreader.current_filemetadata()?.clone();
-        let mut zff_filetype = filemetadata.file_type;
-        if zff_filetype == ZffFileType::Hardlink {
-            let mut buffer = Vec::new();
-            zffreader.rewind()?;
-            zffreader.read_to_end(&mut buffer)?;
-            let original_filenumber = u64::decode_directly(&mut buffer.as_slice())?;
-            zffreader.set_active_file(original_filenumber)?;
-            filemetadata = zffreader.current_filemetadata()?.clone();
-            zff_filetype = filemetadata.file_type;
-        }
-        let inode = filemetadata.first_chunk_number + shift_value;
-        let filetype = convert_filetype(&zff_filetype, zffreader)?;
-        let filename = match filemetadata.filename {
-            Some(ftype) => ftype,
-            None => zffreader.current_fileheader()?.filename
-        };
-        entries.push((inode, filetype, filename.to_string()));
-    }
+/// The result of the resolution of a hardlink.
+enum HardlinkTarget {
+    /// The file number of the resolved (non-hardlink) file, which is set active.
+    Resolved(u64),
+    /// The hardlink chain (file numbers) could not be resolved (e.g. a cycle or too many hops).
+    /// The original hardlink is set active again.
+    Unresolvable(Vec<u64>),
+    /// The target file number (e.g. of a malformed or truncated object) does not exist.
+    /// The original hardlink is set active again.
+    Missing(u64),
+}
 
-    Ok(entries)
+// resolves the given hardlink (and chained hardlinks up to MAX_HARDLINK_HOPS) and sets the resolved file active.
+fn resolve_hardlink<R: Read + Seek>(zffreader: &mut ZffReader<R>, filenumber: u64) -> Result<HardlinkTarget> {
+    let mut chain = vec![filenumber];
+    let mut current_filenumber = filenumber;
+    for _ in 0..MAX_HARDLINK_HOPS {
+        let mut buffer = Vec::new();
+        zffreader.set_active_file(current_filenumber)?;
+        zffreader.rewind()?;
+        zffreader.read_to_end(&mut buffer)?;
+        let target_filenumber = u64::decode_directly(&mut buffer.as_slice())?;
+        if chain.contains(&target_filenumber) {
+            chain.push(target_filenumber);
+            zffreader.set_active_file(filenumber)?;
+            return Ok(HardlinkTarget::Unresolvable(chain));
+        }
+        chain.push(target_filenumber);
+        if let Err(e) = zffreader.set_active_file(target_filenumber) {
+            debug!("{e}");
+            zffreader.set_active_file(filenumber)?;
+            return Ok(HardlinkTarget::Missing(target_filenumber));
+        }
+        if zffreader.current_filemetadata()?.file_type != ZffFileType::Hardlink {
+            return Ok(HardlinkTarget::Resolved(target_filenumber));
+        }
+        current_filenumber = target_filenumber;
+    }
+    zffreader.set_active_file(filenumber)?;
+    Ok(HardlinkTarget::Unresolvable(chain))
 }
 
-// hardlinks should be handled before calling this method.
-fn convert_filetype<R: Read + Seek>(in_type: &ZffFileType, zffreader: &mut ZffReader<R>) -> Result<FileType> {
-    let filetype = match in_type {
+// returns the filetype of the active file. Hardlinks should be handled before calling this method.
+fn convert_filetype<R: Read + Seek>(zffreader: &mut ZffReader<R>) -> Result<FileType> {
+    let filetype = match &zffreader.current_filemetadata()?.file_type {
         ZffFileType::File => FileType::RegularFile,
         ZffFileType::Directory => FileType::Directory,
         ZffFileType::Symlink => FileType::Symlink,
@@ -778,7 +2434,8 @@ fn convert_filetype<R: Read + Seek>(in_type: &ZffFileType, zffreader: &mut ZffRe
 // returns the number of entries which were added.
 fn inode_reverse_map_add_object<R: Read + Seek>(
     zffreader: &mut ZffReader<R>,
-    inode_reverse_map: &mut BTreeMap<u64, (u64, u64)>,
+    inode_reverse_map: &mut HashMap<u64, (u64, u64)>,
+    unresolvable_hardlinks: &mut BTreeSet<u64>,
     object_number: u64,
     shift_value: u64) -> Result<u64> {
     zffreader.set_active_object(object_number)?;
@@ -789,16 +2446,23 @@ fn inode_reverse_map_add_object<R: Read + Seek>(
                 zffreader.set_active_file(*filenumber)?;
 
                 let filemetadata = zffreader.current_filemetadata()?;
-                let mut inode = filemetadata.first_chunk_number + shift_value;
+                let mut inode = file_inode(filemetadata, object_number, *filenumber, shift_value);
                 
                 // checks if the file is a hardlink. In that case, the original file hould be added
                 if filemetadata.file_type == ZffFileType::Hardlink {
-                    let mut buffer = Vec::new();
-                    zffreader.read_to_end(&mut buffer)?;
-                    let original_filenumber = u64::decode_directly(&mut buffer.as_slice())?;
-                    zffreader.set_active_file(original_filenumber)?;
-                    let filemetadata = zffreader.current_filemetadata()?.clone();
-                    inode = filemetadata.first_chunk_number + shift_value;
+                    match resolve_hardlink(zffreader, *filenumber)? {
+                        HardlinkTarget::Resolved(target_filenumber) => inode = file_inode(zffreader.current_filemetadata()?, object_number, target_filenumber, shift_value),
+                        HardlinkTarget::Unresolvable(chain) => {
+                            warn!(object = object_number; "The hardlink {filenumber} of object {object_number} could not be resolved (file numbers: {:?}). \
+                                The hardlink is presented as empty regular file.", chain);
+                            unresolvable_hardlinks.insert(inode);
+                        },
+                        HardlinkTarget::Missing(target_filenumber) => {
+                            warn!(object = object_number; "The target {target_filenumber} of the hardlink {filenumber} of object {object_number} does not exist. \
+                                The hardlink is presented as empty regular file.");
+                            unresolvable_hardlinks.insert(inode);
+                        }
+                    }
                 }
                 inode_reverse_map.insert(inode, (object_number, *filenumber));
                 counter += 1;
@@ -815,6 +2479,85 @@ fn inode_reverse_map_add_object<R: Read + Seek>(
     Ok(counter)
 }
 
+// returns the damaged byte ranges of all files (and physical object images) of the given inode reverse map, which contain
+// chunks with the error flag.
+fn acquisition_errors<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    inode_reverse_map: &HashMap<u64, (u64, u64)>,
+    physical_objects: &BTreeMap<u64, PhysicalObjectData>) -> Result<BTreeMap<u64, Vec<(u64, u64)>>> {
+    // sorted by object and file, so the active object is not switched for every file.
+    let mut files: Vec<(u64, u64, u64)> = inode_reverse_map.iter()
+        .map(|(inode, (object_number, file_number))| (*object_number, *file_number, *inode))
+        .collect();
+    files.sort_unstable();
+    let mut acquisition_errors = BTreeMap::new();
+    for (object_number, file_number, inode) in files {
+        let (first_chunk_number, chunk_size, length_of_data) = if file_number == 0 {
+            match physical_objects.get(&object_number) {
+                Some(physical_object) => (physical_object.first_chunk_number, physical_object.chunk_size, physical_object.length_of_data),
+                None => continue,
+            }
+        } else {
+            let (first_chunk_number, length_of_data) = {
+                let filemetadata = prepare_zffreader_logical_file(zffreader, object_number, file_number)?;
+                (filemetadata.first_chunk_number, filemetadata.length_of_data)
+            };
+            (first_chunk_number, zffreader.active_object_header_ref()?.chunk_size, length_of_data)
+        };
+        let ranges = damaged_ranges(zffreader, first_chunk_number, chunk_size, length_of_data)?;
+        if !ranges.is_empty() {
+            acquisition_errors.insert(inode, ranges);
+        }
+    }
+    Ok(acquisition_errors)
+}
+
+// returns the names and contents of the hash sidecar files of the given physical object (one per stored hash value).
+// The content is in the format of the coreutils, e.g. "<hex>  zff_image.dd".
+fn hash_sidecars_of_object<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64, image_name: &OsStr) -> Result<Vec<(String, Vec<u8>)>> {
+    zffreader.set_active_object(object_number)?;
+    let hashes = match zffreader.active_object_footer()? {
+        ObjectFooter::Physical(footer) => footer.hash_header.hashes,
+        _ => return Ok(Vec::new()),
+    };
+    let mut sidecars = Vec::new();
+    for hash_value in hashes {
+        let name = format!("{}.{}", image_name.to_string_lossy(), hash_file_extension(hash_value.hash_type()));
+        let mut content = hex::encode(hash_value.hash()).into_bytes();
+        content.extend_from_slice(b"  ");
+        content.extend_from_slice(image_name.as_bytes());
+        content.push(b'\n');
+        sidecars.push((name, content));
+    }
+    Ok(sidecars)
+}
+
+// generates the filename of the physical object file by using the given template.
+// The placeholders {object}, {case} and {evidence} are replaced by the object number and the case/evidence number
+// of the description header.
+fn image_name_of_object<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64, template: &str) -> Result<OsString> {
+    zffreader.set_active_object(object_number)?;
+    let description_header = &zffreader.active_object_header_ref()?.description_header;
+    let case_number = description_header.case_number().unwrap_or(IMAGE_NAME_UNKNOWN_VALUE);
+    let evidence_number = description_header.evidence_number().unwrap_or(IMAGE_NAME_UNKNOWN_VALUE);
+    let image_name = template
+        .replace(IMAGE_NAME_PLACEHOLDER_OBJECT, &object_number.to_string())
+        .replace(IMAGE_NAME_PLACEHOLDER_CASE, case_number)
+        .replace(IMAGE_NAME_PLACEHOLDER_EVIDENCE, evidence_number)
+        // the values of the description header could contain path separators.
+        .replace(&['/', '\0'][..], "_");
+    Ok(OsString::from(image_name))
+}
+
+// reads the given range of the data of the given physical object.
+fn read_object_data<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64, offset: u64, size: u64) -> Result<Vec<u8>> {
+    zffreader.set_active_object(object_number)?;
+    zffreader.seek(SeekFrom::Start(offset))?;
+    let mut buffer = vec![0u8; size as usize];
+    zffreader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
 fn prepare_zffreader_logical_file<R: Read + Seek>(
     zffreader: &mut ZffReader<R>, 
     object_no: u64,
@@ -824,14 +2567,46 @@ fn prepare_zffreader_logical_file<R: Read + Seek>(
     zffreader.current_filemetadata()
 }
 
+// returns the inode, the filename and the inode of the parent directory of the given file.
+fn lookup_entry_of_file<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    filenumber: u64,
+    object_number: u64,
+    shift_value: u64) -> Result<(u64, OsString, u64)> {
+    zffreader.set_active_file(filenumber)?;
+    // the filename of the hardlink itself is used, so it is determined before the hardlink is resolved.
+    let filename = current_filename(zffreader)?;
+    let filemetadata = zffreader.current_filemetadata()?;
+    let mut inode = file_inode(filemetadata, object_number, filenumber, shift_value);
+    let parent_file_number = filemetadata.parent_file_number;
+
+    // checks if the file is a hardlink. In that case, the original file hould be added
+    if filemetadata.file_type == ZffFileType::Hardlink {
+        if let HardlinkTarget::Resolved(target_filenumber) = resolve_hardlink(zffreader, filenumber)? {
+            inode = file_inode(zffreader.current_filemetadata()?, object_number, target_filenumber, shift_value);
+        }
+    }
+    let parent_inode = if parent_file_number>0 {
+        zffreader.set_active_file(parent_file_number)?;
+        file_inode(zffreader.current_filemetadata()?, object_number, parent_file_number, shift_value)
+    } else {
+        object_number + 1 //if the file sits in root directory.
+    };
+    Ok((inode, filename, parent_inode))
+}
+
 fn filename_lookup_table_add_object<R: Read + Seek>(
     zffreader: &mut ZffReader<R>, 
-    lookup_table: &mut BTreeMap<String, Vec<(u64, u64)>>, //<Filename, Vec<Parent-Inode, Self-Inode>>
+    filenames: &mut FilenameInterner,
+    lookup_table: &mut BTreeMap<Filename, LookupEntries>, //<Filename, [(Parent-Inode, Self-Inode)]>
+    original_names: &mut BTreeMap<u64, Filename>, //<Inode, original filename>
+    renamed_files: &mut BTreeMap<u64, Filename>, //<File number, presented filename>
     object_number: u64, 
-    shift_value: u64) -> Result<u64> {
+    shift_value: u64,
+    options: &ZffFsOptions) -> Result<u64> {
     zffreader.set_active_object(object_number)?;
     let mut counter = 0;
-
+    let mut used_filenames: BTreeMap<(u64, Filename), u64> = BTreeMap::new(); //<(Parent-Inode, Filename), File number>
 
     let object_footer = match zffreader.active_object_footer()? {
         ObjectFooter::Logical(log) => log,
@@ -839,38 +2614,41 @@ fn filename_lookup_table_add_object<R: Read + Seek>(
         ObjectFooter::Virtual(_) => todo!(), //TODO
     };
     for filenumber in object_footer.file_footer_segment_numbers().keys() {
-        zffreader.set_active_file(*filenumber)?;
-        
-        let filemetadata = zffreader.current_filemetadata()?.clone();
-        let mut inode = filemetadata.first_chunk_number + shift_value;
-
-        // checks if the file is a hardlink. In that case, the original file hould be added
-        if filemetadata.file_type == ZffFileType::Hardlink {
-            let mut buffer = Vec::new();
-            zffreader.read_to_end(&mut buffer)?;
-            let original_filenumber = u64::decode_directly(&mut buffer.as_slice())?;
-            zffreader.set_active_file(original_filenumber)?;
-            let filemetadata = zffreader.current_filemetadata()?.clone();
-            inode = filemetadata.first_chunk_number + shift_value;
-        }
-        //reset the to the hardlink to get the filename of the hardlink.
-        zffreader.set_active_file(*filenumber)?;
-
-        let filename = match filemetadata.filename {
-            Some(fname) => fname,
-            None => zffreader.current_fileheader()?.filename
+        // a single malformed file should not abort the whole mount.
+        let (inode, filename, parent_inode) = match lookup_entry_of_file(zffreader, *filenumber, object_number, shift_value) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!(object = object_number; "The file {filenumber} of object {object_number} could not be added to the lookup table and will be skipped.");
+                debug!("{e}");
+                continue;
+            }
         };
-        let parent_file_number = filemetadata.parent_file_number;
-        let parent_inode = if parent_file_number>0 {
-            zffreader.set_active_file(parent_file_number)?;
-            zffreader.current_filemetadata()?.first_chunk_number + shift_value
+        let mut presented_filename = if options.sanitize_names {
+            sanitize_filename(&filename)
         } else {
-            object_number + 1 //if the file sits in root directory.
+            filename.clone()
         };
 
+        // the first file keeps its name, all following files with the same name in the same directory get a suffix.
+        // The file numbers are iterated in ascending order, so the presented names are deterministic.
+        let key = (parent_inode, filenames.intern(options.normalize_lookup.normalize(&presented_filename)));
+        if let Some(first_filenumber) = used_filenames.get(&key) {
+            let disambiguated_filename = disambiguate_filename(&presented_filename, *filenumber);
+            warn!(object = object_number; "The files {first_filenumber} and {filenumber} of object {object_number} have the same name {:?} in the same directory. \
+                File {filenumber} is presented as {:?}.", presented_filename, disambiguated_filename);
+            renamed_files.insert(*filenumber, filenames.intern(&disambiguated_filename));
+            presented_filename = disambiguated_filename;
+        } else {
+            used_filenames.insert(key, *filenumber);
+        }
+        if presented_filename != filename {
+            original_names.entry(inode).or_insert_with(|| filenames.intern(&filename));
+        }
+        let filename = filenames.intern(options.normalize_lookup.normalize(&presented_filename));
+
         match lookup_table.get_mut(&filename) {
             Some(inner_vec) => inner_vec.push((parent_inode, inode)),
-            None => { let inner_vec = vec![(parent_inode, inode)]; lookup_table.insert(filename, inner_vec); },
+            None => { let inner_vec = smallvec![(parent_inode, inode)]; lookup_table.insert(filename, inner_vec); },
         };
         counter += 1;
     }
@@ -879,102 +2657,134 @@ fn filename_lookup_table_add_object<R: Read + Seek>(
 }
 
 
-fn file_attr_of_file<R: Read + Seek>(mut filemetadata: FileMetadata, zffreader: &mut ZffReader<R>, shift_value: u64) -> Result<FileAttr> {
-    let mut zff_filetype = filemetadata.file_type;
-    if zff_filetype == ZffFileType::Hardlink {
-        let mut buffer = Vec::new();
-        zffreader.read_to_end(&mut buffer)?;
-        let original_filenumber = u64::decode_directly(&mut buffer.as_slice())?;
-        zffreader.set_active_file(original_filenumber)?;
-        filemetadata = zffreader.current_filemetadata()?.clone();
-        zff_filetype = filemetadata.file_type;
+// escapes all control characters, slashes and invalid UTF-8 bytes of the given filename (e.g. a newline will be presented as \x0a).
+// The names . and .. are escaped completely and an empty name is replaced, as they are no valid names of a directory entry.
+fn sanitize_filename(filename: &OsStr) -> OsString {
+    match filename.to_str() {
+        Some("") => return OsString::from(SANITIZED_EMPTY_FILENAME),
+        Some(CURRENT_DIR) | Some(PARENT_DIR) => return OsString::from("\\x2e".repeat(filename.len())),
+        _ => (),
     }
-    let filetype = convert_filetype(&zff_filetype, zffreader)?;
-
-    let atime = match filemetadata.metadata_ext.get(ATIME) {
-        Some(atime) => if let Some(atime) = atime.as_any().downcast_ref::<u64>() {
-            *atime as i64
-        } else {
-            0
-        },
-        None => match zffreader.current_fileheader()?.metadata_ext.get(ATIME) {
-            Some(atime) => if let Some(atime) = atime.as_any().downcast_ref::<u64>() {
-                *atime as i64
-            } else {
-                0
+    let mut sanitized_filename = String::with_capacity(filename.len());
+    let mut remaining = filename.as_bytes();
+    while !remaining.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(remaining) {
+            Ok(valid) => (valid, &remaining[remaining.len()..]),
+            Err(e) => {
+                let valid_up_to = e.valid_up_to();
+                let invalid_len = e.error_len().unwrap_or(remaining.len() - valid_up_to);
+                // unwrap is safe here, as the bytes up to valid_up_to are valid UTF-8.
+                let valid = std::str::from_utf8(&remaining[..valid_up_to]).unwrap();
+                (valid, &remaining[valid_up_to..valid_up_to + invalid_len])
             },
-            None => 0
+        };
+        for c in valid.chars() {
+            if !c.is_control() && c != '/' {
+                sanitized_filename.push(c);
+            } else if (c as u32) <= 0xff {
+                sanitized_filename.push_str(&format!("\\x{:02x}", c as u32));
+            } else {
+                sanitized_filename.push_str(&c.escape_unicode().to_string());
+            }
         }
-    };
-    let atime = match OffsetDateTime::from_unix_timestamp(atime) {
-        Ok(atime) => atime.into(),
-        Err(_) => UNIX_EPOCH,
-    };
+        for byte in invalid {
+            sanitized_filename.push_str(&format!("\\x{:02x}", byte));
+        }
+        remaining = &remaining[valid.len() + invalid.len()..];
+    }
+    OsString::from(sanitized_filename)
+}
 
-    let mtime = match filemetadata.metadata_ext.get(MTIME) {
-        Some(mtime) => if let Some(mtime) = mtime.as_any().downcast_ref::<u64>() {
-            *mtime as i64
-        } else {
-            0
-        },
-        None => match zffreader.current_fileheader()?.metadata_ext.get(MTIME) {
-            Some(mtime) => if let Some(mtime) = mtime.as_any().downcast_ref::<u64>() {
-                *mtime as i64
-            } else {
-                0
-            },
-            None => 0
+// appends the file number to the given filename, e.g. "name (zff#42)".
+fn disambiguate_filename(filename: &OsStr, filenumber: u64) -> OsString {
+    let mut disambiguated_filename = filename.to_os_string();
+    disambiguated_filename.push(format!(" (zff#{filenumber})"));
+    disambiguated_filename
+}
+
+// returns the lowercase form of the given filename. For filenames which are not valid UTF-8, only the ASCII characters are converted.
+fn fold_case(filename: &OsStr) -> OsString {
+    match filename.to_str() {
+        Some(filename) => OsString::from(filename.to_lowercase()),
+        None => filename.to_ascii_lowercase(),
+    }
+}
+
+// builds the case-insensitive lookup table by using the lowercase filenames as keys.
+// The lowercase filenames are interned, so they share the memory of the filenames, which are already lowercase.
+fn case_folded_lookup_table(lookup_table: &BTreeMap<Filename, LookupEntries>, filenames: &mut FilenameInterner) -> BTreeMap<Filename, LookupEntries> {
+    let mut case_folded_lookup_table: BTreeMap<Filename, LookupEntries> = BTreeMap::new();
+    for (filename, entries) in lookup_table {
+        let folded_entries = case_folded_lookup_table.entry(filenames.intern(fold_case(filename))).or_default();
+        for (parent_inode, inode) in entries {
+            if folded_entries.iter().any(|(other_parent_inode, other_inode)| other_parent_inode == parent_inode && other_inode != inode) {
+                warn!("The filename {:?} collides case-insensitively with another file in the directory with inode {parent_inode}. \
+                    Both files are only reachable by their exact names.", filename);
+            }
+            folded_entries.push((*parent_inode, *inode));
         }
-    };
-    let mtime = match OffsetDateTime::from_unix_timestamp(mtime) {
-        Ok(mtime) => mtime.into(),
-        Err(_) => UNIX_EPOCH,
-    };
+    }
+    case_folded_lookup_table
+}
 
-    let ctime = match filemetadata.metadata_ext.get(CTIME) {
-        Some(ctime) => if let Some(ctime) = ctime.as_any().downcast_ref::<u64>() {
-            *ctime as i64
-        } else {
-            0
-        },
-        None => match zffreader.current_fileheader()?.metadata_ext.get(CTIME) {
-            Some(ctime) => if let Some(ctime) = ctime.as_any().downcast_ref::<u64>() {
-                *ctime as i64
-            } else {
-                0
+// the blocks are calculated by using the samebytes map, if the appropriate chunk size is given.
+// The attributes are read from the active file, which is the resolved file after a hardlink was resolved.
+fn file_attr_of_file<R: Read + Seek>(
+    object_number: u64,
+    filenumber: u64,
+    zffreader: &mut ZffReader<R>,
+    shift_value: u64,
+    sparse_chunk_size: Option<u64>,
+    missing_times_fallback: Option<&FileAttr>) -> Result<FileAttr> {
+    let filemetadata = zffreader.current_filemetadata()?;
+    let mut inode = file_inode(filemetadata, object_number, filenumber, shift_value);
+    let (filetype, first_chunk_number, number_of_chunks, length_of_data) = if filemetadata.file_type == ZffFileType::Hardlink {
+        match resolve_hardlink(zffreader, filenumber)? {
+            HardlinkTarget::Resolved(target_filenumber) => {
+                let filemetadata = zffreader.current_filemetadata()?;
+                inode = file_inode(filemetadata, object_number, target_filenumber, shift_value);
+                let (first_chunk_number, number_of_chunks, length_of_data) = (filemetadata.first_chunk_number, filemetadata.number_of_chunks, filemetadata.length_of_data);
+                (convert_filetype(zffreader)?, first_chunk_number, number_of_chunks, length_of_data)
             },
-            None => 0
+            // unresolvable hardlinks are presented as empty regular files.
+            HardlinkTarget::Unresolvable(_) | HardlinkTarget::Missing(_) => (FileType::RegularFile, 0, 0, 0),
         }
-    };
-    let ctime = match OffsetDateTime::from_unix_timestamp(ctime) {
-        Ok(ctime) => ctime.into(),
-        Err(_) => UNIX_EPOCH,
+    } else {
+        let (first_chunk_number, number_of_chunks, length_of_data) = (filemetadata.first_chunk_number, filemetadata.number_of_chunks, filemetadata.length_of_data);
+        (convert_filetype(zffreader)?, first_chunk_number, number_of_chunks, length_of_data)
     };
 
-    let btime = match filemetadata.metadata_ext.get(BTIME) {
-        Some(btime) => if let Some(btime) = btime.as_any().downcast_ref::<u64>() {
-            *btime as i64
-        } else {
-            0
+    let blocks = match sparse_chunk_size {
+        Some(chunk_size) if filetype == FileType::RegularFile => {
+            let allocated_bytes = allocated_bytes(
+                zffreader,
+                first_chunk_number,
+                number_of_chunks,
+                chunk_size,
+                length_of_data)?;
+            blocks_of_allocated_bytes(allocated_bytes)
         },
-        None => match zffreader.current_fileheader()?.metadata_ext.get(BTIME) {
-            Some(btime) => if let Some(btime) = btime.as_any().downcast_ref::<u64>() {
-                *btime as i64
-            } else {
-                0
-            },
-            None => 0
-        }
+        _ => blocks_of_allocated_bytes(length_of_data),
     };
-    let btime = match OffsetDateTime::from_unix_timestamp(btime) {
-        Ok(btime) => btime.into(),
-        Err(_) => UNIX_EPOCH,
+
+    let atime = file_timestamp(zffreader, ATIME)?;
+    let mtime = file_timestamp(zffreader, MTIME)?;
+    let ctime = file_timestamp(zffreader, CTIME)?;
+    let btime = file_timestamp(zffreader, BTIME)?;
+    let (uid, gid) = file_source_owner(zffreader)?.unwrap_or((Uid::effective().into(), Gid::effective().into()));
+    let (atime, mtime, ctime, btime) = match missing_times_fallback {
+        Some(fallback) => (
+            missing_time_or(atime, fallback.atime),
+            missing_time_or(mtime, fallback.mtime),
+            missing_time_or(ctime, fallback.ctime),
+            missing_time_or(btime, fallback.crtime)),
+        None => (atime, mtime, ctime, btime),
     };
 
     Ok(FileAttr {
-        ino: filemetadata.first_chunk_number + shift_value,
-        size: filemetadata.length_of_data,
-        blocks: filemetadata.length_of_data / DEFAULT_BLOCKSIZE as u64 + 1,
+        ino: inode,
+        size: length_of_data,
+        blocks,
         atime,
         mtime,
         ctime,
@@ -982,14 +2792,162 @@ fn file_attr_of_file<R: Read + Seek>(mut filemetadata: FileMetadata, zffreader:
         kind: filetype,
         perm: 0o755,
         nlink: 1,
-        uid: Uid::effective().into(),
-        gid: Gid::effective().into(),
+        uid,
+        gid,
         rdev: 0,
         flags: 0,
         blksize: DEFAULT_BLOCKSIZE,
     })
 }
 
+// returns the uid and gid of the acquired system of the active file, if the metadata contains at least one of them.
+// Missing ids are replaced by the effective ids of the current process.
+fn file_source_owner<R: Read + Seek>(zffreader: &mut ZffReader<R>) -> Result<Option<(u32, u32)>> {
+    let uid = file_ext_integer(zffreader, UID)?.and_then(|uid| u32::try_from(uid).ok());
+    let gid = file_ext_integer(zffreader, GID)?.and_then(|gid| u32::try_from(gid).ok());
+    if uid.is_none() && gid.is_none() {
+        return Ok(None);
+    }
+    Ok(Some((uid.unwrap_or_else(|| Uid::effective().into()), gid.unwrap_or_else(|| Gid::effective().into()))))
+}
+
+// returns the integer value of the given key of the file metadata, or of the file header if the file metadata does not contain the key.
+fn file_ext_integer<R: Read + Seek>(zffreader: &mut ZffReader<R>, key: &str) -> Result<Option<i64>> {
+    if let Some(value) = zffreader.current_filemetadata()?.metadata_ext.get(key) {
+        Ok(ext_integer(value))
+    } else {
+        Ok(zffreader.current_fileheader()?.metadata_ext.get(key).and_then(ext_integer))
+    }
+}
+
+// returns the fallback, if the given timestamp is missing (i.e. UNIX_EPOCH).
+fn missing_time_or(time: SystemTime, fallback: SystemTime) -> SystemTime {
+    if time == UNIX_EPOCH {
+        fallback
+    } else {
+        time
+    }
+}
+
+// returns the timestamp of the given key of the file metadata, or of the file header if the file metadata does not contain the key.
+fn file_timestamp<R: Read + Seek>(zffreader: &mut ZffReader<R>, key: &str) -> Result<SystemTime> {
+    let filemetadata = zffreader.current_filemetadata()?;
+    if filemetadata.metadata_ext.contains_key(key) {
+        Ok(ext_timestamp(&filemetadata.metadata_ext, key))
+    } else {
+        Ok(ext_timestamp(&zffreader.current_fileheader()?.metadata_ext, key))
+    }
+}
+
+// returns the timestamp of the given key (e.g. atime) with nanosecond precision, if the appropriate
+// nanosecond key (e.g. atime_nsec) exists. Missing or invalid timestamps are presented as UNIX_EPOCH.
+fn ext_timestamp(meta: &HashMap<String, MetadataExtendedValue>, key: &str) -> SystemTime {
+    let seconds = match meta.get(key).and_then(ext_integer) {
+        Some(seconds) => seconds,
+        None => return UNIX_EPOCH,
+    };
+    let nanoseconds = meta.get(&format!("{key}{TIMESTAMP_NSEC_SUFFIX}"))
+        .and_then(ext_integer)
+        .filter(|nanoseconds| (0..NANOSECONDS_PER_SECOND).contains(nanoseconds))
+        .unwrap_or(0);
+    match OffsetDateTime::from_unix_timestamp_nanos(seconds as i128 * NANOSECONDS_PER_SECOND as i128 + nanoseconds as i128) {
+        Ok(time) => time.into(),
+        Err(_) => UNIX_EPOCH,
+    }
+}
+
+// the timestamps are usually stored as u64, but we also accept signed values (e.g. timestamps before 1970).
+fn ext_integer(value: &MetadataExtendedValue) -> Option<i64> {
+    let value = value.as_any();
+    if let Some(value) = value.downcast_ref::<u64>() {
+        i64::try_from(*value).ok()
+    } else {
+        value.downcast_ref::<i64>().copied()
+    }
+}
+
+// returns the number of 512-byte blocks which are needed to store the given number of bytes.
+fn blocks_of_allocated_bytes(allocated_bytes: u64) -> u64 {
+    (allocated_bytes + DEFAULT_BLOCKSIZE as u64 - 1) / DEFAULT_BLOCKSIZE as u64
+}
+
+// returns the encryption algorithm of the given object, if the object header is readable (e.g. after the decryption).
+fn encryption_algorithm<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64) -> Option<String> {
+    zffreader.set_active_object(object_number).ok()?;
+    let header = zffreader.active_object_header_ref().ok()?;
+    header.encryption_header.as_ref().map(|encryption_header| encryption_header.algorithm.to_string())
+}
+
+// returns the attributes of the placeholder directory of the given locked object. The timestamps are taken from the object
+// footer, if it is readable without the decryption (the epoch otherwise).
+fn locked_object_dir_attr<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64) -> FileAttr {
+    let mut dir_attr = match zffreader.set_active_object(object_number).and_then(|_| zffreader.active_object_footer()) {
+        Ok(object_footer) => file_attr_of_object_footer(&object_footer),
+        Err(_) => root_dir_attr(std::iter::empty()),
+    };
+    dir_attr.ino = SPECIAL_INODE_LOCKED_OBJECTS_START + object_number;
+    dir_attr.perm = LOCKED_OBJECT_DIR_PERMISSIONS;
+    dir_attr
+}
+
+// returns the content of the notice file in the placeholder directory of the given locked object.
+fn locked_notice(object_number: u64) -> String {
+    format!("The object {object_number} is encrypted and could not be decrypted, as no (valid) password was given.\n\
+        \n\
+        To access the content of the object, mount the container again and pass the password of the object\n\
+        with --decryption-passwords {object_number}=<password> (or enter it, when zffmount asks for it).\n\
+        Use --hide-encrypted to hide the locked objects.\n")
+}
+
+// returns the object number of the object directory with the given name. The name has to consist of the given prefix and
+// the object number in ASCII digits only, so names which only contain the prefix (e.g. object_1object_2), names with
+// whitespaces or signs (e.g. "object_1 " or object_+1) and too large numbers are no object directories.
+fn parse_object_dir_name(name: &str, prefix: &str) -> Option<u64> {
+    let object_number = name.strip_prefix(prefix)?;
+    if object_number.is_empty() || !object_number.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    object_number.parse().ok()
+}
+
+// returns the inode of the object directory with the given name (object number + 1, or SPECIAL_INODE_LOCKED_OBJECTS_START +
+// object number for a locked object). Object numbers, whose inode would overflow or reach into the reserved inode ranges
+// (the empty files, the virtual files or the container inode range), are no object directories.
+fn object_dir_inode(name: &str, prefix: &str) -> Option<u64> {
+    match name.strip_suffix(LOCKED_OBJECT_DIR_SUFFIX) {
+        Some(name) => parse_object_dir_name(name, prefix)?
+            .checked_add(SPECIAL_INODE_LOCKED_OBJECTS_START)
+            .filter(|inode| *inode < 1 << CONTAINER_INODE_SHIFT),
+        None => parse_object_dir_name(name, prefix)?
+            .checked_add(1)
+            .filter(|inode| *inode < SPECIAL_INODE_EMPTY_FILES_START),
+    }
+}
+
+// returns the attributes of a root directory, which contains the directories with the given attributes (e.g. the object
+// directories). The root directory is owned by the effective user, like all other directories. The newest modification time
+// and the oldest creation time of the directories are used (the epoch, if there are no directories).
+pub(crate) fn root_dir_attr(dir_attrs: impl Iterator<Item = FileAttr>) -> FileAttr {
+    let mut root_dir_attr = DEFAULT_ROOT_DIR_ATTR;
+    root_dir_attr.uid = Uid::effective().into();
+    root_dir_attr.gid = Gid::effective().into();
+    let mut newest: Option<SystemTime> = None;
+    let mut oldest: Option<SystemTime> = None;
+    for dir_attr in dir_attrs {
+        newest = Some(newest.map_or(dir_attr.mtime, |newest| max(newest, dir_attr.mtime)));
+        oldest = Some(oldest.map_or(dir_attr.crtime, |oldest| min(oldest, dir_attr.crtime)));
+    }
+    if let Some(newest) = newest {
+        root_dir_attr.atime = newest;
+        root_dir_attr.mtime = newest;
+        root_dir_attr.ctime = newest;
+    }
+    if let Some(oldest) = oldest {
+        root_dir_attr.crtime = oldest;
+    }
+    root_dir_attr
+}
+
 fn file_attr_of_object_footer(object_footer: &ObjectFooter) -> FileAttr {
     let acquisition_start = match OffsetDateTime::from_unix_timestamp(object_footer.acquisition_start() as i64) {
         Ok(time) => time.into(),
@@ -1018,41 +2976,273 @@ fn file_attr_of_object_footer(object_footer: &ObjectFooter) -> FileAttr {
     }
 }
 
+// returns the inode and the attributes of the given file and whether the file has an acquired owner.
+fn file_attr_entry<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    filenumber: u64,
+    shift_value: u64,
+    sparse_chunk_size: Option<u64>,
+    missing_times_fallback: Option<&FileAttr>) -> Result<(u64, FileAttr, bool)> {
+    zffreader.set_active_file(filenumber)?;
+    let inode = file_inode(zffreader.current_filemetadata()?, object_number, filenumber, shift_value);
+    let file_attr = file_attr_of_file(object_number, filenumber, zffreader, shift_value, sparse_chunk_size, missing_times_fallback)?;
+    let has_source_owner = file_source_owner(zffreader)?.is_some();
+    Ok((inode, file_attr, has_source_owner))
+}
+
+// returns the attributes of the data file (e.g. zff_image.dd) of the physical object with the given footer.
+fn file_attr_of_physical_object<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_footer: &ObjectFooter,
+    shift_value: u64,
+    sparse_chunk_size: Option<u64>) -> Result<FileAttr> {
+    let phy_footer = match object_footer {
+        ObjectFooter::Physical(phy_footer) => phy_footer,
+        _ => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, "logical")),
+    };
+    let mut file_attr = file_attr_of_object_footer(object_footer);
+    file_attr.ino = phy_footer.first_chunk_number + shift_value;
+    file_attr.kind = FileType::RegularFile;
+    file_attr.perm = 0o644;
+    file_attr.size = phy_footer.length_of_data;
+    file_attr.blocks = match sparse_chunk_size {
+        Some(chunk_size) => blocks_of_allocated_bytes(allocated_bytes(
+            zffreader,
+            phy_footer.first_chunk_number,
+            phy_footer.number_of_chunks,
+            chunk_size,
+            phy_footer.length_of_data)?),
+        None => phy_footer.length_of_data / DEFAULT_BLOCKSIZE as u64 + 1,
+    };
+    file_attr.nlink = 1;
+    Ok(file_attr)
+}
+
 fn inode_attributes_map_add_object<R: Read + Seek>(
     zffreader: &mut ZffReader<R>, 
-    inode_attributes_map: &mut BTreeMap<u64, FileAttr>, 
+    inode_attributes_map: &mut HashMap<u64, CompactAttr>, 
+    source_owners: &mut BTreeMap<u64, (u32, u32)>,
     object_number: u64, 
-    shift_value: u64) -> Result<u64> {
+    shift_value: u64,
+    sparse_blocks: bool,
+    epoch_for_missing_times: bool) -> Result<u64> {
     zffreader.set_active_object(object_number)?;
     let mut counter = 0;
 
+    let sparse_chunk_size = if sparse_blocks {
+        Some(zffreader.active_object_header_ref()?.chunk_size)
+    } else {
+        None
+    };
+
     let object_footer = zffreader.active_object_footer()?;
-    inode_attributes_map.insert(object_number+1, file_attr_of_object_footer(&object_footer));
+    let object_dir_attr = file_attr_of_object_footer(&object_footer);
+    inode_attributes_map.insert(object_number+1, CompactAttr::from(&object_dir_attr));
+    // missing file timestamps are replaced by the acquisition times of the object (like the timestamps of the object directory).
+    let missing_times_fallback = if epoch_for_missing_times { None } else { Some(&object_dir_attr) };
     match object_footer {
         ObjectFooter::Logical(log_footer) => {
             for filenumber in log_footer.file_footer_segment_numbers().keys() {
-                zffreader.set_active_file(*filenumber)?;
-                let metadata = zffreader.current_filemetadata()?.clone();
-                let inode = metadata.first_chunk_number + shift_value;
-                let file_attr = file_attr_of_file(metadata, zffreader, shift_value)?;
-                inode_attributes_map.insert(inode, file_attr);
+                // a single malformed file should not abort the whole mount.
+                let (inode, file_attr, has_source_owner) = match file_attr_entry(zffreader, object_number, *filenumber, shift_value, sparse_chunk_size, missing_times_fallback) {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn!(object = object_number; "The attributes of file {filenumber} of object {object_number} could not be read. The file will be skipped.");
+                        debug!("{e}");
+                        continue;
+                    }
+                };
+                if has_source_owner {
+                    source_owners.insert(inode, (file_attr.uid, file_attr.gid));
+                }
+                inode_attributes_map.insert(inode, CompactAttr::from(&file_attr));
                 counter += 1;
             }
         },
-        ObjectFooter::Physical(ref phy_footer) => {
-            let inode = phy_footer.first_chunk_number + shift_value;
-            let mut file_attr = file_attr_of_object_footer(&object_footer);
-            file_attr.ino = inode;
-            file_attr.kind = FileType::RegularFile;
-            file_attr.perm = 0o644;
-            file_attr.size = phy_footer.length_of_data;
-            file_attr.blocks = phy_footer.length_of_data / DEFAULT_BLOCKSIZE as u64 + 1;
-            file_attr.nlink = 1;
-            inode_attributes_map.insert(inode, file_attr); //0 is not a valid file number in zff, so we can use this as a placeholder
+        ObjectFooter::Physical(_) => {
+            let file_attr = file_attr_of_physical_object(zffreader, &object_footer, shift_value, sparse_chunk_size)?;
+            inode_attributes_map.insert(file_attr.ino, CompactAttr::from(&file_attr)); //0 is not a valid file number in zff, so we can use this as a placeholder
             counter += 1;
         },
         ObjectFooter::Virtual(_) => todo!(), //TODO
     };
 
     Ok(counter)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_without_normalization_borrows_the_filename() {
+        let filename = OsStr::new("cafe\u{301}");
+        assert!(matches!(LookupNormalization::None.normalize(filename), Cow::Borrowed(name) if name == filename));
+    }
+
+    #[test]
+    fn normalize_nfc_composes_the_filename() {
+        assert_eq!(LookupNormalization::Nfc.normalize(OsStr::new("cafe\u{301}")), OsStr::new("caf\u{e9}"));
+        assert_eq!(LookupNormalization::Nfc.normalize(OsStr::new("caf\u{e9}")), OsStr::new("caf\u{e9}"));
+    }
+
+    #[test]
+    fn normalize_nfd_decomposes_the_filename() {
+        assert_eq!(LookupNormalization::Nfd.normalize(OsStr::new("caf\u{e9}")), OsStr::new("cafe\u{301}"));
+        assert_eq!(LookupNormalization::Nfd.normalize(OsStr::new("cafe\u{301}")), OsStr::new("cafe\u{301}"));
+    }
+
+    #[test]
+    fn normalize_keeps_non_utf8_filenames() {
+        let filename = OsStr::from_bytes(b"caf\xe9");
+        assert_eq!(LookupNormalization::Nfc.normalize(filename), filename);
+        assert_eq!(LookupNormalization::Nfd.normalize(filename), filename);
+    }
+
+    #[test]
+    fn sanitize_filename_escapes_control_characters() {
+        assert_eq!(sanitize_filename(OsStr::new("file\nname")), OsStr::new("file\\x0aname"));
+        assert_eq!(sanitize_filename(OsStr::new("file\tname")), OsStr::new("file\\x09name"));
+        assert_eq!(sanitize_filename(OsStr::new("file\0name")), OsStr::new("file\\x00name"));
+    }
+
+    #[test]
+    fn sanitize_filename_escapes_invalid_utf8() {
+        assert_eq!(sanitize_filename(OsStr::from_bytes(b"file\xffname")), OsStr::new("file\\xffname"));
+    }
+
+    #[test]
+    fn sanitize_filename_escapes_slashes() {
+        assert_eq!(sanitize_filename(OsStr::new("a/b")), OsStr::new("a\\x2fb"));
+    }
+
+    #[test]
+    fn sanitize_filename_escapes_dot_names() {
+        assert_eq!(sanitize_filename(OsStr::new(".")), OsStr::new("\\x2e"));
+        assert_eq!(sanitize_filename(OsStr::new("..")), OsStr::new("\\x2e\\x2e"));
+        assert_eq!(sanitize_filename(OsStr::new("...")), OsStr::new("..."));
+        assert_eq!(sanitize_filename(OsStr::new(".hidden")), OsStr::new(".hidden"));
+    }
+
+    #[test]
+    fn sanitize_filename_replaces_an_empty_name() {
+        assert_eq!(sanitize_filename(OsStr::new("")), OsStr::new(SANITIZED_EMPTY_FILENAME));
+    }
+
+    #[test]
+    fn sanitize_filename_keeps_regular_names() {
+        assert_eq!(sanitize_filename(OsStr::new("Bericht ä.txt")), OsStr::new("Bericht ä.txt"));
+    }
+
+    fn metadata(values: Vec<(&str, MetadataExtendedValue)>) -> HashMap<String, MetadataExtendedValue> {
+        values.into_iter().map(|(key, value)| (key.to_string(), value)).collect()
+    }
+
+    #[test]
+    fn ext_timestamp_with_nanoseconds() {
+        let meta = metadata(vec![
+            (MTIME, MetadataExtendedValue::U64(1_700_000_000)),
+            ("mtime_nsec", MetadataExtendedValue::U64(123_456_789)),
+        ]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789));
+    }
+
+    #[test]
+    fn ext_timestamp_of_a_missing_key_is_the_epoch() {
+        assert_eq!(ext_timestamp(&metadata(vec![]), MTIME), UNIX_EPOCH);
+        // the nanoseconds alone are no timestamp.
+        let meta = metadata(vec![("mtime_nsec", MetadataExtendedValue::U64(5))]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH);
+    }
+
+    #[test]
+    fn ext_timestamp_of_a_malformed_value_is_the_epoch() {
+        let meta = metadata(vec![(MTIME, MetadataExtendedValue::String(String::from("yesterday")))]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH);
+        // a u64 which does not fit into an i64.
+        let meta = metadata(vec![(MTIME, MetadataExtendedValue::U64(u64::MAX))]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH);
+    }
+
+    #[test]
+    fn ext_timestamp_ignores_malformed_nanoseconds() {
+        let meta = metadata(vec![
+            (MTIME, MetadataExtendedValue::U64(60)),
+            ("mtime_nsec", MetadataExtendedValue::U64(NANOSECONDS_PER_SECOND as u64)),
+        ]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH + Duration::from_secs(60));
+        let meta = metadata(vec![
+            (MTIME, MetadataExtendedValue::U64(60)),
+            ("mtime_nsec", MetadataExtendedValue::I64(-1)),
+        ]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH + Duration::from_secs(60));
+    }
+
+    #[test]
+    fn ext_timestamp_before_the_epoch() {
+        let meta = metadata(vec![
+            (MTIME, MetadataExtendedValue::I64(-86_400)),
+            ("mtime_nsec", MetadataExtendedValue::U64(500_000_000)),
+        ]);
+        assert_eq!(ext_timestamp(&meta, MTIME), UNIX_EPOCH - Duration::from_secs(86_400) + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn parse_object_dir_name_accepts_the_prefixed_object_number() {
+        assert_eq!(parse_object_dir_name("object_1", "object_"), Some(1));
+        assert_eq!(parse_object_dir_name("object_0042", "object_"), Some(42));
+    }
+
+    #[test]
+    fn parse_object_dir_name_rejects_a_wrong_prefix() {
+        assert_eq!(parse_object_dir_name("obj_1", "object_"), None);
+        assert_eq!(parse_object_dir_name("Object_1", "object_"), None);
+    }
+
+    #[test]
+    fn parse_object_dir_name_rejects_a_non_numeric_suffix() {
+        assert_eq!(parse_object_dir_name("object_", "object_"), None);
+        assert_eq!(parse_object_dir_name("object_1object_2", "object_"), None);
+        assert_eq!(parse_object_dir_name("object_1 ", "object_"), None);
+        assert_eq!(parse_object_dir_name("object_+1", "object_"), None);
+        assert_eq!(parse_object_dir_name("object_-1", "object_"), None);
+    }
+
+    #[test]
+    fn parse_object_dir_name_rejects_an_overflowing_number() {
+        assert_eq!(parse_object_dir_name("object_18446744073709551616", "object_"), None);
+    }
+
+    #[test]
+    fn object_dir_inode_of_an_object_and_a_locked_object() {
+        assert_eq!(object_dir_inode("object_1", "object_"), Some(2));
+        assert_eq!(object_dir_inode("object_1.locked", "object_"), Some(SPECIAL_INODE_LOCKED_OBJECTS_START + 1));
+        assert_eq!(object_dir_inode("object_1", "obj_"), None);
+    }
+
+    // the lookup answers ENOENT for these names (instead of panicking on an overflowing addition).
+    #[test]
+    fn object_dir_inode_rejects_overflowing_object_numbers() {
+        assert_eq!(object_dir_inode("object_18446744073709551615", "object_"), None);
+        assert_eq!(object_dir_inode("object_18446744073709551615.locked", "object_"), None);
+    }
+
+    #[test]
+    fn object_dir_inode_rejects_the_reserved_inode_ranges() {
+        let last_object = SPECIAL_INODE_EMPTY_FILES_START - 2;
+        assert_eq!(object_dir_inode(&format!("object_{last_object}"), "object_"), Some(SPECIAL_INODE_EMPTY_FILES_START - 1));
+        assert_eq!(object_dir_inode(&format!("object_{}", last_object + 1), "object_"), None);
+
+        let last_locked_object = (1 << CONTAINER_INODE_SHIFT) - SPECIAL_INODE_LOCKED_OBJECTS_START - 1;
+        assert_eq!(object_dir_inode(&format!("object_{last_locked_object}.locked"), "object_"), Some((1 << CONTAINER_INODE_SHIFT) - 1));
+        assert_eq!(object_dir_inode(&format!("object_{}.locked", last_locked_object + 1), "object_"), None);
+    }
+
+    #[test]
+    fn parse_object_dir_name_with_an_empty_prefix() {
+        assert_eq!(parse_object_dir_name("7", ""), Some(7));
+        assert_eq!(parse_object_dir_name("", ""), None);
+        assert_eq!(parse_object_dir_name("object_7", ""), None);
+    }
+}
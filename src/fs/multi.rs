@@ -0,0 +1,263 @@
+// - STD
+use std::ffi::{OsStr, OsString};
+use std::io::{Read, Seek};
+use std::sync::Arc;
+use std::time::Duration;
+
+// - internal
+use super::{ZffFs, ActivityTracker, root_dir_attr};
+use crate::constants::*;
+
+// - external
+use log::debug;
+use fuser::{
+    FileAttr, FileType, Filesystem, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
+    ReplyOpen, ReplyLseek, ReplyXattr, ReplyEmpty, Request, KernelConfig,
+};
+use libc::{c_int, ENOENT, EACCES, ENODATA};
+
+/// A filesystem which serves multiple independent containers under one mount point.
+/// Each container is presented as a top-level directory (named after the container) with the usual object_N tree beneath.
+/// The inodes of each container are shifted by (container index + 1) << CONTAINER_INODE_SHIFT, so the appropriate
+/// container of each request can be determined by the inode.
+pub struct ZffMultiFs<R: Read + Seek> {
+    containers: Vec<(OsString, ZffFs<R>)>,
+    activity: Arc<ActivityTracker>,
+    root_attr: FileAttr,
+}
+
+impl<R: Read + Seek> ZffMultiFs<R> {
+    /// Creates a new filesystem by using the given (named) containers. The names have to be unique.
+    pub fn new(containers: Vec<(String, ZffFs<R>)>) -> Self {
+        let activity = Arc::new(ActivityTracker::new());
+        let containers: Vec<(OsString, ZffFs<R>)> = containers.into_iter().enumerate().map(|(index, (name, mut fs))| {
+            fs.set_inode_offset((index as u64 + 1) << CONTAINER_INODE_SHIFT);
+            fs.set_activity_tracker(Arc::clone(&activity));
+            (OsString::from(name), fs)
+        }).collect();
+        let root_attr = root_dir_attr(containers.iter().map(|(_, fs)| fs.root_attr()));
+        Self {
+            containers,
+            activity,
+            root_attr,
+        }
+    }
+
+    /// Sets the owner of all files and directories of all containers (e.g. the identity after dropping the privileges).
+    pub fn set_owner(&mut self, uid: u32, gid: u32) {
+        self.root_attr.uid = uid;
+        self.root_attr.gid = gid;
+        for (_, fs) in self.containers.iter_mut() {
+            fs.set_owner(uid, gid);
+        }
+    }
+
+    /// Returns the tracker of the filesystem activity, which is shared by all containers.
+    pub fn activity_tracker(&self) -> Arc<ActivityTracker> {
+        Arc::clone(&self.activity)
+    }
+
+    // returns true, if the user of the given request is permitted to access the root directory. All containers are
+    // mounted with the same options, so the user has to be permitted by each of them.
+    fn access_permitted(&self, req: &Request<'_>, operation: &str) -> bool {
+        self.containers.iter().all(|(_, fs)| fs.access_permitted(req, operation))
+    }
+
+    // returns the TTL and the given (presented) attributes of a directory of the mount point as replied to the given caller.
+    // All containers are mounted with the same options, so the first container determines the owner squashing.
+    fn caller_attr(&self, attr: FileAttr, caller: (u32, u32)) -> (Duration, FileAttr) {
+        match self.containers.first() {
+            Some((_, fs)) => (fs.attr_ttl(), fs.caller_attr(attr, caller)),
+            None => (TTL, attr),
+        }
+    }
+
+    // returns the container, which contains the given inode.
+    fn container(&mut self, inode: u64) -> Option<&mut ZffFs<R>> {
+        let index = (inode >> CONTAINER_INODE_SHIFT) as usize;
+        self.containers.get_mut(index.checked_sub(1)?).map(|(_, fs)| fs)
+    }
+}
+
+impl<R: Read + Seek> Filesystem for ZffMultiFs<R> {
+    fn init(&mut self, req: &Request<'_>, config: &mut KernelConfig) -> Result<(), c_int> {
+        for (_, fs) in self.containers.iter_mut() {
+            fs.init(req, config)?;
+        }
+        Ok(())
+    }
+
+    fn destroy(&mut self) {
+        for (_, fs) in self.containers.iter_mut() {
+            fs.destroy();
+        }
+    }
+
+    fn open(&mut self, req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+        match self.container(ino) {
+            Some(fs) => fs.open(req, ino, flags, reply),
+            None => reply.opened(0, 0),
+        }
+    }
+
+    fn release(
+        &mut self,
+        req: &Request<'_>,
+        ino: u64,
+        fh: u64,
+        flags: i32,
+        lock_owner: Option<u64>,
+        flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        match self.container(ino) {
+            Some(fs) => fs.release(req, ino, fh, flags, lock_owner, flush, reply),
+            None => reply.ok(),
+        }
+    }
+
+    fn read(
+        &mut self,
+        req: &Request,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        flags: i32,
+        lock: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match self.container(ino) {
+            Some(fs) => fs.read(req, ino, fh, offset, size, flags, lock, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn lseek(&mut self, req: &Request<'_>, ino: u64, fh: u64, offset: i64, whence: i32, reply: ReplyLseek) {
+        match self.container(ino) {
+            Some(fs) => fs.lseek(req, ino, fh, offset, whence, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn opendir(&mut self, req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+        // the listing of the root directory is built on each readdir call, as it contains only the containers.
+        if ino == SPECIAL_INODE_ROOT_DIR {
+            if self.access_permitted(req, "OPENDIR") {
+                reply.opened(0, 0);
+            } else {
+                reply.error(EACCES);
+            }
+            return;
+        }
+        match self.container(ino) {
+            Some(fs) => fs.opendir(req, ino, flags, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn releasedir(&mut self, req: &Request<'_>, ino: u64, fh: u64, flags: i32, reply: ReplyEmpty) {
+        match self.container(ino) {
+            Some(fs) => fs.releasedir(req, ino, fh, flags, reply),
+            None => reply.ok(),
+        }
+    }
+
+    fn readdir(
+    &mut self,
+    req: &Request,
+    ino: u64,
+    fh: u64,
+    offset: i64,
+    mut reply: ReplyDirectory,
+    ) {
+        if ino != SPECIAL_INODE_ROOT_DIR {
+            match self.container(ino) {
+                Some(fs) => fs.readdir(req, ino, fh, offset, reply),
+                None => reply.error(ENOENT),
+            }
+            return;
+        }
+        if !self.access_permitted(req, "READDIR") {
+            reply.error(EACCES);
+            return;
+        }
+        self.activity.touch();
+        let mut entries = vec![
+            (SPECIAL_INODE_ROOT_DIR, OsString::from(CURRENT_DIR)),
+            (SPECIAL_INODE_ROOT_DIR, OsString::from(PARENT_DIR)),
+        ];
+        for (name, fs) in &self.containers {
+            entries.push((fs.root_attr().ino, name.clone()));
+        }
+        for (index, (inode, name)) in entries.into_iter().skip(offset as usize).enumerate() {
+            if reply.add(inode, offset + index as i64 + 1, FileType::Directory, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn lookup(&mut self, req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if parent != SPECIAL_INODE_ROOT_DIR {
+            match self.container(parent) {
+                Some(fs) => fs.lookup(req, parent, name, reply),
+                None => reply.error(ENOENT),
+            }
+            return;
+        }
+        if !self.access_permitted(req, "LOOKUP") {
+            reply.error(EACCES);
+            return;
+        }
+        self.activity.touch();
+        match self.containers.iter().find(|(container_name, _)| container_name == name) {
+            Some((_, fs)) => {
+                let (ttl, attr) = self.caller_attr(fs.root_attr(), (req.uid(), req.gid()));
+                reply.entry(&ttl, &attr, DEFAULT_ENTRY_GENERATION);
+            },
+            None => {
+                debug!("LOOKUP: container {:?} not found.", name);
+                reply.error(ENOENT);
+            }
+        }
+    }
+
+    fn readlink(&mut self, req: &Request<'_>, ino: u64, reply: ReplyData) {
+        match self.container(ino) {
+            Some(fs) => fs.readlink(req, ino, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+
+    fn getxattr(&mut self, req: &Request<'_>, ino: u64, name: &OsStr, size: u32, reply: ReplyXattr) {
+        match self.container(ino) {
+            Some(fs) => fs.getxattr(req, ino, name, size, reply),
+            None => reply.error(ENODATA),
+        }
+    }
+
+    fn listxattr(&mut self, req: &Request<'_>, ino: u64, size: u32, reply: ReplyXattr) {
+        match self.container(ino) {
+            Some(fs) => fs.listxattr(req, ino, size, reply),
+            None => if size == 0 { reply.size(0) } else { reply.data(&[]) },
+        }
+    }
+
+    fn getattr(&mut self, req: &Request, ino: u64, fh: Option<u64>, reply: ReplyAttr) {
+        if ino == SPECIAL_INODE_ROOT_DIR {
+            if !self.access_permitted(req, "GETATTR") {
+                reply.error(EACCES);
+                return;
+            }
+            self.activity.touch();
+            let (ttl, attr) = self.caller_attr(self.root_attr, (req.uid(), req.gid()));
+            reply.attr(&ttl, &attr);
+            return;
+        }
+        match self.container(ino) {
+            Some(fs) => fs.getattr(req, ino, fh, reply),
+            None => reply.error(ENOENT),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// - STD
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// - external
+use nix::unistd::{User, Group};
+
+const MAPPING_COMMENT_PREFIX: char = '#';
+const MAPPING_USER_PREFIX: &str = "uid";
+const MAPPING_GROUP_PREFIX: &str = "gid";
+const MAPPING_DEFAULT_SOURCE: &str = "*";
+
+/// Maps the user and group ids of the acquired system to the ids of the local system.
+/// The mapping file contains one mapping per line:
+/// ```text
+/// # source uid/gid -> local user/group (name or number)
+/// uid 1000:alice
+/// gid 1000:analysts
+/// uid *:nobody
+/// ```
+/// A `*` defines the default mapping of all unmapped ids. Without a default mapping, unmapped ids pass through numerically.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct OwnerMap {
+    users: HashMap<u32, u32>,
+    groups: HashMap<u32, u32>,
+    default_user: Option<u32>,
+    default_group: Option<u32>,
+}
+
+impl OwnerMap {
+    /// Reads the mapping file and resolves the local user and group names.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(path.as_ref()).map_err(|e| e.to_string())?;
+        Self::parse(&content)
+    }
+
+    /// Parses the content of a mapping file and resolves the local user and group names.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut owner_map = Self::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(MAPPING_COMMENT_PREFIX) {
+                continue;
+            }
+            let line_number = index + 1;
+            let (kind, mapping) = line.split_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {line_number}: expected `uid SOURCE:LOCAL` or `gid SOURCE:LOCAL`"))?;
+            let (source, local) = mapping.trim().split_once(':')
+                .ok_or_else(|| format!("line {line_number}: no `:` found in `{mapping}`"))?;
+            let source = match source {
+                MAPPING_DEFAULT_SOURCE => None,
+                source => Some(source.parse::<u32>().map_err(|e| format!("line {line_number}: invalid id `{source}`: {e}"))?),
+            };
+            match kind {
+                MAPPING_USER_PREFIX => {
+                    let uid = resolve_user(local).map_err(|e| format!("line {line_number}: {e}"))?;
+                    match source {
+                        Some(source) => { owner_map.users.insert(source, uid); },
+                        None => owner_map.default_user = Some(uid),
+                    }
+                },
+                MAPPING_GROUP_PREFIX => {
+                    let gid = resolve_group(local).map_err(|e| format!("line {line_number}: {e}"))?;
+                    match source {
+                        Some(source) => { owner_map.groups.insert(source, gid); },
+                        None => owner_map.default_group = Some(gid),
+                    }
+                },
+                kind => return Err(format!("line {line_number}: unknown mapping type `{kind}`")),
+            }
+        }
+        Ok(owner_map)
+    }
+
+    /// Returns the local uid of the given source uid.
+    pub fn map_uid(&self, uid: u32) -> u32 {
+        self.users.get(&uid).copied().or(self.default_user).unwrap_or(uid)
+    }
+
+    /// Returns the local gid of the given source gid.
+    pub fn map_gid(&self, gid: u32) -> u32 {
+        self.groups.get(&gid).copied().or(self.default_group).unwrap_or(gid)
+    }
+}
+
+// resolves the given local user (name or number) by using the passwd database.
+fn resolve_user(user: &str) -> Result<u32, String> {
+    if let Ok(uid) = user.parse::<u32>() {
+        return Ok(uid);
+    }
+    match User::from_name(user) {
+        Ok(Some(user)) => Ok(user.uid.as_raw()),
+        Ok(None) => Err(format!("unknown user `{user}`")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// resolves the given local group (name or number) by using the group database.
+fn resolve_group(group: &str) -> Result<u32, String> {
+    if let Ok(gid) = group.parse::<u32>() {
+        return Ok(gid);
+    }
+    match Group::from_name(group) {
+        Ok(Some(group)) => Ok(group.gid.as_raw()),
+        Ok(None) => Err(format!("unknown group `{group}`")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_skips_comments_and_blank_lines() {
+        let owner_map = OwnerMap::parse("# a comment\n\n   \n  # an indented comment\nuid 1000:2000\n").unwrap();
+        assert_eq!(owner_map.users, HashMap::from([(1000, 2000)]));
+        assert!(owner_map.groups.is_empty());
+        assert_eq!(OwnerMap::parse("").unwrap(), OwnerMap::default());
+    }
+
+    #[test]
+    fn parse_numeric_local_ids() {
+        let owner_map = OwnerMap::parse("uid 1000:2000\ngid 100:200\ngid\t101:201").unwrap();
+        assert_eq!(owner_map.users, HashMap::from([(1000, 2000)]));
+        assert_eq!(owner_map.groups, HashMap::from([(100, 200), (101, 201)]));
+    }
+
+    #[test]
+    fn parse_the_default_mapping() {
+        let owner_map = OwnerMap::parse("uid *:65534\ngid *:65533").unwrap();
+        assert!(owner_map.users.is_empty());
+        assert_eq!(owner_map.default_user, Some(65534));
+        assert_eq!(owner_map.default_group, Some(65533));
+    }
+
+    #[test]
+    fn parse_rejects_an_unknown_kind() {
+        let error = OwnerMap::parse("# comment\nsid 1000:2000").unwrap_err();
+        assert_eq!(error, "line 2: unknown mapping type `sid`");
+    }
+
+    #[test]
+    fn parse_rejects_a_missing_colon() {
+        let error = OwnerMap::parse("uid 1000-2000").unwrap_err();
+        assert_eq!(error, "line 1: no `:` found in `1000-2000`");
+        assert!(OwnerMap::parse("uid").unwrap_err().starts_with("line 1: expected"));
+    }
+
+    #[test]
+    fn parse_rejects_an_invalid_source_id() {
+        assert!(OwnerMap::parse("uid alice:1000").unwrap_err().starts_with("line 1: invalid id `alice`"));
+        assert!(OwnerMap::parse("uid -1:1000").is_err());
+    }
+
+    #[test]
+    fn unmapped_ids_pass_through_without_a_default() {
+        let owner_map = OwnerMap::parse("uid 1000:2000\ngid 100:200").unwrap();
+        assert_eq!(owner_map.map_uid(1000), 2000);
+        assert_eq!(owner_map.map_uid(1001), 1001);
+        assert_eq!(owner_map.map_gid(100), 200);
+        assert_eq!(owner_map.map_gid(0), 0);
+    }
+
+    #[test]
+    fn unmapped_ids_use_the_default() {
+        let owner_map = OwnerMap::parse("uid 1000:2000\nuid *:65534\ngid *:65533").unwrap();
+        assert_eq!(owner_map.map_uid(1000), 2000);
+        assert_eq!(owner_map.map_uid(1001), 65534);
+        assert_eq!(owner_map.map_uid(0), 65534);
+        assert_eq!(owner_map.map_gid(100), 65533);
+    }
+}
@@ -0,0 +1,206 @@
+// - STD
+use std::fmt;
+use std::io::{Read, Seek};
+
+// - internal
+use crate::constants::*;
+use crate::timestamps;
+use zff::{
+    Result,
+    footer::ObjectFooter,
+    io::zffreader::ZffReader,
+    ZffError,
+    ZffErrorKind,
+};
+
+// - external
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// The format of the acquisition reports and the unreadable ranges files.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl ReportFormat {
+    // returns the filename of the given file stem (e.g. acquisition_report) in this format.
+    pub(crate) fn file_name(&self, stem: &str) -> String {
+        match self {
+            ReportFormat::Text => format!("{stem}.txt"),
+            ReportFormat::Json => format!("{stem}.json"),
+        }
+    }
+}
+
+/// A stored hash value of a physical object.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub(crate) struct ReportHash {
+    pub hash_type: String,
+    pub value: String,
+}
+
+/// The acquisition report of a single object, which is built from the description header and the footer of the object.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub(crate) struct AcquisitionReport {
+    pub object_number: u64,
+    pub object_type: String,
+    pub case_number: Option<String>,
+    pub evidence_number: Option<String>,
+    pub examiner_name: Option<String>,
+    pub notes: Option<String>,
+    pub acquisition_start: String,
+    pub acquisition_end: String,
+    pub chunk_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_of_data: Option<u64>, // physical objects only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_of_files: Option<u64>, // logical objects only.
+    pub hashes: Vec<ReportHash>,
+    pub number_of_segments: u64, // the segments of the whole container.
+    pub decrypted_at_mount: bool,
+    pub generated_by: String,
+}
+
+impl AcquisitionReport {
+    // returns the content of the report file in the given format.
+    pub(crate) fn render(&self, format: ReportFormat) -> Vec<u8> {
+        match format {
+            ReportFormat::Text => self.to_string().into_bytes(),
+            ReportFormat::Json => {
+                // the report contains only strings and numbers, so the serialization could not fail.
+                let mut json = serde_json::to_vec_pretty(self).unwrap_or_default();
+                json.push(b'\n');
+                json
+            },
+        }
+    }
+}
+
+impl fmt::Display for AcquisitionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| String::from("-"));
+        writeln!(f, "Acquisition report of object {}", self.object_number)?;
+        writeln!(f)?;
+        writeln!(f, "Object type:        {}", self.object_type)?;
+        writeln!(f, "Case number:        {}", unknown(&self.case_number))?;
+        writeln!(f, "Evidence number:    {}", unknown(&self.evidence_number))?;
+        writeln!(f, "Examiner:           {}", unknown(&self.examiner_name))?;
+        writeln!(f, "Acquisition start:  {}", self.acquisition_start)?;
+        writeln!(f, "Acquisition end:    {}", self.acquisition_end)?;
+        writeln!(f, "Chunk size:         {} bytes", self.chunk_size)?;
+        if let Some(length_of_data) = self.length_of_data {
+            writeln!(f, "Size of the image:  {length_of_data} bytes")?;
+        }
+        if let Some(number_of_files) = self.number_of_files {
+            writeln!(f, "Number of files:    {number_of_files}")?;
+        }
+        writeln!(f, "Segments:           {} (of the whole container)", self.number_of_segments)?;
+        if !self.hashes.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Stored hash values:")?;
+            for hash in &self.hashes {
+                writeln!(f, "  {}: {}", hash.hash_type, hash.value)?;
+            }
+        }
+        if let Some(notes) = &self.notes {
+            writeln!(f)?;
+            writeln!(f, "Notes:")?;
+            for line in notes.lines() {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        if self.decrypted_at_mount {
+            writeln!(f)?;
+            writeln!(f, "Note: The object is encrypted and was decrypted by zffmount at mount time.")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Generated by {}", self.generated_by)
+    }
+}
+
+/// A range of a physical object, which could not be read while acquiring it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub(crate) struct UnreadableRange {
+    pub start: u64,
+    pub end: u64, // inclusive.
+    pub length: u64,
+}
+
+/// Returns the content of the unreadable ranges file of the given damaged byte ranges (start, exclusive end) in the given
+/// format. The text file is empty, if there are no unreadable ranges.
+pub(crate) fn render_unreadable_ranges(ranges: &[(u64, u64)], format: ReportFormat) -> Vec<u8> {
+    let ranges: Vec<UnreadableRange> = ranges.iter()
+        .map(|(start, end)| UnreadableRange { start: *start, end: end - 1, length: end - start })
+        .collect();
+    match format {
+        ReportFormat::Text if ranges.is_empty() => Vec::new(),
+        ReportFormat::Text => {
+            let mut content = String::from("# start offset, end offset (inclusive), length - in bytes\n");
+            for range in ranges {
+                content.push_str(&format!("{} {} {}\n", range.start, range.end, range.length));
+            }
+            content.into_bytes()
+        },
+        ReportFormat::Json => {
+            let mut json = serde_json::to_vec_pretty(&ranges).unwrap_or_default();
+            json.push(b'\n');
+            json
+        },
+    }
+}
+
+/// Builds the acquisition report of the given object.
+pub(crate) fn acquisition_report_of_object<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    object_number: u64,
+    number_of_segments: u64,
+    decrypted_at_mount: bool) -> Result<AcquisitionReport> {
+    zffreader.set_active_object(object_number)?;
+    let header = zffreader.active_object_header_ref()?;
+    let chunk_size = header.chunk_size;
+    let description_header = &header.description_header;
+    let case_number = description_header.case_number().map(String::from);
+    let evidence_number = description_header.evidence_number().map(String::from);
+    let examiner_name = description_header.examiner_name().map(String::from);
+    let notes = description_header.notes().map(String::from);
+
+    let object_footer = zffreader.active_object_footer()?;
+    let (object_type, length_of_data, number_of_files, hashes) = match &object_footer {
+        ObjectFooter::Physical(footer) => {
+            let hashes = footer.hash_header.hashes.iter()
+                .map(|hash_value| ReportHash { hash_type: hash_value.hash_type().to_string(), value: hex::encode(hash_value.hash()) })
+                .collect();
+            ("physical", Some(footer.length_of_data), None, hashes)
+        },
+        ObjectFooter::Logical(footer) => ("logical", None, Some(footer.file_footer_segment_numbers().len() as u64), Vec::new()),
+        ObjectFooter::Virtual(_) => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, "virtual")),
+    };
+    Ok(AcquisitionReport {
+        object_number,
+        object_type: object_type.to_string(),
+        case_number,
+        evidence_number,
+        examiner_name,
+        notes,
+        acquisition_start: format_timestamp(object_footer.acquisition_start() as i64),
+        acquisition_end: format_timestamp(object_footer.acquisition_end() as i64),
+        chunk_size,
+        length_of_data,
+        number_of_files,
+        hashes,
+        number_of_segments,
+        decrypted_at_mount,
+        generated_by: format!("zffmount {}", env!("CARGO_PKG_VERSION")),
+    })
+}
+
+// formats the given unix timestamp (in seconds) in the configured timezone.
+fn format_timestamp(timestamp: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(timestamp) {
+        Ok(time) => timestamps::format(time),
+        Err(_) => timestamp.to_string(),
+    }
+}
@@ -0,0 +1,75 @@
+// - STD
+use std::borrow::Cow;
+use std::cmp::min;
+use std::io::{Read, Seek, SeekFrom};
+
+// - internal
+use crate::constants::*;
+use super::chunkmap::samebyte_of_chunk;
+use super::chunk_cache::{ChunkCache, ChunkRange, read_cached};
+use zff::{Result, io::zffreader::ZffReader};
+
+// the zero chunks are the common case (e.g. the unused space of a disk image). The static buffer is placed in the
+// zero-initialized data segment, so it does not need to be allocated or filled at runtime.
+static ZERO_BUFFER: [u8; SAMEBYTES_ZERO_BUFFER_SIZE] = [0; SAMEBYTES_ZERO_BUFFER_SIZE];
+
+/// Reads the given range of the active file (or physical object) of the reader. The samebytes chunks are filled
+/// without reading (and decompressing) them, so only the other chunks are read by the reader (or the chunk cache, if given).
+/// If the range is covered exclusively by samebytes chunks of the same byte, the reader is not used at all.
+pub(crate) fn read_samebytes_aware<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    mut chunk_cache: Option<&mut ChunkCache>,
+    range: ChunkRange,
+    offset: u64,
+    size: u32) -> Result<Cow<'static, [u8]>> {
+    let end = min(offset.saturating_add(size as u64), range.length_of_data);
+    if offset >= end {
+        return Ok(Cow::Borrowed(&[]));
+    }
+    let first_index = offset / range.chunk_size;
+    let last_index = (end - 1) / range.chunk_size;
+    let mut samebytes = Vec::with_capacity((last_index - first_index + 1) as usize);
+    for index in first_index..=last_index {
+        samebytes.push(samebyte_of_chunk(zffreader, range.first_chunk_number + index)?);
+    }
+    if let Some(byte) = samebytes[0] {
+        if samebytes.iter().all(|samebyte| *samebyte == Some(byte)) {
+            return Ok(filled(byte, (end - offset) as usize));
+        }
+    }
+
+    let mut data = Vec::with_capacity((end - offset) as usize);
+    let mut index = first_index;
+    while index <= last_index {
+        let run_start = index;
+        let samebyte = samebytes[(index - first_index) as usize];
+        // the following chunks with the same kind (the same samebyte or data chunks) are handled together.
+        while index <= last_index && samebytes[(index - first_index) as usize] == samebyte {
+            index += 1;
+        }
+        let start = offset.max(run_start * range.chunk_size);
+        let length = min(end, index * range.chunk_size) - start;
+        match samebyte {
+            Some(byte) => data.resize(data.len() + length as usize, byte),
+            None => match chunk_cache.as_deref_mut() {
+                Some(chunk_cache) => data.extend_from_slice(&read_cached(zffreader, chunk_cache, range, start, length as u32)?),
+                None => {
+                    let position = data.len();
+                    data.resize(position + length as usize, 0);
+                    zffreader.seek(SeekFrom::Start(start))?;
+                    zffreader.read_exact(&mut data[position..])?;
+                }
+            },
+        }
+    }
+    Ok(Cow::Owned(data))
+}
+
+// returns the given number of the given byte (from the static zero buffer, if possible).
+fn filled(byte: u8, length: usize) -> Cow<'static, [u8]> {
+    if byte == 0 && length <= ZERO_BUFFER.len() {
+        Cow::Borrowed(&ZERO_BUFFER[..length])
+    } else {
+        Cow::Owned(vec![byte; length])
+    }
+}
@@ -0,0 +1,262 @@
+// - STD
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// - internal
+use crate::constants::*;
+
+// - external
+use log::{trace, log_enabled, Level};
+
+// the number of buckets per power of two. The relative error of the percentiles is 1/SUB_BUCKETS.
+const SUB_BUCKETS: u64 = 8;
+const SUB_BUCKET_BITS: u32 = 3;
+
+/// The filesystem operations, which are measured.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub(crate) enum Operation {
+    Read,
+    Readdir,
+    Lookup,
+    Getattr,
+    Readlink,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Operation::Read => "READ",
+            Operation::Readdir => "READDIR",
+            Operation::Lookup => "LOOKUP",
+            Operation::Getattr => "GETATTR",
+            Operation::Readlink => "READLINK",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A log-linear histogram of the latencies of a single operation. The memory usage is constant,
+/// regardless of the number of measured calls.
+#[derive(Debug, Clone)]
+struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; bucket_index(u64::MAX) + 1],
+            count: 0,
+        }
+    }
+
+    fn record(&mut self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.buckets[bucket_index(nanos)] += 1;
+        self.count += 1;
+    }
+
+    // returns the lower bound of the bucket, which contains the given percentile.
+    fn percentile(&self, percentile: f64) -> Duration {
+        let rank = ((self.count as f64 * percentile / 100.0).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Duration::from_nanos(bucket_lower_bound(index));
+            }
+        }
+        Duration::ZERO
+    }
+}
+
+fn bucket_index(nanos: u64) -> usize {
+    if nanos < SUB_BUCKETS {
+        return nanos as usize;
+    }
+    let exponent = 63 - nanos.leading_zeros();
+    let mantissa = (nanos >> (exponent - SUB_BUCKET_BITS)) & (SUB_BUCKETS - 1);
+    ((exponent - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS + mantissa) as usize
+}
+
+fn bucket_lower_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let exponent = index / SUB_BUCKETS + SUB_BUCKET_BITS as u64 - 1;
+    let mantissa = index % SUB_BUCKETS;
+    (SUB_BUCKETS + mantissa) << (exponent - SUB_BUCKET_BITS as u64)
+}
+
+/// The latency statistics of all measured operations.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LatencyStats {
+    histograms: BTreeMap<Operation, LatencyHistogram>,
+}
+
+impl LatencyStats {
+    pub(crate) fn record(&mut self, operation: Operation, duration: Duration) {
+        self.histograms.entry(operation).or_insert_with(LatencyHistogram::new).record(duration);
+    }
+}
+
+impl fmt::Display for LatencyStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (operation, histogram) in &self.histograms {
+            writeln!(f, "{operation}: calls={} p50={:?} p95={:?} p99={:?}",
+                histogram.count,
+                histogram.percentile(50.0),
+                histogram.percentile(95.0),
+                histogram.percentile(99.0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the duration of a single operation. The duration will be logged (at trace level) and recorded
+/// in the latency statistics (if enabled), when the timer is dropped.
+pub(crate) struct OperationTimer {
+    operation: Operation,
+    details: String,
+    start: Instant,
+    stats: Option<Arc<Mutex<LatencyStats>>>,
+}
+
+impl OperationTimer {
+    /// Returns None, if the log level is below trace and the latency statistics are disabled,
+    /// so the timing overhead is skipped entirely. The details are only generated if needed.
+    pub(crate) fn start<F: FnOnce() -> String>(
+        operation: Operation,
+        stats: &Option<Arc<Mutex<LatencyStats>>>,
+        details: F) -> Option<Self> {
+        let trace_enabled = log_enabled!(Level::Trace);
+        if !trace_enabled && stats.is_none() {
+            return None;
+        }
+        Some(Self {
+            operation,
+            details: if trace_enabled { details() } else { String::new() },
+            start: Instant::now(),
+            stats: stats.clone(),
+        })
+    }
+
+    /// Appends the given details, which are only known after the operation (e.g. the number of read chunks), to the trace line.
+    pub(crate) fn add_details<F: FnOnce() -> String>(&mut self, details: F) {
+        if log_enabled!(Level::Trace) {
+            self.details.push(' ');
+            self.details.push_str(&details());
+        }
+    }
+}
+
+impl Drop for OperationTimer {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        trace!("{} {} took={:.1?}", self.operation, self.details, duration);
+        if let Some(stats) = &self.stats {
+            match stats.lock() {
+                Ok(mut stats) => stats.record(self.operation, duration),
+                Err(poisoned) => poisoned.into_inner().record(self.operation, duration),
+            }
+        }
+    }
+}
+
+/// The panics, which were caught in the filesystem operations (see ZffFs::guarded).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PanicStats {
+    caught: u64,
+    inodes: HashMap<u64, u64>, //<inode, number of caught panics>
+}
+
+impl PanicStats {
+    pub(crate) fn record(&mut self, inode: u64) {
+        self.caught += 1;
+        *self.inodes.entry(inode).or_default() += 1;
+    }
+
+    pub(crate) fn caught(&self) -> u64 {
+        self.caught
+    }
+
+    /// Returns true, if the operations of the given inode panicked repeatedly and should fail immediately.
+    pub(crate) fn is_failing(&self, inode: u64) -> bool {
+        matches!(self.inodes.get(&inode), Some(panics) if *panics >= PANIC_FAIL_FAST_THRESHOLD)
+    }
+}
+
+impl fmt::Display for PanicStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failing = self.inodes.values().filter(|panics| **panics >= PANIC_FAIL_FAST_THRESHOLD).count();
+        write!(f, "Panics caught: {} in {} inodes ({failing} inodes failing fast)", self.caught, self.inodes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_values_have_their_own_bucket() {
+        for nanos in 0..SUB_BUCKETS {
+            assert_eq!(bucket_index(nanos), nanos as usize);
+            assert_eq!(bucket_lower_bound(nanos as usize), nanos);
+        }
+        assert_eq!(bucket_index(8), 8);
+        assert_eq!(bucket_lower_bound(8), 8);
+    }
+
+    #[test]
+    fn buckets_at_a_power_of_two() {
+        assert_eq!(bucket_index(15), 15);
+        assert_eq!(bucket_index(16), 16);
+        assert_eq!(bucket_index(17), 16);
+        assert_eq!(bucket_index(18), 17);
+        assert_eq!(bucket_lower_bound(16), 16);
+        assert_eq!(bucket_lower_bound(17), 18);
+        assert_eq!(bucket_index((1 << 20) - 1) + 1, bucket_index(1 << 20));
+        assert_eq!(bucket_lower_bound(bucket_index(1 << 20)), 1 << 20);
+    }
+
+    #[test]
+    fn the_largest_value_is_in_the_last_bucket() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(bucket_index(u64::MAX), histogram.buckets.len() - 1);
+        assert_eq!(bucket_lower_bound(bucket_index(u64::MAX)), 0b1111 << 60);
+    }
+
+    #[test]
+    fn the_lower_bound_of_the_bucket_is_not_above_the_value() {
+        let values = (0..4096).chain((0..64).map(|shift| 1u64 << shift)).chain((1..64).map(|shift| (1u64 << shift) - 1)).chain([u64::MAX]);
+        for nanos in values {
+            let lower_bound = bucket_lower_bound(bucket_index(nanos));
+            assert!(lower_bound <= nanos, "{nanos}");
+            // the relative error is at most 1/SUB_BUCKETS.
+            assert!(nanos - lower_bound <= lower_bound / SUB_BUCKETS, "{nanos}");
+        }
+    }
+
+    #[test]
+    fn percentiles_of_a_known_distribution() {
+        let mut histogram = LatencyHistogram::new();
+        for nanos in 1..=100 {
+            histogram.record(Duration::from_nanos(nanos));
+        }
+        assert_eq!(histogram.count, 100);
+        // the 50th value is in the bucket 48..52, the 99th value is in the bucket 96..104.
+        assert_eq!(histogram.percentile(50.0), Duration::from_nanos(48));
+        assert_eq!(histogram.percentile(99.0), Duration::from_nanos(96));
+        assert_eq!(histogram.percentile(100.0), Duration::from_nanos(96));
+        assert_eq!(histogram.percentile(0.0), Duration::from_nanos(1));
+    }
+
+    #[test]
+    fn percentiles_of_an_empty_histogram() {
+        assert_eq!(LatencyHistogram::new().percentile(50.0), Duration::ZERO);
+    }
+}
@@ -0,0 +1,172 @@
+// - STD
+use std::fmt;
+use std::io::{Read, Seek};
+use std::time::SystemTime;
+
+// - internal
+use super::ZffFs;
+use crate::constants::*;
+use crate::timestamps;
+use zff::io::zffreader::ObjectType as ZffReaderObjectType;
+
+// - external
+use fuser::FileType;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// The preloaded chunkmaps of a container.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreloadSummary {
+    pub mode: String, // none, in-memory or redb.
+    pub offsets: bool,
+    pub sizes: bool,
+    pub flags: bool,
+    pub samebytes: bool,
+}
+
+/// The summary of a single object.
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectSummary {
+    pub object_number: u64,
+    pub object_type: String,
+    /// The size of the image (physical objects) or the total size of the regular files (logical objects).
+    pub size: Option<u64>,
+    pub acquisition_start: Option<String>,
+    pub acquisition_end: Option<String>,
+    pub encryption: String, // none, decrypted or locked.
+    /// The name of the top-level entry in the mount point (None, if the object is not presented).
+    pub entry: Option<String>,
+}
+
+/// An entry of the object list at the root directory (see XATTR_OBJECTS).
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ObjectListEntry {
+    pub number: u64,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub size: Option<u64>,
+    pub encrypted: bool,
+}
+
+/// The summary of a single container.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>, // only set, if multiple containers are mounted.
+    pub objects: Vec<ObjectSummary>,
+    pub preload: PreloadSummary,
+}
+
+/// The summary of the mount, which is printed after the initialization.
+#[derive(Debug, Clone, Serialize)]
+pub struct MountSummary {
+    pub containers: Vec<ContainerSummary>,
+    pub mount_options: Vec<String>,
+}
+
+impl fmt::Display for MountSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Mount summary")?;
+        for container in &self.containers {
+            writeln!(f)?;
+            if let Some(name) = &container.container {
+                writeln!(f, "Container {name}")?;
+            }
+            writeln!(f, "{:>6} | {:<9} | {:>14} | {:<25} | {:<25} | {:<9} | entry",
+                "object", "type", "size", "acquisition start", "acquisition end", "encrypted")?;
+            for object in &container.objects {
+                writeln!(f, "{:>6} | {:<9} | {:>14} | {:<25} | {:<25} | {:<9} | {}",
+                    object.object_number,
+                    object.object_type,
+                    object.size.map(|size| size.to_string()).as_deref().unwrap_or("-"),
+                    object.acquisition_start.as_deref().unwrap_or("-"),
+                    object.acquisition_end.as_deref().unwrap_or("-"),
+                    object.encryption,
+                    object.entry.as_deref().unwrap_or("(hidden)"))?;
+            }
+            let preload = &container.preload;
+            writeln!(f, "Preloaded chunkmaps: mode {}, offsets: {}, sizes: {}, flags: {}, samebytes: {}",
+                preload.mode, preload.offsets, preload.sizes, preload.flags, preload.samebytes)?;
+        }
+        writeln!(f)?;
+        write!(f, "Mount options: {}", self.mount_options.join(", "))
+    }
+}
+
+impl<R: Read + Seek> ZffFs<R> {
+    /// Returns the summary of this container (with the given name, if multiple containers are mounted).
+    pub fn summary(&self, container: Option<&str>) -> ContainerSummary {
+        let objects = self.cache.object_list.iter()
+            .map(|(&object_number, obj_type)| self.object_summary(object_number, obj_type))
+            .collect();
+        ContainerSummary {
+            container: container.map(String::from),
+            objects,
+            preload: self.preload.clone(),
+        }
+    }
+
+    // returns the summary of the given object.
+    fn object_summary(&self, object_number: u64, obj_type: &ZffReaderObjectType) -> ObjectSummary {
+        let locked = obj_type == &ZffReaderObjectType::Encrypted;
+        // the attributes of the object directory (or of the placeholder directory) contain the acquisition times.
+        let dir_inode = if locked { SPECIAL_INODE_LOCKED_OBJECTS_START + object_number } else { object_number + 1 };
+        let dir_attr = self.cache.attr(dir_inode);
+        let size = self.object_size(object_number, obj_type);
+        let encryption = match self.cache.encrypted_objects.get(&object_number) {
+            None => "none",
+            Some(encryption) if encryption.decrypted => "decrypted",
+            Some(_) => "locked",
+        };
+        let entry = if locked {
+            self.locked_object_number(dir_inode)
+                .map(|_| format!("{}{object_number}{LOCKED_OBJECT_DIR_SUFFIX}", self.object_dir_prefix()))
+        } else {
+            match self.object_path(object_number).to_string_lossy() {
+                path if path.is_empty() => Some(String::from("/")),
+                path => Some(path.into_owned()),
+            }
+        };
+        ObjectSummary {
+            object_number,
+            object_type: obj_type.to_string(),
+            size,
+            acquisition_start: dir_attr.map(|attr| format_time(attr.crtime)),
+            acquisition_end: dir_attr.map(|attr| format_time(attr.mtime)),
+            encryption: encryption.to_string(),
+            entry,
+        }
+    }
+
+    // returns the list of the objects of this container (as JSON array).
+    pub(crate) fn object_list_json(&self) -> Vec<u8> {
+        let objects: Vec<ObjectListEntry> = self.cache.object_list.iter()
+            .map(|(&object_number, obj_type)| ObjectListEntry {
+                number: object_number,
+                object_type: obj_type.to_string(),
+                size: self.object_size(object_number, obj_type),
+                encrypted: self.cache.encrypted_objects.contains_key(&object_number),
+            })
+            .collect();
+        // the entries contain only strings, numbers and booleans, so the serialization could not fail.
+        serde_json::to_vec(&objects).unwrap_or_default()
+    }
+
+    // returns the size of the image (physical objects) or the total size of the regular files (logical objects).
+    fn object_size(&self, object_number: u64, obj_type: &ZffReaderObjectType) -> Option<u64> {
+        match obj_type {
+            ZffReaderObjectType::Physical => self.cache.physical_objects.get(&object_number).map(|object| object.length_of_data),
+            ZffReaderObjectType::Logical => Some(self.cache.inode_reverse_map.iter()
+                .filter(|(_, (file_object_number, _))| *file_object_number == object_number)
+                .filter_map(|(inode, _)| self.cache.inode_attributes_map.get(inode))
+                .filter(|attr| attr.kind == FileType::RegularFile)
+                .map(|attr| attr.size)
+                .sum()),
+            _ => None,
+        }
+    }
+}
+
+fn format_time(time: SystemTime) -> String {
+    timestamps::format(OffsetDateTime::from(time))
+}
@@ -0,0 +1,174 @@
+// - STD
+use std::cmp::min;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+
+// - internal
+use crate::timestamps;
+use zff::{
+    Result,
+    header::{FileType as ZffFileType, HashValue},
+    footer::ObjectFooter,
+    hashing::Hash,
+    io::zffreader::ZffReader,
+    ZffError,
+    ZffErrorKind,
+};
+
+// - external
+use log::{debug, info};
+use digest::DynDigest;
+use time::OffsetDateTime;
+
+/// The comparison of a single stored hash value with the appropriate computed one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct HashVerification {
+    pub file_number: Option<u64>, // None for physical objects.
+    pub hash_type: String,
+    pub stored: Vec<u8>,
+    pub computed: Vec<u8>,
+}
+
+impl HashVerification {
+    pub(crate) fn is_match(&self) -> bool {
+        self.stored == self.computed
+    }
+}
+
+/// The result of the integrity verification of a single object.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct VerificationResult {
+    pub object_number: u64,
+    pub timestamp: OffsetDateTime,
+    pub hashes: Vec<HashVerification>,
+    pub failed_chunks: Vec<u64>,
+}
+
+impl VerificationResult {
+    fn new(object_number: u64) -> Self {
+        Self {
+            object_number,
+            timestamp: OffsetDateTime::now_utc(),
+            hashes: Vec::new(),
+            failed_chunks: Vec::new(),
+        }
+    }
+
+    /// Returns true, if all hash values are matching and no chunk could not be read.
+    pub(crate) fn is_successful(&self) -> bool {
+        self.failed_chunks.is_empty() && self.hashes.iter().all(|hash| hash.is_match())
+    }
+}
+
+impl fmt::Display for VerificationResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let timestamp = timestamps::format(self.timestamp);
+        writeln!(f, "Verification of object {}", self.object_number)?;
+        writeln!(f, "Timestamp: {timestamp}")?;
+        writeln!(f, "Result: {}", if self.is_successful() { "successful" } else { "failed" })?;
+        for hash in &self.hashes {
+            writeln!(f)?;
+            match hash.file_number {
+                Some(file_number) => writeln!(f, "[file {file_number}] {}", hash.hash_type)?,
+                None => writeln!(f, "[image] {}", hash.hash_type)?,
+            }
+            writeln!(f, "  stored:   {}", hex::encode(&hash.stored))?;
+            writeln!(f, "  computed: {}", hex::encode(&hash.computed))?;
+            writeln!(f, "  result:   {}", if hash.is_match() { "match" } else { "MISMATCH" })?;
+        }
+        writeln!(f)?;
+        if self.failed_chunks.is_empty() {
+            writeln!(f, "Failed chunks: none")
+        } else {
+            let failed_chunks: Vec<String> = self.failed_chunks.iter().map(|chunk_no| chunk_no.to_string()).collect();
+            writeln!(f, "Failed chunks: {}", failed_chunks.join(", "))
+        }
+    }
+}
+
+/// Verifies the integrity of the given object by reading the complete data and comparing the computed
+/// hash values with the stored ones. This could take a long time on large objects.
+pub(crate) fn verify_object<R: Read + Seek>(zffreader: &mut ZffReader<R>, object_number: u64) -> Result<VerificationResult> {
+    zffreader.set_active_object(object_number)?;
+    let chunk_size = zffreader.active_object_header_ref()?.chunk_size;
+    let mut result = VerificationResult::new(object_number);
+
+    match zffreader.active_object_footer()? {
+        ObjectFooter::Physical(footer) => {
+            verify_active_data(
+                zffreader,
+                &footer.hash_header.hashes,
+                footer.length_of_data,
+                chunk_size,
+                footer.first_chunk_number,
+                None,
+                &mut result)?;
+        },
+        ObjectFooter::Logical(footer) => {
+            for filenumber in footer.file_footer_segment_numbers().keys() {
+                zffreader.set_active_file(*filenumber)?;
+                let filemetadata = zffreader.current_filemetadata()?;
+                if filemetadata.file_type != ZffFileType::File {
+                    continue;
+                }
+                let length_of_data = filemetadata.length_of_data;
+                let first_chunk_number = filemetadata.first_chunk_number;
+                let stored_hashes = zffreader.current_filefooter()?.hash_header.hashes;
+                verify_active_data(
+                    zffreader,
+                    &stored_hashes,
+                    length_of_data,
+                    chunk_size,
+                    first_chunk_number,
+                    Some(*filenumber),
+                    &mut result)?;
+            }
+        },
+        ObjectFooter::Virtual(_) => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, "virtual")),
+    }
+    info!("Verification of object {object_number} finished: {}", if result.is_successful() { "successful" } else { "failed" });
+    Ok(result)
+}
+
+// reads the data of the active object (or the active file) chunkwise and feeds the appropriate hashers.
+fn verify_active_data<R: Read + Seek>(
+    zffreader: &mut ZffReader<R>,
+    stored_hashes: &[HashValue],
+    length_of_data: u64,
+    chunk_size: u64,
+    first_chunk_number: u64,
+    file_number: Option<u64>,
+    result: &mut VerificationResult) -> Result<()> {
+    let mut hashers: Vec<(&HashValue, Box<dyn DynDigest>)> = stored_hashes.iter()
+        .map(|hash_value| (hash_value, Hash::new_hasher(hash_value.hash_type())))
+        .collect();
+
+    zffreader.rewind()?;
+    let mut position = 0;
+    let mut chunk_number = first_chunk_number;
+    while position < length_of_data {
+        let to_read = min(chunk_size, length_of_data - position);
+        let mut buffer = vec![0u8; to_read as usize];
+        if let Err(e) = zffreader.read_exact(&mut buffer) {
+            debug!("Could not read chunk {chunk_number} while verifying object {}: {e}", result.object_number);
+            result.failed_chunks.push(chunk_number);
+            buffer = vec![0u8; to_read as usize];
+            zffreader.seek(SeekFrom::Start(position + to_read))?;
+        }
+        for (_, hasher) in hashers.iter_mut() {
+            hasher.update(&buffer);
+        }
+        position += to_read;
+        chunk_number += 1;
+    }
+
+    for (hash_value, hasher) in hashers {
+        result.hashes.push(HashVerification {
+            file_number,
+            hash_type: hash_value.hash_type().to_string(),
+            stored: hash_value.hash().to_vec(),
+            computed: hasher.finalize().to_vec(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+// - STD
+use std::sync::Arc;
+
+// - internal
+use crate::constants::*;
+use super::archive::ArchiveLayout;
+use super::chunkmap::ChunkmapLayout;
+use super::attr::CompactAttr;
+use zff::hashing::HashType;
+
+// - external
+use fuser::FileType;
+
+/// The different types of virtual files, which are placed by zffmount into the object directories.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum VirtualFileType {
+    /// Contains the result of the integrity verification of the appropriate object.
+    Verification,
+    /// Contains the chunk allocation map of the appropriate physical object. The content is generated on the fly by each read.
+    Chunkmap(ChunkmapLayout),
+    /// A segment of the split raw image of the appropriate physical object, which contains the data of the given offset window.
+    RawSegment { offset: u64, length: u64 },
+    /// Contains a stored hash value of the image of the appropriate physical object in the format of the coreutils
+    /// (e.g. sha256sum -c). The content is set while creating the file.
+    HashSidecar,
+    /// Contains the acquisition report of the appropriate object. The content is set while creating the file.
+    AcquisitionReport,
+    /// Contains the byte ranges of the appropriate physical object, which could not be read while acquiring it.
+    UnreadableRanges,
+    /// Explains, that the appropriate object is encrypted and could not be decrypted. The file is placed in the locked
+    /// placeholder directory of the object and the content is set while creating the file.
+    LockedNotice,
+    /// A tar archive of the whole content of the appropriate logical object. The content is generated on the fly by each read.
+    Archive(Arc<ArchiveLayout>),
+}
+
+/// A synthetic file which is not part of the container itself, but generated by zffmount.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct VirtualFile {
+    pub object_number: u64,
+    pub parent_inode: u64,
+    pub name: String,
+    pub file_type: VirtualFileType,
+    pub content: Option<Vec<u8>>, // will be None until the content was generated.
+}
+
+impl VirtualFile {
+    pub(crate) fn new<N: Into<String>>(object_number: u64, parent_inode: u64, name: N, file_type: VirtualFileType) -> Self {
+        Self {
+            object_number,
+            parent_inode,
+            name: name.into(),
+            file_type,
+            content: None,
+        }
+    }
+
+    /// Returns true, if the size in the file attributes is only an estimate (until the content was generated).
+    pub(crate) fn size_is_estimated(&self) -> bool {
+        self.content.is_none() && matches!(self.file_type, VirtualFileType::Verification | VirtualFileType::UnreadableRanges)
+    }
+}
+
+/// Returns the file attributes of a virtual file. The timestamps are taken from the given attributes of the parent directory.
+pub(crate) fn virtual_file_attr(size: u64, parent_attr: &CompactAttr) -> CompactAttr {
+    let mut file_attr = *parent_attr;
+    file_attr.set_size(size);
+    file_attr.kind = FileType::RegularFile;
+    file_attr.perm = VIRTUAL_FILE_PERMISSIONS;
+    file_attr.nlink = 1;
+    file_attr
+}
+
+/// Returns the file extension of the files, which contain hash values of the given type (e.g. sha256 for image.dd.sha256).
+pub fn hash_file_extension(hash_type: &HashType) -> &'static str {
+    match hash_type {
+        HashType::Blake2b512 => "blake2b512",
+        HashType::SHA256 => "sha256",
+        HashType::SHA512 => "sha512",
+        HashType::SHA3_256 => "sha3_256",
+        HashType::Blake3 => "blake3",
+    }
+}
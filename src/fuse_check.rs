@@ -0,0 +1,74 @@
+// - STD
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// - internal
+use crate::addons::find_fusermount;
+use crate::constants::*;
+use crate::error::ZffMountError;
+
+// - external
+use log::debug;
+use nix::unistd::{access, AccessFlags, Uid};
+
+const FUSE_DEVICE_PATH: &str = "/dev/fuse";
+const FUSE_CONF_PATH: &str = "/etc/fuse.conf";
+const USER_ALLOW_OTHER: &str = "user_allow_other";
+
+/// Checks, if a FUSE filesystem could be mounted in the current environment. The first problem found is returned with a
+/// hint how to fix it:
+/// - /dev/fuse has to exist (i.e. the fuse kernel module is loaded) and has to be readable and writable,
+/// - unprivileged users need a fusermount binary on the PATH,
+/// - unprivileged users need user_allow_other in /etc/fuse.conf to use --allow-other.
+pub(crate) fn check_fuse_environment(allow_other: bool) -> Result<(), ZffMountError> {
+    let fuse_device = Path::new(FUSE_DEVICE_PATH);
+    if !fuse_device.exists() {
+        return Err(unavailable(
+            format!("{FUSE_DEVICE_PATH} does not exist"),
+            "Load the fuse kernel module (modprobe fuse). In a container, pass the device to the container (e.g. --device /dev/fuse)."));
+    }
+    if let Err(e) = access(fuse_device, AccessFlags::R_OK | AccessFlags::W_OK) {
+        debug!("{e}");
+        return Err(unavailable(
+            format!("{FUSE_DEVICE_PATH} is not accessible"),
+            "Run zffmount as root or grant the user read and write access to /dev/fuse (e.g. by the fuse group)."));
+    }
+
+    // root mounts the filesystem directly, without the setuid fusermount binary.
+    if Uid::effective().is_root() {
+        return Ok(());
+    }
+    if find_fusermount().is_none() {
+        return Err(unavailable(
+            format!("no fusermount binary ({}) was found on the PATH", FUSERMOUNT_BINARIES.join(", ")),
+            "Install fuse3 (e.g. apt install fuse3) or run zffmount as root."));
+    }
+    if allow_other {
+        match user_allow_other_enabled() {
+            Ok(true) => (),
+            Ok(false) => return Err(unavailable(
+                format!("--allow-other requires {USER_ALLOW_OTHER} in {FUSE_CONF_PATH}"),
+                "Add the line user_allow_other to /etc/fuse.conf or run zffmount as root.")),
+            Err(e) => {
+                debug!("{e}");
+                return Err(unavailable(
+                    format!("--allow-other requires {USER_ALLOW_OTHER}, but {FUSE_CONF_PATH} could not be read"),
+                    "Create /etc/fuse.conf with the line user_allow_other or run zffmount as root."));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unavailable(reason: String, hint: &str) -> ZffMountError {
+    ZffMountError::FuseUnavailable { reason, hint: hint.to_string() }
+}
+
+// checks, if user_allow_other is set in the fuse.conf (the comments start with #).
+fn user_allow_other_enabled() -> io::Result<bool> {
+    let content = fs::read_to_string(FUSE_CONF_PATH)?;
+    Ok(content.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .any(|line| line == USER_ALLOW_OTHER))
+}
@@ -0,0 +1,145 @@
+// - STD
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+// the number of fetched blocks, which will be cached per segment.
+const BLOCK_CACHE_SIZE: usize = 16;
+
+/// A remote resource, which could be read by (inclusive) byte ranges.
+pub(crate) trait RangeSource: Send + Sync + 'static {
+    /// The total length of the resource.
+    fn length(&self) -> u64;
+    /// Fetches the given inclusive byte range.
+    fn fetch(&self, first_byte: u64, last_byte: u64) -> io::Result<Vec<u8>>;
+}
+
+/// A Read + Seek facade of a [RangeSource], which fetches the data in blocks of the given size.
+/// The most recently used blocks are cached. Optionally, the following block will be fetched in the background.
+pub(crate) struct BlockReader<S: RangeSource> {
+    source: Arc<S>,
+    position: u64,
+    block_size: u64,
+    // the most recently used block is at the front.
+    blocks: VecDeque<(u64, Vec<u8>)>,
+    prefetch: bool,
+    pending: Option<(u64, JoinHandle<io::Result<Vec<u8>>>)>,
+}
+
+impl<S: RangeSource> BlockReader<S> {
+    pub(crate) fn new(source: S, block_size: u64, prefetch: bool) -> io::Result<Self> {
+        if block_size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the block size must not be 0"));
+        }
+        Ok(Self {
+            source: Arc::new(source),
+            position: 0,
+            block_size,
+            blocks: VecDeque::with_capacity(BLOCK_CACHE_SIZE),
+            prefetch,
+            pending: None,
+        })
+    }
+
+    // returns the inclusive byte range of the given block.
+    fn block_range(&self, block_number: u64) -> (u64, u64) {
+        let first_byte = block_number * self.block_size;
+        let last_byte = (first_byte + self.block_size).min(self.source.length()) - 1;
+        (first_byte, last_byte)
+    }
+
+    fn fetch_block(&mut self, block_number: u64) -> io::Result<Vec<u8>> {
+        if let Some((pending_block, _)) = &self.pending {
+            if *pending_block == block_number {
+                // unwrap is safe here, as the pending fetch was checked above.
+                let (_, handle) = self.pending.take().unwrap();
+                return match handle.join() {
+                    Ok(result) => result,
+                    Err(_) => Err(io::Error::new(io::ErrorKind::Other, "the prefetch thread panicked")),
+                };
+            }
+        }
+        // a prefetch of another block is useless for a non-sequential read (the thread finishes detached).
+        self.pending = None;
+        let (first_byte, last_byte) = self.block_range(block_number);
+        self.source.fetch(first_byte, last_byte)
+    }
+
+    // fetches the block after the given block in the background, if it is not cached or pending already.
+    fn prefetch_next(&mut self, block_number: u64) {
+        let next_block = block_number + 1;
+        if !self.prefetch
+            || self.pending.is_some()
+            || next_block * self.block_size >= self.source.length()
+            || self.blocks.iter().any(|(number, _)| *number == next_block) {
+            return;
+        }
+        let (first_byte, last_byte) = self.block_range(next_block);
+        let source = Arc::clone(&self.source);
+        self.pending = Some((next_block, thread::spawn(move || source.fetch(first_byte, last_byte))));
+    }
+
+    // returns the given block from the cache or fetches it.
+    fn block(&mut self, block_number: u64) -> io::Result<&[u8]> {
+        match self.blocks.iter().position(|(number, _)| *number == block_number) {
+            Some(index) => {
+                // unwrap is safe here, as the index was found above.
+                let block = self.blocks.remove(index).unwrap();
+                self.blocks.push_front(block);
+            },
+            None => {
+                let data = self.fetch_block(block_number)?;
+                if self.blocks.len() >= BLOCK_CACHE_SIZE {
+                    self.blocks.pop_back();
+                }
+                self.blocks.push_front((block_number, data));
+            }
+        }
+        self.prefetch_next(block_number);
+        Ok(&self.blocks[0].1)
+    }
+}
+
+impl<S: RangeSource> Read for BlockReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.source.length() || buf.is_empty() {
+            return Ok(0);
+        }
+        let block_number = self.position / self.block_size;
+        let offset = (self.position % self.block_size) as usize;
+        let block = self.block(block_number)?;
+        let length = buf.len().min(block.len() - offset);
+        buf[..length].copy_from_slice(&block[offset..offset + length]);
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl<S: RangeSource> Seek for BlockReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.source.length().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+/// Checks that the fetched data has the length of the requested inclusive byte range.
+pub(crate) fn check_fetched_length(name: &str, first_byte: u64, last_byte: u64, data: &[u8]) -> io::Result<()> {
+    let expected_length = last_byte - first_byte + 1;
+    if data.len() as u64 != expected_length {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("{name}: expected {expected_length} bytes at offset {first_byte}, got {}", data.len())));
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+// - STD
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::unix::fs::{FileExt, FileTypeExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+// - external
+use log::info;
+
+// ioctls of linux/fs.h
+const BLKSSZGET: u64 = 0x1268;
+const BLKGETSIZE64: u64 = 0x80081272;
+// the logical block size, if the device does not report it.
+const DEFAULT_LOGICAL_BLOCK_SIZE: usize = 512;
+// the size of the bounce buffer; larger reads of the zff reader are split into multiple reads.
+const DIRECT_IO_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Returns true, if the given path is a block device.
+pub(crate) fn is_block_device(path: &Path) -> io::Result<bool> {
+    Ok(path.metadata()?.file_type().is_block_device())
+}
+
+/// Returns the size of the given block device (the metadata of a block device always reports 0 bytes).
+pub(crate) fn block_device_size(device: &File) -> io::Result<u64> {
+    let mut size: u64 = 0;
+    // Safety: BLKGETSIZE64 expects a pointer to an u64.
+    if unsafe { libc::ioctl(device.as_raw_fd(), BLKGETSIZE64 as _, &mut size as *mut u64) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(size)
+}
+
+/// Opens the given block device and logs its size.
+pub(crate) fn open_block_device(path: &Path) -> io::Result<File> {
+    let device = File::open(path)?;
+    info!("Using block device {} with a size of {} bytes.", path.display(), block_device_size(&device)?);
+    Ok(device)
+}
+
+/// A block device, which is read with O_DIRECT (bypassing the page cache). As O_DIRECT requires reads at aligned offsets
+/// into aligned buffers, the data is read into an aligned bounce buffer first.
+pub(crate) struct DirectReader {
+    device: File,
+    length: u64,
+    position: u64,
+    alignment: usize,
+    buffer: Vec<u8>,
+    // the offset of the aligned part of the buffer.
+    buffer_start: usize,
+    // the device offset and the number of valid bytes of the buffer content.
+    buffered_offset: u64,
+    buffered_length: usize,
+}
+
+impl DirectReader {
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let device = OpenOptions::new().read(true).custom_flags(libc::O_DIRECT).open(path)?;
+        let length = block_device_size(&device)?;
+        let alignment = logical_block_size(&device).unwrap_or(DEFAULT_LOGICAL_BLOCK_SIZE);
+        // the buffer is allocated with additional space, to be able to align the start.
+        let buffer = vec![0; DIRECT_IO_BUFFER_SIZE + alignment];
+        let buffer_start = buffer.as_ptr().align_offset(alignment);
+        info!("Using block device {} with a size of {length} bytes (O_DIRECT, alignment {alignment} bytes).", path.display());
+        Ok(Self { device, length, position: 0, alignment, buffer, buffer_start, buffered_offset: 0, buffered_length: 0 })
+    }
+
+    // reads the aligned window, which contains the current position, into the bounce buffer.
+    fn fill_buffer(&mut self) -> io::Result<()> {
+        let aligned_offset = self.position - self.position % self.alignment as u64;
+        let buffer = &mut self.buffer[self.buffer_start..self.buffer_start + DIRECT_IO_BUFFER_SIZE];
+        self.buffered_length = 0;
+        let read = self.device.read_at(buffer, aligned_offset)?;
+        self.buffered_offset = aligned_offset;
+        self.buffered_length = read;
+        Ok(())
+    }
+}
+
+impl Read for DirectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.length || buf.is_empty() {
+            return Ok(0);
+        }
+        let buffered_end = self.buffered_offset + self.buffered_length as u64;
+        if self.position < self.buffered_offset || self.position >= buffered_end {
+            self.fill_buffer()?;
+        }
+        let offset = (self.position - self.buffered_offset) as usize;
+        if offset >= self.buffered_length {
+            return Ok(0);
+        }
+        let length = buf.len().min(self.buffered_length - offset);
+        let start = self.buffer_start + offset;
+        buf[..length].copy_from_slice(&self.buffer[start..start + length]);
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl Seek for DirectReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+fn logical_block_size(device: &File) -> Option<usize> {
+    let mut size: libc::c_int = 0;
+    // Safety: BLKSSZGET expects a pointer to an int.
+    if unsafe { libc::ioctl(device.as_raw_fd(), BLKSSZGET as _, &mut size as *mut libc::c_int) } < 0 {
+        return None;
+    }
+    usize::try_from(size).ok().filter(|size| size.is_power_of_two())
+}
@@ -0,0 +1,94 @@
+// - STD
+use std::env;
+use std::io::{self, Read};
+
+// - internal
+use super::block::*;
+
+// - external
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use log::debug;
+
+// the credentials could be given by environment variables, so they do not appear in the process list.
+const ENV_HTTP_BEARER_TOKEN: &str = "ZFFMOUNT_HTTP_BEARER_TOKEN";
+const ENV_HTTP_USER: &str = "ZFFMOUNT_HTTP_USER";
+const ENV_HTTP_PASSWORD: &str = "ZFFMOUNT_HTTP_PASSWORD";
+const HTTP_STATUS_PARTIAL_CONTENT: u16 = 206;
+
+/// A segment, which is read by HTTP range requests. The data is fetched in blocks of the given size;
+/// the most recently used blocks are cached.
+pub(crate) type HttpRangeReader = BlockReader<HttpSource>;
+
+impl HttpRangeReader {
+    /// Opens the given URL. Fails, if the server does not support range requests.
+    pub(crate) fn open(url: &str, block_size: u64) -> io::Result<Self> {
+        BlockReader::new(HttpSource::open(url)?, block_size, false)
+    }
+}
+
+/// A resource of a HTTP(S) server, which supports range requests.
+pub(crate) struct HttpSource {
+    agent: ureq::Agent,
+    url: String,
+    authorization: Option<String>,
+    length: u64,
+}
+
+impl HttpSource {
+    fn open(url: &str) -> io::Result<Self> {
+        let mut source = Self {
+            agent: ureq::Agent::new(),
+            url: url.to_string(),
+            authorization: authorization_from_env(),
+            length: 0,
+        };
+        // the total length is part of the Content-Range header of every partial response.
+        let response = source.range_request(0, 0)?;
+        let content_range = response.header("Content-Range").unwrap_or_default().to_string();
+        source.length = match content_range.rsplit_once('/').and_then(|(_, length)| length.parse().ok()) {
+            Some(length) => length,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown length of {url} (Content-Range: {content_range})"))),
+        };
+        debug!("Opened {url} with a length of {} bytes.", source.length);
+        Ok(source)
+    }
+
+    // requests the given (inclusive) byte range and ensures that the server responds with partial content.
+    fn range_request(&self, first_byte: u64, last_byte: u64) -> io::Result<ureq::Response> {
+        let mut request = self.agent.get(&self.url).set("Range", &format!("bytes={first_byte}-{last_byte}"));
+        if let Some(authorization) = &self.authorization {
+            request = request.set("Authorization", authorization);
+        }
+        let response = request.call().map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}: {e}", self.url)))?;
+        if response.status() != HTTP_STATUS_PARTIAL_CONTENT {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("the server of {} does not support range requests (status {})", self.url, response.status())));
+        }
+        Ok(response)
+    }
+}
+
+impl RangeSource for HttpSource {
+    fn length(&self) -> u64 {
+        self.length
+    }
+
+    fn fetch(&self, first_byte: u64, last_byte: u64) -> io::Result<Vec<u8>> {
+        let expected_length = last_byte - first_byte + 1;
+        let mut data = Vec::with_capacity(expected_length as usize);
+        self.range_request(first_byte, last_byte)?.into_reader().take(expected_length).read_to_end(&mut data)?;
+        check_fetched_length(&self.url, first_byte, last_byte, &data)?;
+        Ok(data)
+    }
+}
+
+// a bearer token is preferred over basic auth credentials.
+fn authorization_from_env() -> Option<String> {
+    if let Ok(token) = env::var(ENV_HTTP_BEARER_TOKEN) {
+        return Some(format!("Bearer {token}"));
+    }
+    let user = env::var(ENV_HTTP_USER).ok()?;
+    let password = env::var(ENV_HTTP_PASSWORD).unwrap_or_default();
+    Some(format!("Basic {}", BASE64.encode(format!("{user}:{password}"))))
+}
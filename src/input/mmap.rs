@@ -0,0 +1,100 @@
+// - STD
+use std::cmp::min;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// - external
+use log::debug;
+use memmap2::Mmap;
+
+/// A memory-mapped segment file, which is read without any read syscalls (the page cache is used directly).
+/// Before every read, the length of the file is checked (fstat), so a truncated file results in an I/O error instead of SIGBUS.
+pub(crate) struct MmapReader {
+    file: File,
+    mmap: Mmap,
+    position: u64,
+}
+
+impl Read for MmapReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mapped_length = self.mmap.len() as u64;
+        if self.position >= mapped_length || buf.is_empty() {
+            return Ok(0);
+        }
+        let start = self.position as usize;
+        let end = min(mapped_length, self.position + buf.len() as u64) as usize;
+        // accessing a page of the mapping beyond the current end of the file raises SIGBUS.
+        let current_length = self.file.metadata()?.len();
+        if current_length < end as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("the mapped segment file was truncated from {mapped_length} to {current_length} bytes")));
+        }
+        buf[..end - start].copy_from_slice(&self.mmap[start..end]);
+        self.position = end as u64;
+        Ok(end - start)
+    }
+}
+
+impl Seek for MmapReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.mmap.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+/// Maps the given segment file read-only.
+pub(crate) fn open_mmap(path: &Path) -> io::Result<MmapReader> {
+    let file = File::open(path)?;
+    let length = file.metadata()?.len();
+    // the whole file has to fit into the address space (e.g. on 32-bit targets).
+    if usize::try_from(length).is_err() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is too large to be memory-mapped on this platform ({length} bytes)", path.display())));
+    }
+    // Safety: the mapping is read-only and the length of the file is checked before every read (see MmapReader). A segment file,
+    // which is truncated by another process between the check and the access, still results in SIGBUS; this is documented for
+    // --input-mmap, as evidence files are not expected to change while mounted.
+    let mmap = unsafe { Mmap::map(&file)? };
+    if mmap.len() as u64 != length {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} changed while it was mapped", path.display())));
+    }
+    debug!("Mapped {} ({length} bytes).", path.display());
+    Ok(MmapReader { file, mmap, position: 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reading_a_truncated_file_fails() {
+        let path = std::env::temp_dir().join(format!("zffmount-test-mmap-{}", std::process::id()));
+        fs::write(&path, vec![7u8; 8192]).unwrap();
+        let mut reader = open_mmap(&path).unwrap();
+        let mut buffer = [0u8; 4096];
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [7u8; 4096]);
+
+        File::options().write(true).open(&path).unwrap().set_len(4096).unwrap();
+        let error = reader.read(&mut buffer).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        // the remaining part of the file is still readable.
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,234 @@
+// - STD
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// - modules
+mod block;
+mod device;
+mod mmap;
+mod http;
+#[cfg(feature = "s3")]
+mod object_storage;
+#[cfg(feature = "uring")]
+mod uring;
+
+// - internal
+pub(crate) use http::*;
+pub(crate) use device::*;
+pub(crate) use mmap::*;
+#[cfg(feature = "s3")]
+pub(crate) use object_storage::*;
+#[cfg(feature = "uring")]
+pub(crate) use uring::*;
+
+// - external
+use log::warn;
+
+const SCHEME_SEPARATOR: &str = "://";
+/// The scheme of local files, which is used for all inputs without a scheme.
+pub(crate) const DEFAULT_SCHEME: &str = "file";
+
+/// A readable segment of a zff container. Every Read + Seek + Send type (e.g. a [File]) is a segment source.
+/// A backend for a new transport has to implement Read + Seek and has to be registered in the [SourceRegistry]
+/// with an appropriate [OpenFn] (see the in-memory backend in the tests of this module for an example).
+pub trait SegmentSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> SegmentSource for T {}
+
+/// Opens the segments of the given URL (or path, for the default scheme). A single URL could result in multiple segments
+/// (e.g. a prefix of an object storage), which have to be returned in the order of their segment numbers.
+pub type OpenFn = fn(&str, &InputOptions) -> io::Result<Vec<Box<dyn SegmentSource>>>;
+
+/// The options to open the input segments.
+#[derive(Debug, Clone)]
+pub struct InputOptions {
+    /// Reads block devices with O_DIRECT.
+    pub direct_input: bool,
+    /// Maps the segment files into memory instead of reading them.
+    pub mmap: bool,
+    /// Reads the segment files through an io_uring.
+    #[cfg(feature = "uring")]
+    pub uring: bool,
+    /// The size of a single range request of the HTTP(S) backend.
+    pub http_block_size: u64,
+    /// The size of a single range request of the S3 backend.
+    #[cfg(feature = "s3")]
+    pub s3_request_size: u64,
+    /// Fetches the following range of a S3 object in the background.
+    #[cfg(feature = "s3")]
+    pub s3_prefetch: bool,
+}
+
+/// A type-erased segment source, which could be used by ZffFs.
+pub struct InputReader(Box<dyn SegmentSource>);
+
+impl Read for InputReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Seek for InputReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+/// The backends of the input segments, keyed by the URL scheme.
+pub struct SourceRegistry {
+    options: InputOptions,
+    backends: HashMap<String, OpenFn>,
+}
+
+impl SourceRegistry {
+    /// Creates a registry with the built-in backends (local files, HTTP(S) and, if enabled, S3).
+    pub fn new(options: InputOptions) -> Self {
+        let mut registry = Self { options, backends: HashMap::new() };
+        registry.register(DEFAULT_SCHEME, open_file);
+        registry.register("http", open_http);
+        registry.register("https", open_http);
+        #[cfg(feature = "s3")]
+        registry.register("s3", open_s3_segments);
+        registry
+    }
+
+    /// Registers the backend of the given scheme. An existing backend of the scheme will be replaced.
+    pub fn register(&mut self, scheme: &str, open: OpenFn) {
+        self.backends.insert(scheme.to_ascii_lowercase(), open);
+    }
+
+    /// Opens the given input path (a local file or a URL). Inputs without a scheme are opened by the backend of the default scheme.
+    pub fn open(&self, path: &Path) -> io::Result<Vec<InputReader>> {
+        let url = match path.to_str() {
+            Some(url) => url,
+            // local paths are not necessarily valid UTF-8 (and could not be a URL in this case).
+            None => return Ok(vec![InputReader(Box::new(File::open(path)?))]),
+        };
+        let scheme = split_scheme(url).map(|(scheme, _)| scheme.to_ascii_lowercase()).unwrap_or_else(|| DEFAULT_SCHEME.to_string());
+        let open = match self.backends.get(&scheme) {
+            Some(open) => open,
+            None => return Err(io::Error::new(io::ErrorKind::Unsupported, format!("no backend available for {scheme}{SCHEME_SEPARATOR} inputs"))),
+        };
+        Ok(open(url, &self.options)?.into_iter().map(InputReader).collect())
+    }
+}
+
+/// Returns true, if the input "path" is a URL (and should e.g. not be globbed).
+pub(crate) fn is_url(path: &Path) -> bool {
+    path.to_str().and_then(split_scheme).is_some()
+}
+
+// splits "scheme://location" into the scheme and the location.
+fn split_scheme(url: &str) -> Option<(&str, &str)> {
+    let (scheme, location) = url.split_once(SCHEME_SEPARATOR)?;
+    let valid_scheme = scheme.chars().next().map(|c| c.is_ascii_alphabetic()).unwrap_or(false)
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+    valid_scheme.then_some((scheme, location))
+}
+
+fn open_file(url: &str, options: &InputOptions) -> io::Result<Vec<Box<dyn SegmentSource>>> {
+    let path = Path::new(split_scheme(url).map(|(_, path)| path).unwrap_or(url));
+    if !is_block_device(path)? {
+        if options.mmap {
+            return Ok(vec![Box::new(open_mmap(path)?)]);
+        }
+        #[cfg(feature = "uring")]
+        if options.uring {
+            match UringReader::open(path) {
+                Ok(reader) => return Ok(vec![Box::new(reader)]),
+                // e.g. an older kernel or a seccomp filter, which blocks io_uring.
+                Err(e) => warn!("io_uring is not available, using the standard read path for {}: {e}", path.display()),
+            }
+        }
+        return Ok(vec![Box::new(File::open(path)?)]);
+    }
+    // the metadata of a block device reports a length of 0, so it could not be mapped as a whole.
+    if options.mmap {
+        warn!("{} is a block device and will not be memory-mapped.", path.display());
+    }
+    if options.direct_input {
+        Ok(vec![Box::new(DirectReader::open(path)?)])
+    } else {
+        Ok(vec![Box::new(open_block_device(path)?)])
+    }
+}
+
+fn open_http(url: &str, options: &InputOptions) -> io::Result<Vec<Box<dyn SegmentSource>>> {
+    Ok(vec![Box::new(HttpRangeReader::open(url, options.http_block_size)?)])
+}
+
+#[cfg(feature = "s3")]
+fn open_s3_segments(url: &str, options: &InputOptions) -> io::Result<Vec<Box<dyn SegmentSource>>> {
+    let segments = open_s3(url, options.s3_request_size, options.s3_prefetch)?;
+    Ok(segments.into_iter().map(|segment| Box::new(segment) as Box<dyn SegmentSource>).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const SEGMENT_SIZE: usize = 4;
+
+    // an example backend, which serves the location of the URL as segments of SEGMENT_SIZE bytes (e.g. mem://abcdefgh).
+    fn open_memory(url: &str, _options: &InputOptions) -> io::Result<Vec<Box<dyn SegmentSource>>> {
+        let (_, location) = split_scheme(url).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing scheme"))?;
+        Ok(location.as_bytes().chunks(SEGMENT_SIZE).map(|segment| Box::new(Cursor::new(segment.to_vec())) as Box<dyn SegmentSource>).collect())
+    }
+
+    fn options() -> InputOptions {
+        InputOptions {
+            direct_input: false,
+            mmap: false,
+            #[cfg(feature = "uring")]
+            uring: false,
+            http_block_size: 0,
+            #[cfg(feature = "s3")]
+            s3_request_size: 0,
+            #[cfg(feature = "s3")]
+            s3_prefetch: false,
+        }
+    }
+
+    fn read_to_string(mut reader: InputReader) -> String {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn registered_backend_opens_its_segments_in_order() {
+        let mut registry = SourceRegistry::new(options());
+        registry.register("mem", open_memory);
+        let segments = registry.open(Path::new("mem://abcdefghij")).unwrap();
+        let segments: Vec<String> = segments.into_iter().map(read_to_string).collect();
+        assert_eq!(segments, ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn schemes_are_case_insensitive() {
+        let mut registry = SourceRegistry::new(options());
+        registry.register("MEM", open_memory);
+        let mut segments = registry.open(Path::new("Mem://abc")).unwrap();
+        let mut segment = segments.remove(0);
+        segment.seek(SeekFrom::Start(1)).unwrap();
+        assert_eq!(read_to_string(segment), "bc");
+    }
+
+    #[test]
+    fn unknown_scheme_is_unsupported() {
+        let registry = SourceRegistry::new(options());
+        let error = registry.open(Path::new("mem://abc")).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn split_scheme_requires_a_valid_scheme() {
+        assert_eq!(split_scheme("s3://bucket/prefix"), Some(("s3", "bucket/prefix")));
+        assert_eq!(split_scheme("/path/to/file.z01"), None);
+        assert_eq!(split_scheme("1abc://location"), None);
+        assert_eq!(split_scheme("a b://location"), None);
+    }
+}
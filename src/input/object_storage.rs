@@ -0,0 +1,105 @@
+// - STD
+use std::env;
+use std::io::{self, Seek, SeekFrom};
+
+// - internal
+use super::block::*;
+
+// - external
+use log::debug;
+use s3::{bucket::Bucket, creds::Credentials, region::Region};
+use zff::{header::SegmentHeader, HeaderCoding};
+
+const S3_URL_PREFIX: &str = "s3://";
+// the region and the endpoint (e.g. of a MinIO server) are read from the same environment variables as the AWS tools.
+const ENV_AWS_REGION: &str = "AWS_REGION";
+const ENV_AWS_DEFAULT_REGION: &str = "AWS_DEFAULT_REGION";
+const ENV_AWS_ENDPOINT_URL: &str = "AWS_ENDPOINT_URL";
+const DEFAULT_AWS_REGION: &str = "us-east-1";
+
+/// A segment, which is read by range requests (GetObject) of a S3-compatible object storage.
+pub(crate) type S3Reader = BlockReader<S3Source>;
+
+/// An object of a S3-compatible object storage.
+pub(crate) struct S3Source {
+    bucket: Box<Bucket>,
+    key: String,
+    length: u64,
+}
+
+impl S3Source {
+    fn open(bucket: Box<Bucket>, key: &str) -> io::Result<Self> {
+        let (head, _) = bucket.head_object(key).map_err(|e| s3_error(&bucket.name, key, e))?;
+        let length = match head.content_length.and_then(|length| u64::try_from(length).ok()) {
+            Some(length) => length,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown length of s3://{}/{key}", bucket.name))),
+        };
+        debug!("Opened s3://{}/{key} with a length of {length} bytes.", bucket.name);
+        Ok(Self { bucket, key: key.to_string(), length })
+    }
+}
+
+impl RangeSource for S3Source {
+    fn length(&self) -> u64 {
+        self.length
+    }
+
+    fn fetch(&self, first_byte: u64, last_byte: u64) -> io::Result<Vec<u8>> {
+        let response = self.bucket.get_object_range(&self.key, first_byte, Some(last_byte))
+            .map_err(|e| s3_error(&self.bucket.name, &self.key, e))?;
+        let data = response.bytes().to_vec();
+        check_fetched_length(&format!("s3://{}/{}", self.bucket.name, self.key), first_byte, last_byte, &data)?;
+        Ok(data)
+    }
+}
+
+/// Opens the segments of the given S3 URL. If the URL ends with a slash, all objects with the given prefix are opened
+/// and ordered by the segment numbers of their segment headers.
+pub(crate) fn open_s3(url: &str, request_size: u64, prefetch: bool) -> io::Result<Vec<S3Reader>> {
+    let (bucket_name, key) = match url.strip_prefix(S3_URL_PREFIX).and_then(|location| location.split_once('/')) {
+        Some((bucket_name, key)) if !bucket_name.is_empty() => (bucket_name, key),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid S3 URL {url}, expected s3://bucket/key"))),
+    };
+    let bucket = open_bucket(bucket_name)?;
+    if !key.is_empty() && !key.ends_with('/') {
+        return Ok(vec![BlockReader::new(S3Source::open(bucket, key)?, request_size, prefetch)?]);
+    }
+
+    let listing = bucket.list(key.to_string(), None).map_err(|e| s3_error(bucket_name, key, e))?;
+    let mut segments = Vec::new();
+    for object in listing.iter().flat_map(|result| &result.contents) {
+        let mut segment = BlockReader::new(S3Source::open(bucket.clone(), &object.key)?, request_size, prefetch)?;
+        // objects with another content than zff segments (e.g. hash files) are skipped.
+        match SegmentHeader::decode_directly(&mut segment) {
+            Ok(header) => {
+                segment.seek(SeekFrom::Start(0))?;
+                segments.push((header.segment_number, segment));
+            },
+            Err(e) => debug!("Skipping s3://{bucket_name}/{}, which is not a zff segment: {e}", object.key),
+        }
+    }
+    if segments.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("no zff segments found at {url}")));
+    }
+    segments.sort_by_key(|(segment_number, _)| *segment_number);
+    Ok(segments.into_iter().map(|(_, segment)| segment).collect())
+}
+
+// the credentials are read from the standard AWS credential chain (environment, profile, instance metadata).
+fn open_bucket(bucket_name: &str) -> io::Result<Box<Bucket>> {
+    let region_name = env::var(ENV_AWS_REGION)
+        .or_else(|_| env::var(ENV_AWS_DEFAULT_REGION))
+        .unwrap_or_else(|_| DEFAULT_AWS_REGION.to_string());
+    let (region, path_style) = match env::var(ENV_AWS_ENDPOINT_URL) {
+        // S3-compatible servers often do not support virtual-hosted-style requests.
+        Ok(endpoint) => (Region::Custom { region: region_name, endpoint }, true),
+        Err(_) => (region_name.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{e}")))?, false),
+    };
+    let credentials = Credentials::default().map_err(|e| io::Error::new(io::ErrorKind::PermissionDenied, format!("{e}")))?;
+    let bucket = Bucket::new(bucket_name, region, credentials).map_err(|e| s3_error(bucket_name, "", e))?;
+    Ok(if path_style { bucket.with_path_style() } else { bucket })
+}
+
+fn s3_error(bucket_name: &str, key: &str, e: s3::error::S3Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("s3://{bucket_name}/{key}: {e}"))
+}
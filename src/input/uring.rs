@@ -0,0 +1,217 @@
+// - STD
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+// - external
+use io_uring::{opcode, types, IoUring};
+
+// the readahead batch has to fit into the submission queue.
+const URING_ENTRIES: u32 = 8;
+// the size of a single readahead request.
+const URING_READAHEAD_BLOCK_SIZE: usize = 128*1024; // 128 KiB
+// the number of readahead requests, which are submitted at once (if the segment is read sequentially).
+const URING_READAHEAD_BLOCKS: usize = 4;
+
+// a read request, which was submitted to the kernel. The kernel writes into the buffer until the completion was reaped,
+// so the buffer is owned by the reader (and not borrowed from the caller) and must not be freed before.
+struct InFlightRead {
+    offset: u64,
+    buffer: Vec<u8>,
+    filled: usize,
+}
+
+/// A segment file, which is read by positional reads submitted through an io_uring.
+/// Sequential reads are served by a readahead, which submits the reads of several blocks at once.
+pub(crate) struct UringReader {
+    file: File,
+    ring: IoUring,
+    length: u64,
+    position: u64,
+    // the submitted reads, keyed by their user_data.
+    in_flight: HashMap<u64, InFlightRead>,
+    next_user_data: u64,
+    // the completed readahead blocks (offset, data).
+    readahead: VecDeque<(u64, Vec<u8>)>,
+    // the end of the last read, to detect sequential reads.
+    last_end: Option<u64>,
+}
+
+impl UringReader {
+    /// Opens the given file. Fails, if the kernel does not support io_uring (or it is disabled).
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let ring = IoUring::new(URING_ENTRIES)?;
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+        Ok(Self {
+            file,
+            ring,
+            length,
+            position: 0,
+            in_flight: HashMap::new(),
+            next_user_data: 0,
+            readahead: VecDeque::new(),
+            last_end: None,
+        })
+    }
+
+    /// Reads the given ranges (offset, length) with a single submission and returns the data of each range in the given order.
+    /// The data of a range is shorter than requested, if the range exceeds the end of the file.
+    pub(crate) fn read_batch(&mut self, ranges: &[(u64, usize)]) -> io::Result<Vec<Vec<u8>>> {
+        // the reads of an earlier, failed call have to be completed before their buffers could be dropped.
+        if !self.in_flight.is_empty() {
+            self.wait_for_in_flight()?;
+        }
+        let mut user_data = Vec::with_capacity(ranges.len());
+        for &(offset, length) in ranges {
+            let read = InFlightRead { offset, buffer: vec![0; length], filled: 0 };
+            let id = self.next_user_data;
+            self.next_user_data = self.next_user_data.wrapping_add(1);
+            self.push_read(id, read)?;
+            user_data.push(id);
+        }
+        let mut completed = self.wait_for_in_flight()?;
+        Ok(user_data.iter().filter_map(|id| completed.remove(id)).collect())
+    }
+
+    // pushes the (remaining part of the) given read to the submission queue. The read is only kept in flight, if it was pushed.
+    fn push_read(&mut self, user_data: u64, read: InFlightRead) -> io::Result<()> {
+        let length = u32::try_from(read.buffer.len() - read.filled).unwrap_or(u32::MAX);
+        let read = self.in_flight.entry(user_data).or_insert(read);
+        // the heap buffer of the vector does not move, if the map is resized.
+        let entry = opcode::Read::new(types::Fd(self.file.as_raw_fd()), read.buffer[read.filled..].as_mut_ptr(), length)
+            .offset(read.offset + read.filled as u64)
+            .build()
+            .user_data(user_data);
+        // Safety: the buffer is owned by self.in_flight until the completion of this user_data was reaped.
+        let pushed = unsafe { self.ring.submission().push(&entry).is_ok() };
+        if !pushed {
+            self.in_flight.remove(&user_data);
+            return Err(io::Error::new(io::ErrorKind::Other, "the io_uring submission queue is full"));
+        }
+        Ok(())
+    }
+
+    // submits the pushed reads and waits until all reads in flight are completed. Short reads are resubmitted for the remaining part.
+    // Returns early only, if the ring itself fails. In this case, the reads stay in flight (and their buffers stay valid).
+    fn wait_for_in_flight(&mut self) -> io::Result<HashMap<u64, Vec<u8>>> {
+        let mut completed = HashMap::new();
+        let mut error = None;
+        while !self.in_flight.is_empty() {
+            if let Err(e) = self.ring.submit_and_wait(1) {
+                // the completion queue is reaped anyway below (which resolves EBUSY), then the wait is retried.
+                if !matches!(e.raw_os_error(), Some(libc::EINTR) | Some(libc::EAGAIN) | Some(libc::EBUSY)) {
+                    return Err(e);
+                }
+            }
+            let completions: Vec<(u64, i32)> = self.ring.completion().map(|completion| (completion.user_data(), completion.result())).collect();
+            for (user_data, result) in completions {
+                let mut read = match self.in_flight.remove(&user_data) {
+                    Some(read) => read,
+                    None => continue,
+                };
+                if result < 0 {
+                    error.get_or_insert_with(|| io::Error::from_raw_os_error(-result));
+                    continue;
+                }
+                read.filled += result as usize;
+                if result == 0 || read.filled == read.buffer.len() {
+                    // a read of 0 bytes marks the end of the file.
+                    read.buffer.truncate(read.filled);
+                    completed.insert(user_data, read.buffer);
+                } else if error.is_none() {
+                    self.push_read(user_data, read)?;
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(completed),
+        }
+    }
+
+    // copies the data at the current position from the readahead blocks, if available.
+    fn read_from_readahead(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let position = self.position;
+        while let Some((offset, data)) = self.readahead.front() {
+            if position < *offset {
+                self.readahead.clear();
+                return None;
+            }
+            let start = (position - offset) as usize;
+            if start >= data.len() {
+                self.readahead.pop_front();
+                continue;
+            }
+            let length = buf.len().min(data.len() - start);
+            buf[..length].copy_from_slice(&data[start..start + length]);
+            return Some(length);
+        }
+        None
+    }
+}
+
+impl Read for UringReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.length || buf.is_empty() {
+            return Ok(0);
+        }
+        let read = match self.read_from_readahead(buf) {
+            Some(read) => read,
+            None if self.last_end == Some(self.position) => {
+                let mut ranges = Vec::with_capacity(URING_READAHEAD_BLOCKS);
+                let mut offset = self.position;
+                while ranges.len() < URING_READAHEAD_BLOCKS && offset < self.length {
+                    let length = (self.length - offset).min(URING_READAHEAD_BLOCK_SIZE as u64) as usize;
+                    ranges.push((offset, length));
+                    offset += length as u64;
+                }
+                let blocks = self.read_batch(&ranges)?;
+                self.readahead = ranges.iter().map(|(offset, _)| *offset).zip(blocks).collect();
+                self.read_from_readahead(buf).unwrap_or(0)
+            },
+            None => {
+                let length = (self.length - self.position).min(buf.len() as u64) as usize;
+                let data = self.read_batch(&[(self.position, length)])?.pop().unwrap_or_default();
+                buf[..data.len()].copy_from_slice(&data);
+                data.len()
+            },
+        };
+        self.position += read as u64;
+        self.last_end = Some(self.position);
+        Ok(read)
+    }
+}
+
+impl Seek for UringReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+impl Drop for UringReader {
+    fn drop(&mut self) {
+        if self.in_flight.is_empty() {
+            return;
+        }
+        // the kernel could still write into the buffers of the reads in flight, so they are leaked, if they could not be completed.
+        if self.wait_for_in_flight().is_err() {
+            for (_, read) in self.in_flight.drain() {
+                std::mem::forget(read.buffer);
+            }
+        }
+    }
+}
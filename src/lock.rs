@@ -0,0 +1,81 @@
+// - STD
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+
+// - external
+use log::debug;
+use zff::{header::SegmentHeader, HeaderCoding};
+
+const LOCK_DIRECTORY_NAME: &str = "zffmount";
+const XDG_RUNTIME_DIR: &str = "XDG_RUNTIME_DIR";
+
+/// The result of a failed lock attempt.
+pub(crate) enum LockError {
+    /// The lock is already held by another zffmount process (or another container of this process).
+    /// Contains the PID and the mount point, as recorded in the lock file.
+    AlreadyLocked(String, String),
+    Io(io::Error),
+}
+
+impl From<io::Error> for LockError {
+    fn from(e: io::Error) -> Self {
+        LockError::Io(e)
+    }
+}
+
+/// An advisory lock (flock) of a zff container, identified by the unique identifier of the container.
+/// The lock is released by the kernel as soon as the file descriptor is closed, which also happens on
+/// every exit path of the process (including exit() and signal-driven unmounts).
+pub(crate) struct ContainerLock {
+    _file: File,
+}
+
+impl ContainerLock {
+    /// Tries to acquire the lock for the container with the given unique identifier and records the PID
+    /// of this process and the given mount point in the lock file.
+    pub(crate) fn acquire(unique_identifier: u64, mount_point: &str) -> Result<Self, LockError> {
+        let lock_dir = lock_directory();
+        fs::create_dir_all(&lock_dir)?;
+        let lock_path = lock_dir.join(format!("{unique_identifier:016x}.lock"));
+        debug!("Using lock file {}", lock_path.display());
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&lock_path)?;
+
+        // Safety: the file descriptor is valid as long as the file is open.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(libc::EWOULDBLOCK) {
+                return Err(LockError::Io(e));
+            }
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            let mut lines = content.lines();
+            let pid = lines.next().unwrap_or_default().to_string();
+            let mount_point = lines.next().unwrap_or_default().to_string();
+            return Err(LockError::AlreadyLocked(pid, mount_point));
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        writeln!(file, "{mount_point}")?;
+        file.flush()?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Reads the unique identifier of the container from the segment header of the given segment.
+pub(crate) fn container_unique_identifier<R: Read>(segment: &mut R) -> zff::Result<u64> {
+    Ok(SegmentHeader::decode_directly(segment)?.unique_identifier)
+}
+
+// the lock files are placed in $XDG_RUNTIME_DIR/zffmount, or in the temp directory if XDG_RUNTIME_DIR is not set.
+fn lock_directory() -> PathBuf {
+    let base = match env::var_os(XDG_RUNTIME_DIR) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => env::temp_dir(),
+    };
+    base.join(LOCK_DIRECTORY_NAME)
+}
@@ -0,0 +1,288 @@
+// - STD
+use std::io::{self, Write};
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::Mutex;
+
+// - internal
+use crate::constants::*;
+
+// - external
+use log::{Log, Level, LevelFilter, Metadata, Record, kv::{self, Key, Value, VisitSource}};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use syslog::{Facility, Formatter3164, LoggerBackend};
+use systemd_journal_logger::JournalLog;
+
+/// The backend, which receives the log records.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) enum LogTarget {
+    /// Writes the log to stderr (or the given log file).
+    #[default]
+    Stderr,
+    /// Sends the log to the local syslog daemon.
+    Syslog,
+    /// Sends the log to the systemd journal, the structured context (e.g. the object number) is stored in separate fields.
+    Journald,
+}
+
+/// The format of the log records.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) enum LogFormat {
+    /// The human-readable format of env_logger.
+    #[default]
+    Text,
+    /// One JSON object per log record, which contains the timestamp, level, message and the structured context
+    /// (e.g. object, inode, operation).
+    Json,
+}
+
+/// The configuration of the logger.
+#[derive(Debug, Clone)]
+pub(crate) struct LoggerConfig {
+    pub level: LevelFilter,
+    /// logs the records of all modules (e.g. zff), instead of the zffmount records only.
+    pub all_modules: bool,
+    pub target: LogTarget,
+    pub format: LogFormat,
+    pub log_file: Option<PathBuf>,
+    /// suppresses the log on stderr (e.g. as stderr is used by the progress events). A log file is still written.
+    pub quiet_stderr: bool,
+}
+
+/// Initializes the global logger by using the appropriate backend.
+/// Errors are printed directly to stderr, as the logger is not usable in that case.
+pub(crate) fn setup_logger(config: LoggerConfig) {
+    if config.log_file.is_some() && config.target != LogTarget::Stderr {
+        eprintln!("A log file can only be used with the stderr log target.");
+        exit(EXIT_STATUS_ERROR);
+    }
+    if config.format == LogFormat::Json && config.target != LogTarget::Stderr {
+        eprintln!("The json log format can only be used with the stderr log target.");
+        exit(EXIT_STATUS_ERROR);
+    }
+    let logger: Box<dyn Log> = match config.target {
+        LogTarget::Stderr => {
+            // env_logger handles the filtering itself.
+            env_logger(&config).init();
+            return;
+        },
+        LogTarget::Syslog => match SyslogLogger::new() {
+            Ok(logger) => Box::new(logger),
+            Err(e) => {
+                eprintln!("Could not connect to syslog: {e}");
+                exit(EXIT_STATUS_ERROR);
+            }
+        },
+        LogTarget::Journald => match JournalLog::new() {
+            Ok(logger) => Box::new(logger.with_syslog_identifier(String::from(env!("CARGO_PKG_NAME")))),
+            Err(e) => {
+                eprintln!("Could not connect to the systemd journal: {e}");
+                exit(EXIT_STATUS_ERROR);
+            }
+        },
+    };
+    let logger = FilteredLogger {
+        inner: logger,
+        level: config.level,
+        all_modules: config.all_modules,
+    };
+    if let Err(e) = log::set_boxed_logger(Box::new(logger)) {
+        eprintln!("Could not initialize the logger: {e}");
+        exit(EXIT_STATUS_ERROR);
+    }
+    log::set_max_level(config.level);
+}
+
+fn env_logger(config: &LoggerConfig) -> env_logger::Builder {
+    let mut builder = env_logger::builder();
+    builder.format_timestamp_nanos();
+    // without a log file, the whole log would be written to stderr.
+    let level = if config.quiet_stderr && config.log_file.is_none() { LevelFilter::Off } else { config.level };
+    if config.all_modules {
+        builder.filter_level(level);
+    } else {
+        builder.filter_module(env!("CARGO_PKG_NAME"), level);
+    };
+    if config.format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_record(record)));
+    }
+
+    if let Some(log_file_path) = &config.log_file {
+        let log_file = match OpenOptions::new().create(true).append(true).open(log_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("Could not open log file {}: {e}", log_file_path.display());
+                exit(EXIT_STATUS_ERROR);
+            }
+        };
+        // colored output would write the escape sequences to the log file.
+        builder.write_style(env_logger::WriteStyle::Never);
+        builder.target(env_logger::Target::Pipe(Box::new(LogFileWriter::new(log_file, stderr_is_tty() && !config.quiet_stderr))));
+    }
+    builder
+}
+
+// filters the records by level and module for the backends, which have no own filter.
+struct FilteredLogger {
+    inner: Box<dyn Log>,
+    level: LevelFilter,
+    all_modules: bool,
+}
+
+impl Log for FilteredLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level 
+        && (self.all_modules || metadata.target().starts_with(env!("CARGO_PKG_NAME")))
+        && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+// sends the records to the local syslog daemon. The structured context is appended to the message, as
+// RFC 3164 has no structured data.
+struct SyslogLogger {
+    logger: Mutex<syslog::Logger<LoggerBackend, Formatter3164>>,
+}
+
+impl SyslogLogger {
+    fn new() -> syslog::Result<Self> {
+        let formatter = Formatter3164 {
+            facility: Facility::LOG_DAEMON,
+            hostname: None,
+            process: String::from(env!("CARGO_PKG_NAME")),
+            pid: std::process::id(),
+        };
+        Ok(Self {
+            logger: Mutex::new(syslog::unix(formatter)?),
+        })
+    }
+}
+
+impl Log for SyslogLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let mut message = record.args().to_string();
+        for (key, value) in key_values(record) {
+            message.push_str(&format!(" {key}={value}"));
+        }
+        let mut logger = match self.logger.lock() {
+            Ok(logger) => logger,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // errors can not be logged here.
+        let _ = match record.level() {
+            Level::Error => logger.err(message),
+            Level::Warn => logger.warning(message),
+            Level::Info => logger.info(message),
+            Level::Debug | Level::Trace => logger.debug(message),
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+/// Returns the structured context (key-value pairs) of the given record.
+pub(crate) fn key_values(record: &Record) -> Vec<(String, String)> {
+    let mut collector = KeyValueCollector(Vec::new());
+    // the collector never returns an error.
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+// returns the given record as single line JSON object.
+fn json_record(record: &Record) -> JsonValue {
+    let now = OffsetDateTime::now_utc();
+    let mut fields = JsonMap::new();
+    fields.insert(String::from("timestamp"), JsonValue::from(now.format(&Rfc3339).unwrap_or_else(|_| now.to_string())));
+    fields.insert(String::from("level"), JsonValue::from(record.level().as_str()));
+    fields.insert(String::from("target"), JsonValue::from(record.target()));
+    fields.insert(String::from("message"), JsonValue::from(record.args().to_string()));
+    let mut collector = JsonCollector(JsonMap::new());
+    // the collector never returns an error.
+    let _ = record.key_values().visit(&mut collector);
+    if !collector.0.is_empty() {
+        fields.insert(String::from("context"), JsonValue::Object(collector.0));
+    }
+    JsonValue::Object(fields)
+}
+
+struct JsonCollector(JsonMap<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        // numbers (e.g. object numbers or inodes) should stay numbers in the JSON output.
+        let value = if let Some(number) = value.to_u64() {
+            JsonValue::from(number)
+        } else if let Some(number) = value.to_i64() {
+            JsonValue::from(number)
+        } else if let Some(boolean) = value.to_bool() {
+            JsonValue::from(boolean)
+        } else {
+            JsonValue::from(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+struct KeyValueCollector(Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for KeyValueCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+/// A log target which writes all log records to the given log file and (optionally) to stderr.
+pub(crate) struct LogFileWriter {
+    file: File,
+    tee_stderr: bool,
+}
+
+impl LogFileWriter {
+    pub(crate) fn new(file: File, tee_stderr: bool) -> Self {
+        Self {
+            file,
+            tee_stderr,
+        }
+    }
+}
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write_all(buf)?;
+        if self.tee_stderr {
+            // errors while writing to stderr should not prevent logging to the file.
+            let _ = io::stderr().write_all(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.tee_stderr {
+            let _ = io::stderr().flush();
+        }
+        self.file.flush()
+    }
+}
+
+/// Returns true, if stderr is connected to a terminal.
+pub(crate) fn stderr_is_tty() -> bool {
+    // Safety: isatty only checks the given file descriptor.
+    unsafe { libc::isatty(libc::STDERR_FILENO) == 1 }
+}
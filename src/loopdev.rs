@@ -0,0 +1,128 @@
+// - STD
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+// - external
+use log::debug;
+
+// ioctls and flags of linux/loop.h
+const LOOP_SET_FD: u64 = 0x4C00;
+const LOOP_CLR_FD: u64 = 0x4C01;
+const LOOP_SET_STATUS64: u64 = 0x4C04;
+const LOOP_CTL_GET_FREE: u64 = 0x4C82;
+const LO_FLAGS_AUTOCLEAR: u32 = 4;
+const LO_FLAGS_PARTSCAN: u32 = 8;
+const LO_NAME_SIZE: usize = 64;
+const LO_KEY_SIZE: usize = 32;
+
+const LOOP_CONTROL_PATH: &str = "/dev/loop-control";
+// the number of retries, if another process grabs the free loop device first.
+const LOOP_ATTACH_RETRIES: usize = 5;
+
+#[repr(C)]
+struct LoopInfo64 {
+    lo_device: u64,
+    lo_inode: u64,
+    lo_rdevice: u64,
+    lo_offset: u64,
+    lo_sizelimit: u64,
+    lo_number: u32,
+    lo_encrypt_type: u32,
+    lo_encrypt_key_size: u32,
+    lo_flags: u32,
+    lo_file_name: [u8; LO_NAME_SIZE],
+    lo_crypt_name: [u8; LO_NAME_SIZE],
+    lo_encrypt_key: [u8; LO_KEY_SIZE],
+    lo_init: [u64; 2],
+}
+
+/// A read-only loop device, which is backed by a file of the mounted filesystem.
+pub(crate) struct LoopDevice {
+    path: PathBuf,
+    device: File,
+}
+
+impl LoopDevice {
+    /// Attaches the given file to the next free loop device. The loop device is read-only, as the backing file
+    /// is opened read-only. If partscan is true, the kernel scans the partition table of the backing file
+    /// (and creates e.g. /dev/loop0p1).
+    pub(crate) fn attach<P: AsRef<Path>>(backing_file: P, partscan: bool) -> io::Result<Self> {
+        let backing_file_path = backing_file.as_ref();
+        let backing_file = File::open(backing_file_path)?;
+        let loop_control = OpenOptions::new().read(true).write(true).open(LOOP_CONTROL_PATH)?;
+
+        let mut last_error = io::Error::from_raw_os_error(libc::EBUSY);
+        for _ in 0..LOOP_ATTACH_RETRIES {
+            // Safety: LOOP_CTL_GET_FREE has no argument.
+            let number = unsafe { libc::ioctl(loop_control.as_raw_fd(), LOOP_CTL_GET_FREE as _) };
+            if number < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let path = PathBuf::from(format!("/dev/loop{number}"));
+            let device = File::open(&path)?;
+            // Safety: LOOP_SET_FD expects the file descriptor of the backing file.
+            if unsafe { libc::ioctl(device.as_raw_fd(), LOOP_SET_FD as _, backing_file.as_raw_fd()) } < 0 {
+                last_error = io::Error::last_os_error();
+                // the loop device was grabbed by another process in the meantime.
+                if last_error.raw_os_error() == Some(libc::EBUSY) {
+                    debug!("{} is already in use, trying the next free loop device.", path.display());
+                    continue;
+                }
+                return Err(last_error);
+            }
+            let loop_device = Self { path, device };
+            loop_device.set_status(backing_file_path, partscan)?;
+            return Ok(loop_device);
+        }
+        Err(last_error)
+    }
+
+    // sets the flags and the filename of the backing file. The loop device will be detached, if this fails.
+    fn set_status(&self, backing_file_path: &Path, partscan: bool) -> io::Result<()> {
+        let mut info = LoopInfo64 {
+            lo_device: 0,
+            lo_inode: 0,
+            lo_rdevice: 0,
+            lo_offset: 0,
+            lo_sizelimit: 0,
+            lo_number: 0,
+            lo_encrypt_type: 0,
+            lo_encrypt_key_size: 0,
+            // autoclear releases the loop device if zffmount exits without detaching it.
+            lo_flags: if partscan { LO_FLAGS_AUTOCLEAR | LO_FLAGS_PARTSCAN } else { LO_FLAGS_AUTOCLEAR },
+            lo_file_name: [0; LO_NAME_SIZE],
+            lo_crypt_name: [0; LO_NAME_SIZE],
+            lo_encrypt_key: [0; LO_KEY_SIZE],
+            lo_init: [0; 2],
+        };
+        let backing_file_name = backing_file_path.to_string_lossy();
+        let backing_file_name = backing_file_name.as_bytes();
+        let len = backing_file_name.len().min(LO_NAME_SIZE - 1);
+        info.lo_file_name[..len].copy_from_slice(&backing_file_name[..len]);
+
+        // Safety: LOOP_SET_STATUS64 expects a pointer to a loop_info64 struct.
+        if unsafe { libc::ioctl(self.device.as_raw_fd(), LOOP_SET_STATUS64 as _, &info as *const LoopInfo64) } < 0 {
+            let e = io::Error::last_os_error();
+            // Safety: LOOP_CLR_FD has no argument.
+            unsafe { libc::ioctl(self.device.as_raw_fd(), LOOP_CLR_FD as _) };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the loop device (e.g. /dev/loop0).
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Detaches the backing file from the loop device.
+    pub(crate) fn detach(self) -> io::Result<()> {
+        // Safety: LOOP_CLR_FD has no argument.
+        if unsafe { libc::ioctl(self.device.as_raw_fd(), LOOP_CLR_FD as _) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
@@ -1,52 +1,101 @@
 // - STD
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::process::exit;
-use std::path::PathBuf;
-use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 // - modules
 mod fs;
 mod constants;
 mod addons;
+mod logging;
+mod loopdev;
+mod umount;
+mod lock;
+mod pidfile;
+mod privileges;
+mod input;
+mod progress;
+mod error;
+mod panic_hook;
+mod fuse_check;
+mod timestamps;
 
 // - internal
 use fs::*;
 use constants::*;
+use error::ZffMountError;
 use addons::*;
+use input::{InputOptions, InputReader, SourceRegistry};
 
 // - external
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use nix::unistd::sleep;
-use signal_hook::{consts::{SIGINT, SIGHUP, SIGTERM}, iterator::Signals};
+use signal_hook::{consts::{SIGINT, SIGHUP, SIGTERM}, iterator::Signals, flag, low_level, SigId};
 use log::{LevelFilter, info, error, warn, debug};
-use fuser::MountOption;
-
-
-
-
+use fuser::{MountOption, Filesystem, BackgroundSession};
+use zff::hashing::HashType;
 
 #[derive(Parser, Clone)]
-#[clap(about, version, author)]
+#[clap(about, version, author, subcommand_negates_reqs = true)]
 pub struct Cli {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+
     /// The input files. This should be your zff image files. You can use this option multiple times.
+    /// The segments could also be given as HTTP(S) URLs, if the server supports range requests. The credentials could be set by
+    /// the environment variables ZFFMOUNT_HTTP_BEARER_TOKEN or ZFFMOUNT_HTTP_USER and ZFFMOUNT_HTTP_PASSWORD.
     #[clap(short='i', long="inputfiles", global=true, required=false, value_delimiter = ' ', num_args = 1..)]
     inputfiles: Vec<PathBuf>,
 
+    /// Mounts an additional container as top-level directory with the given name, e.g. source=/images/source.z??
+    /// (the input files are given as glob pattern). You can use this option multiple times to serve multiple containers by one mount point.
+    /// The passwords of the objects of this container could be given by using -p <name>/<object number>:<password>.
+    #[clap(long="container", value_parser = parse_container, conflicts_with="inputfiles")]
+    containers: Vec<(String, String)>,
+
     /// The output format.
-    #[clap(short='m', long="mount-point")]
-    mount_point: PathBuf,
+    #[clap(short='m', long="mount-point", required_unless_present="benchmark")]
+    mount_point: Option<PathBuf>,
 
     /// The password(s), if the file(s) are encrypted. You can use this option multiple times to enter different passwords for different objects.
-    #[clap(short='p', long="decryption-passwords", value_parser = parse_key_val::<String, String>)]
+    #[clap(short='p', long="decryption-passwords", global=true, value_parser = parse_key_val::<String, String>)]
     decryption_passwords: Vec<(String, String)>,
 
     /// The Loglevel
     #[clap(short='l', long="log-level", value_enum, default_value="info")]
     log_level: LogLevel,
 
+    /// Suppresses all non-error output (overrides the log level). On success, a single line "MOUNTED <mount-point>"
+    /// is printed to stdout, so wrapper scripts know when the filesystem is usable.
+    #[clap(short='q', long="quiet")]
+    quiet: bool,
+
+    /// Writes the log to the given file (append mode) instead of stderr.
+    /// If stderr is a terminal, the log will be additionally written to stderr.
+    #[clap(long="log-file")]
+    log_file: Option<PathBuf>,
+
+    /// The log backend. Syslog and journald are useful if zffmount runs as a service.
+    /// Journald stores the structured context (e.g. the object number) in separate fields.
+    #[clap(long="log-target", value_enum, default_value="stderr")]
+    log_target: LogTarget,
+
+    /// Emits machine-readable progress events during the initialization and the preloading (e.g. for a GUI frontend).
+    /// Json emits one JSON object per line on stderr, e.g. {"phase":"preload_chunk_size_map","done":0,"total":98765432},
+    /// and {"phase":"mounted"} as last event. The log is not written to stderr in this mode (use --log-file instead).
+    #[clap(long="progress", value_enum, default_value="none")]
+    progress: ProgressFormat,
+
+    /// The format of the log records. Json emits one JSON object per record (e.g. for log ingestion),
+    /// containing the timestamp, level, message and the structured context (e.g. object, inode, operation).
+    #[clap(long="log-format", value_enum, default_value="text")]
+    log_format: LogFormat,
+
     /// None: saves memory but the read operations are slower (default)  
     #[clap(short='M', long="preload-mode", value_enum, default_value="none", 
     required_if_eq_any=[("preload_chunk_offset_map", "true"), ("preload_chunk_size_map", "true"), 
@@ -83,6 +132,420 @@ pub struct Cli {
 
     #[clap(short='r', long="redb-path", required_if_eq("preload_mode", "redb"))]
     redb_path: Option<PathBuf>,
+
+    /// Verifies the integrity of all objects while mounting, instead of the first time the appropriate verification.txt is read.
+    /// This could take a long time on large objects.
+    #[clap(long="verify-at-mount")]
+    verify_at_mount: bool,
+
+    /// Reports the logical size of the files as allocated blocks (e.g. for du), instead of omitting the zero chunks.
+    /// By default, zero chunks are omitted if the flags and the samebytes maps are preloaded.
+    #[clap(long="logical-blocks")]
+    logical_blocks: bool,
+
+    /// Bypasses the kernel page cache for all files. This is useful for single sequential reads of large images
+    /// (e.g. hashing), as the page cache would only be filled with data which will never be read again.
+    /// Repeated reads of the same data will be slower, as each read has to be served by zffmount.
+    #[clap(long="direct-io", conflicts_with="keep_page_cache")]
+    direct_io: bool,
+
+    /// Keeps the kernel page cache of a file when the file is opened again. This is useful if the same files
+    /// are read repeatedly (e.g. grep over logical files), as the data of a zff container can never change.
+    /// Needs more memory for the page cache.
+    #[clap(long="keep-page-cache")]
+    keep_page_cache: bool,
+
+    /// Lets the kernel cache the entries, the attributes, the directory listings, the symlink targets and the page cache
+    /// of the files for the whole mount time, as a mounted container never changes. This speeds up repeated tree walks
+    /// (e.g. a recursive grep), which otherwise look up every path again after one second. Implies --keep-page-cache.
+    #[clap(long="cache-forever", conflicts_with="direct_io")]
+    cache_forever: bool,
+
+    /// Matches filenames case-insensitively at lookup (e.g. for images of Windows systems).
+    /// The directory listings still contain the original filenames.
+    #[clap(long="case-insensitive")]
+    case_insensitive: bool,
+
+    /// Normalizes the filenames (unicode normalization form) before comparing them at lookup.
+    /// E.g. use nfc for images of macOS systems (which store the filenames in NFD) to lookup files by using names in NFC.
+    /// The directory listings still contain the original filenames.
+    #[clap(long="normalize-lookup", value_enum, default_value="none")]
+    normalize_lookup: NormalizeLookup,
+
+    /// Presents filenames containing control characters (e.g. newlines or tabs) with escaped representations (e.g. file\x0aname).
+    /// The original filename is available via the extended attribute user.zff.original_name.
+    #[clap(long="sanitize-names")]
+    sanitize_names: bool,
+
+    /// Measures the latency of the filesystem operations (read, readdir, lookup, getattr, readlink) and logs the
+    /// percentiles (p50/p95/p99) per operation when the filesystem is unmounted.
+    /// The duration of each single operation is logged at trace level, regardless of this option.
+    #[clap(long="latency-stats")]
+    latency_stats: bool,
+
+    /// The maximum size of the cache of decompressed chunks per container (e.g. 256M), which serves repeated reads of the same
+    /// data (e.g. the partition table area) without decompressing the chunks again. 0 disables the cache.
+    /// The hit/miss statistics are logged when the filesystem is unmounted.
+    #[clap(long="cache-size", value_parser = parse_size, default_value="64M")]
+    cache_size: u64,
+
+    /// The filename of the physical object file (default: zff_image.dd). The placeholders {object}, {case} and {evidence}
+    /// will be replaced by the object number and the case/evidence number of the description header, e.g. {case}_{object}.raw
+    #[clap(long="image-name")]
+    image_name: Option<String>,
+
+    /// Initializes only the given object and presents its content (the image of a physical object or the file tree of a logical object)
+    /// directly in the root directory of the mount point, instead of the object_N directory.
+    #[clap(long="object", global=true)]
+    object: Option<u64>,
+
+    /// The prefix of the object directories (e.g. EVD- presents the objects as EVD-1, EVD-2, ...).
+    #[clap(long="object-dir-prefix", global=true, default_value=OBJECT_PATH_PREFIX, value_parser = parse_object_dir_prefix)]
+    object_dir_prefix: String,
+
+    /// Presents the content of the object directly in the root directory of the mount point, if the container contains exactly
+    /// one decrypted object (e.g. to use <mount-point>/zff_image.dd independent of the object number). Ignored for multiple objects.
+    #[clap(long="flatten-single", conflicts_with="object")]
+    flatten_single: bool,
+
+    /// Presents missing file timestamps as 1970-01-01 00:00:00 (UNIX epoch). By default, missing timestamps are replaced
+    /// by the acquisition times of the object (acquisition end for atime, mtime and ctime, acquisition start for the birth time).
+    #[clap(long="epoch-for-missing-times")]
+    epoch_for_missing_times: bool,
+
+    /// Maps the uids and gids of the acquired system to local users and groups by using the given mapping file.
+    /// Each line maps a source id to a local user or group (name or number), e.g. `uid 1000:alice`, `gid 1000:analysts`
+    /// or `uid *:nobody` as default. Unmapped ids pass through numerically. The original ids are available as extended attributes.
+    #[clap(long="map-owners")]
+    map_owners: Option<PathBuf>,
+
+    /// Rewrites absolute symlink targets (e.g. /etc/alternatives/java) to relative targets, which stay inside the object.
+    /// Targets which were not acquired are left absolute. The original target is available as extended attribute.
+    #[clap(long="rebase-symlinks")]
+    rebase_symlinks: bool,
+
+    /// The maximum readahead of the kernel, e.g. 1M (default: four times the largest chunk size of the container).
+    /// The kernel could limit the value.
+    #[clap(long="max-readahead", value_parser = parse_size_u32)]
+    max_readahead: Option<u32>,
+
+    /// The maximum size of a single read request of the kernel, e.g. 1M. Larger requests could cover multiple zff chunks.
+    /// The kernel could limit the value (e.g. by the maximum number of pages per request).
+    #[clap(long="max-read", value_parser = parse_size_u32)]
+    max_read: Option<u32>,
+
+    /// The maximum number of pending background requests (e.g. readahead) of the kernel.
+    #[clap(long="max-background")]
+    max_background: Option<u16>,
+
+    /// The number of pending background requests, at which the kernel considers the filesystem as congested.
+    /// Should be lower than --max-background.
+    #[clap(long="congestion-threshold")]
+    congestion_threshold: Option<u16>,
+
+    /// The size of a single range request, if the input files are given as HTTP(S) URLs (e.g. 4M).
+    /// Some of the most recently fetched blocks of each segment will be cached.
+    #[clap(long="http-block-size", value_parser = parse_size, default_value="1M")]
+    http_block_size: u64,
+
+    /// Reads input files, which are block devices (e.g. /dev/sdb), with O_DIRECT, so the reads do not compete with the page cache.
+    #[clap(long="direct-input")]
+    direct_input: bool,
+
+    /// Maps the input files into memory instead of reading them, which avoids a syscall per read on fast local storage.
+    /// Use --benchmark with and without this option to compare both read paths. The input files must not be modified or
+    /// truncated while they are mounted: reads of a truncated file fail, but a truncation during a read terminates zffmount (SIGBUS).
+    #[clap(long="input-mmap", conflicts_with="direct_input")]
+    input_mmap: bool,
+
+    /// Reads the input files through an io_uring (experimental). Falls back to the standard read path, if the kernel does not
+    /// support io_uring. Use --benchmark --benchmark-input to compare both read paths.
+    #[cfg(feature = "uring")]
+    #[clap(long="input-uring", conflicts_with_all=["direct_input", "input_mmap"])]
+    input_uring: bool,
+
+    /// The size of a single range request, if the input files are given as S3 URLs (e.g. s3://bucket/case/image.z01, or
+    /// s3://bucket/case/ to use all segments with the prefix). The credentials are read from the standard AWS environment
+    /// variables and profiles; AWS_ENDPOINT_URL could be used for S3-compatible servers.
+    #[cfg(feature = "s3")]
+    #[clap(long="s3-request-size", value_parser = parse_size, default_value="8M")]
+    s3_request_size: u64,
+
+    /// Fetches the following range of a S3 object in the background, which speeds up sequential reads.
+    #[cfg(feature = "s3")]
+    #[clap(long="s3-prefetch")]
+    s3_prefetch: bool,
+
+    /// Allows to execute files directly from the mounted filesystem. By default, the filesystem is mounted with noexec.
+    #[clap(long="exec")]
+    exec: bool,
+
+    /// Interprets character and block special files of the mounted filesystem. By default, the filesystem is mounted with nodev.
+    #[clap(long="dev")]
+    dev: bool,
+
+    /// Honors the setuid and setgid bits of the mounted files. By default, the filesystem is mounted with nosuid.
+    #[clap(long="suid")]
+    suid: bool,
+
+    /// Allows other users (including root) to access the mounted filesystem. By default, only the user who mounted the filesystem
+    /// has access. Unprivileged users need user_allow_other in /etc/fuse.conf to use this option.
+    #[clap(long="allow-other")]
+    allow_other: bool,
+
+    /// Restricts the access to the mounted filesystem to the user with the given uid (could be given multiple times).
+    /// The requests of all other users (including root) are denied with EACCES, so neither the content nor the metadata
+    /// is disclosed. Combined with --allow-other, e.g. a service account could mount the container for a single analyst.
+    #[clap(long="restrict-to-uid")]
+    restrict_to_uid: Vec<u32>,
+
+    /// Permits the root user to access the filesystem, if the access is restricted by --restrict-to-uid.
+    #[clap(long="allow-root-access", requires="restrict_to_uid")]
+    allow_root_access: bool,
+
+    /// Presents all files and directories as owned by the user (and group) of the accessing process, so tools which check
+    /// the ownership behave the same for each user. The acquired owners are still available as extended attributes.
+    /// The attributes are not cached by the kernel in this mode (as the cache is shared by all users).
+    #[clap(long="squash-owner", value_enum, default_value="none")]
+    squash_owner: SquashOwner,
+
+    /// Presents the physical objects as split raw images of the given segment size (e.g. zff_image.001, zff_image.002, ...),
+    /// instead of a single zff_image.dd. The size could be given with a binary unit suffix, e.g. 2G.
+    #[clap(long="split-raw", value_parser = parse_size)]
+    split_raw: Option<u64>,
+
+    /// Presents the stored acquisition hash values of each physical object as files next to the image (e.g. zff_image.dd.sha256,
+    /// one file per stored hash algorithm), in the format of the coreutils, so the image could be verified by e.g. sha256sum -c.
+    #[clap(long="hash-sidecars", conflicts_with="split_raw")]
+    hash_sidecars: bool,
+
+    /// The format of the acquisition report (object_N/acquisition_report.txt or acquisition_report.json), which contains
+    /// the description header (e.g. examiner name, evidence number, notes) and the acquisition times of the object,
+    /// and of the map of the ranges of the physical objects, which could not be read while acquiring them (unreadable_ranges.txt).
+    #[clap(long="report-format", value_enum, default_value="text")]
+    report_format: AcquisitionReportFormat,
+
+    /// Places a tar archive of the whole content of each logical object into its directory (e.g. object_1/object_1.tar),
+    /// so a logical object could be copied as a single file. The archive is generated on the fly by each read, but the
+    /// layout is built while mounting, which needs a walk through all directories of the logical objects.
+    #[clap(long="virtual-archives")]
+    virtual_archives: bool,
+
+    /// The format of the mount summary, which is printed after the initialization (the objects, the preloaded chunkmaps
+    /// and the mount options). The table is logged at the info level, the JSON document is printed to stdout.
+    #[clap(long="summary-format", value_enum, default_value="table")]
+    summary_format: SummaryFormat,
+
+    /// The timezone of the timestamps in the metadata output (e.g. the acquisition reports, the verification files and the
+    /// ls subcommand): utc, local or an IANA name (e.g. Europe/Berlin). The timestamps always contain the offset.
+    /// The timestamps of the files and the log records are not affected.
+    #[clap(long="timezone", global=true, default_value="utc", value_parser = timestamps::parse_timezone)]
+    timezone: timestamps::TimeZone,
+
+    /// The format of the timestamps in the metadata output, in the format description syntax of the time crate
+    /// (e.g. "[month]/[day]/[year] [hour repr:12]:[minute] [period] [offset_hour sign:mandatory]:[offset_minute]").
+    /// The default is RFC 3339 (e.g. 2023-05-04T13:37:00+02:00). The offset is only rendered, if it is part of the format.
+    #[clap(long="date-format", global=true, value_parser = timestamps::parse_date_format)]
+    date_format: Option<timestamps::DateFormat>,
+
+    /// Fails the reads of files, which contain data that could not be read while acquiring it, with an I/O error
+    /// (instead of serving the partial data). The affected files are marked by the xattr user.zff.acquisition_error.
+    /// Without a preloaded chunk flags map, the header of every chunk is read while mounting to find these files.
+    #[clap(long="fail-damaged-reads")]
+    fail_damaged_reads: bool,
+
+    /// Hides the encrypted objects, which could not be decrypted. By default, these objects are presented as locked
+    /// object_N.locked directories, which contain a LOCKED.txt with a hint how to pass the password.
+    #[clap(long="hide-encrypted", global=true)]
+    hide_encrypted: bool,
+
+    /// Aborts with the exit status 7, if an encrypted object could not be decrypted (instead of mounting the other objects).
+    #[clap(long="require-all-decrypted", global=true)]
+    require_all_decrypted: bool,
+
+    /// Attaches the zff_image.dd of each physical object to a read-only loop device after mounting (Linux only, needs the appropriate permissions).
+    /// The loop devices will be detached while unmounting.
+    #[clap(long="losetup", conflicts_with="split_raw")]
+    losetup: bool,
+
+    /// Scans the partition table of the loop devices (e.g. to get /dev/loop0p1).
+    #[clap(long="losetup-partscan", requires="losetup")]
+    losetup_partscan: bool,
+
+    /// Measures the sequential and random read throughput of all physical objects by using the configured preload settings,
+    /// instead of mounting the container. This helps to choose the appropriate preload strategy.
+    #[clap(long="benchmark")]
+    benchmark: bool,
+
+    /// Copies the content of the container to the mount point (which is used as destination directory), instead of mounting it.
+    /// This is useful, if FUSE is not available (e.g. in an unprivileged container without /dev/fuse). The directory structure
+    /// and the timestamps are preserved and the password and preload options are used as for a mount.
+    #[clap(long="no-fuse", conflicts_with_all=["benchmark", "losetup"])]
+    no_fuse: bool,
+
+    /// Reads only the given number of bytes of each object sequentially in benchmark mode (default: the whole object).
+    #[clap(long="benchmark-size", requires="benchmark")]
+    benchmark_size: Option<u64>,
+
+    /// The number of random 64 KiB reads per object in benchmark mode.
+    #[clap(long="benchmark-random-reads", requires="benchmark", default_value="100")]
+    benchmark_random_reads: u64,
+
+    /// Drops the privileges to the given user (and group) after the filesystem was mounted (e.g. zffmount:zffmount or 1000:1000).
+    /// If the group is omitted, the primary group of the user is used. The files of the mounted filesystem are owned by this identity.
+    #[clap(long="run-as", value_parser = privileges::parse_identity)]
+    run_as: Option<privileges::Identity>,
+
+    /// Writes the PID of the zffmount process to the given file after the filesystem was mounted. The file is removed while unmounting.
+    /// The umount subcommand uses the PID file to signal the zffmount process, which then unmounts the filesystem gracefully.
+    #[clap(long="pid-file", global=true)]
+    pid_file: Option<PathBuf>,
+
+    /// Unmounts the filesystem automatically, if there was no filesystem operation for the given duration (e.g. 30m, 8h, 2d).
+    /// Open files count as activity, so e.g. a long-running hash job will not be interrupted.
+    #[clap(long="idle-timeout", value_parser = parse_duration)]
+    idle_timeout: Option<Duration>,
+
+    /// Detaches the filesystem lazily (MNT_DETACH), if it could not be unmounted gracefully within the given duration after a shutdown signal
+    /// (e.g. 30 or 2m). Processes which still use the filesystem will get I/O errors afterwards. zffmount exits with 5, if the unmount was forced.
+    #[clap(long="force-unmount-timeout", value_parser = parse_duration)]
+    force_unmount_timeout: Option<Duration>,
+
+    /// Allows to mount the same container multiple times (e.g. at different mount points). By default, zffmount refuses to mount
+    /// a container, which is already mounted by another zffmount process, as both processes would e.g. use the same redb database.
+    #[clap(long="allow-multiple-mounts")]
+    allow_multiple_mounts: bool,
+
+    /// Prints the benchmark results as JSON instead of a table.
+    #[clap(long="benchmark-json", requires="benchmark")]
+    benchmark_json: bool,
+
+    /// Additionally measures random 64 KiB reads of the local input files with the standard and the io_uring read path in benchmark mode.
+    /// The number of reads per file is given by --benchmark-random-reads. Both read paths use the same offsets, so the files should be
+    /// larger than the page cache (or the cache should be dropped) to compare the device reads.
+    #[cfg(feature = "uring")]
+    #[clap(long="benchmark-input", requires="benchmark")]
+    benchmark_input: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Commands {
+    /// Unmounts the zffmount filesystem at the given mount point.
+    /// Exits with 2 if the path is not a zffmount mount point, with 3 if the filesystem is busy and with 4 if the permission was denied.
+    Umount {
+        /// The mount point of the zffmount filesystem.
+        mount_point: PathBuf,
+
+        /// Detaches the filesystem immediately and cleans up all references as soon as it is not busy anymore.
+        #[clap(short='z', long="lazy")]
+        lazy: bool,
+
+        /// Detaches the filesystem lazily, if it is still busy after the graceful unmount. The processes which still use the filesystem will be logged.
+        #[clap(short='f', long="force")]
+        force: bool,
+    },
+    /// Copies a file or directory out of the container to disk, without mounting the container.
+    /// Without --object, the whole container is extracted (with the object_N directories).
+    Extract(ExtractArgs),
+    /// Lists the content of the container, without mounting it (the same tree, which would be presented at the mount point).
+    /// The encrypted objects, which could not be decrypted, are listed as locked entries.
+    Ls(LsArgs),
+}
+
+#[derive(Args, Clone, Debug)]
+struct ExtractArgs {
+    /// The path of the file or directory in the logical object given by --object (e.g. /Users/bob/Documents), which is extracted
+    /// to <out>/<name>. Without a path, the whole object is extracted (the image of a physical object as dd image).
+    #[clap(long="path")]
+    path: Option<PathBuf>,
+
+    /// The destination directory. It will be created, if it does not exist.
+    #[clap(long="out")]
+    out: PathBuf,
+
+    /// Computes the hash values of the extracted files while writing them and writes the manifest <out>.<algorithm>
+    /// (e.g. export.sha256, in the format of sha256sum). You can use this option multiple times.
+    #[clap(long="hash", value_enum)]
+    hash: Vec<HashAlgorithm>,
+
+    /// The handling of the files and directories, which already exist in the destination directory.
+    /// Error: keeps the existing entry and exits with 1 after the extraction. Skip: keeps the existing entry.
+    /// Overwrite: replaces existing files and merges existing directories. Rename: appends a number (e.g. report.pdf.1).
+    #[clap(long="on-conflict", value_enum, default_value="error")]
+    on_conflict: OnConflict,
+
+    /// Creates the images of physical objects as sparse files (the zero chunks are not written).
+    #[clap(long="sparse")]
+    sparse: bool,
+
+    /// Compares the hash values of the extracted files (and images) with the hash values stored in the container and writes
+    /// the reports <out>.verification.txt and <out>.verification.json. Exits with 6, if any hash value does not match.
+    #[clap(long="verify")]
+    verify: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct LsArgs {
+    /// The path of the directory (or file) in the object given by --object (e.g. /Users/bob), which is listed.
+    /// Without a path, the whole object is listed.
+    #[clap(long="path")]
+    path: Option<PathBuf>,
+
+    /// Lists the content of all subdirectories, too (as flat listing with the full paths).
+    #[clap(short='r', long="recursive")]
+    recursive: bool,
+
+    /// The output format of the listing.
+    #[clap(long="format", value_enum, default_value="table")]
+    format: ListFormat,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum ListFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum HashAlgorithm {
+    Blake2b512,
+    Sha256,
+    Sha512,
+    Sha3_256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    fn hash_type(&self) -> HashType {
+        match self {
+            HashAlgorithm::Blake2b512 => HashType::Blake2b512,
+            HashAlgorithm::Sha256 => HashType::SHA256,
+            HashAlgorithm::Sha512 => HashType::SHA512,
+            HashAlgorithm::Sha3_256 => HashType::SHA3_256,
+            HashAlgorithm::Blake3 => HashType::Blake3,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum OnConflict {
+    Error,
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum AcquisitionReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum SummaryFormat {
+    Table,
+    Json,
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -92,6 +555,38 @@ enum PreloadMode {
     Redb,
 }
 
+#[derive(ValueEnum, Clone, Debug)]
+enum NormalizeLookup {
+    None,
+    Nfc,
+    Nfd,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum SquashOwner {
+    None,
+    Caller,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum LogTarget {
+    Stderr,
+    Syslog,
+    Journald,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+enum ProgressFormat {
+    None,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
 #[derive(ValueEnum, Clone, Debug, PartialEq)]
 enum LogLevel {
     Error,
@@ -103,27 +598,280 @@ enum LogLevel {
     Trace
 }
 
-fn open_files(args: &Cli) -> Vec<File> {
-    let input_paths = &args.inputfiles.clone();
+fn open_files(input_paths: &[PathBuf], registry: &SourceRegistry) -> Result<Vec<InputReader>, ZffMountError> {
     let mut inputfiles = Vec::new();
     info!("Opening {} segment files.", input_paths.len());
-    for path in input_paths {
-        let file = match File::open(path) {
-            Ok(file) => file,
-            Err(e) => {
-                error!("{e}");
-                exit(EXIT_STATUS_ERROR);
-            },
-        };
-        inputfiles.push(file);
+    let total = input_paths.len() as u64;
+    progress::report(progress::Phase::OpenSegments, Some(0), Some(total));
+    for (index, path) in input_paths.iter().enumerate() {
+        // a single path could result in multiple segments (e.g. a S3 prefix).
+        let files = registry.open(path).map_err(|e| ZffMountError::io(e.to_string(), e))?;
+        inputfiles.extend(files);
+        progress::report(progress::Phase::OpenSegments, Some(index as u64 + 1), Some(total));
+    }
+    Ok(inputfiles)
+}
+
+// checks, if the given mount point exists and is a directory.
+fn check_mount_point(mount_point: &Path) -> Result<(), ZffMountError> {
+    let reason = match std::fs::metadata(mount_point) {
+        Ok(metadata) if metadata.is_dir() => return Ok(()),
+        Ok(_) => String::from("not a directory"),
+        Err(e) => e.to_string(),
+    };
+    Err(ZffMountError::MountPointInvalid { path: mount_point.to_path_buf(), reason })
+}
+
+// logs the given error and exits the process with the appropriate exit status.
+fn exit_with_error(e: ZffMountError) -> ! {
+    match e {
+        ZffMountError::Aborted => warn!("{e}"),
+        _ => error!("{e}"),
+    }
+    if let Some(detail) = e.detail() {
+        debug!("{detail}");
     }
-    inputfiles
+    exit(e.exit_status());
 }
 
 fn main() {
     let args = Cli::parse();
 
-    let log_level = match args.log_level {
+    logging::setup_logger(gen_logger_config(&args));
+    panic_hook::install();
+    if args.progress == ProgressFormat::Json {
+        progress::enable();
+    }
+    if let (Some(identity), Some(log_file)) = (args.run_as, &args.log_file) {
+        chown_for_identity(log_file, identity);
+    }
+
+    // the unmount mechanisms are probed once, before the privileges are dropped or a panic has to be handled.
+    let unmount_mechanisms = unmount_mechanisms();
+    debug!("Unmount mechanisms: {}", unmount_mechanisms.iter().map(|mechanism| mechanism.to_string()).collect::<Vec<_>>().join(", "));
+
+    timestamps::set_timezone(args.timezone);
+    timestamps::set_date_format(args.date_format.clone());
+
+    if let Some(Commands::Umount { mount_point, lazy, force }) = &args.command {
+        umount::umount(mount_point, *lazy, *force, args.pid_file.as_deref());
+    }
+
+    if let Some(path) = &args.pid_file {
+        if let Some(pid) = pidfile::running_pid(path) {
+            error!("zffmount is already running with PID {pid} (see PID file {}).", path.display());
+            exit(EXIT_STATUS_ERROR);
+        }
+    }
+
+    // the mount point and the FUSE environment are checked before the (possibly long) initialization.
+    if let (Some(mount_point), false) = (&args.mount_point, args.benchmark) {
+        check_mount_point(mount_point).unwrap_or_else(|e| exit_with_error(e));
+        if !args.no_fuse {
+            fuse_check::check_fuse_environment(args.allow_other).unwrap_or_else(|e| {
+                warn!("Use --no-fuse to copy the content of the container to {} instead of mounting it.", mount_point.display());
+                exit_with_error(e)
+            });
+        }
+    }
+
+    // the initialization (especially the preloading of the chunkmaps) can take a long time, so it should be abortable.
+    let (abort, abort_handlers) = setup_abort_handler();
+
+    if let Some(Commands::Extract(extract_args)) = &args.command {
+        run_extract_command(&args, extract_args, &abort, abort_handlers);
+    }
+    if let Some(Commands::Ls(ls_args)) = &args.command {
+        run_ls_command(&args, ls_args, &abort, abort_handlers);
+    }
+
+    // the single container (given by --inputfiles) has no name.
+    let mut filesystems = Vec::new();
+    // the locks are held until the process exits.
+    let mut _locks = Vec::new();
+    if args.containers.is_empty() {
+        _locks.extend(lock_container(&args, &args.inputfiles));
+        filesystems.push((None, gen_fs(&args, &args.inputfiles, None, &abort).unwrap_or_else(|e| exit_with_error(e))));
+    } else {
+        // the inodes of each container are shifted by (container index + 1) << CONTAINER_INODE_SHIFT.
+        let max_containers = u64::MAX >> CONTAINER_INODE_SHIFT;
+        if args.containers.len() as u64 > max_containers {
+            error!("{} containers are given, but only {max_containers} containers could be served by one mount point.", args.containers.len());
+            exit(EXIT_STATUS_ERROR);
+        }
+        for (name, pattern) in &args.containers {
+            if filesystems.iter().any(|(other_name, _)| other_name.as_ref() == Some(name)) {
+                error!("The container name {name} is used multiple times.");
+                exit(EXIT_STATUS_ERROR);
+            }
+            info!("Initializing container {name} ...");
+            let input_paths = expand_glob(pattern);
+            _locks.extend(lock_container(&args, &input_paths));
+            let fs = gen_fs(&args, &input_paths, Some(name), &abort).unwrap_or_else(|e| exit_with_error(e));
+            filesystems.push((Some(name.clone()), fs));
+        }
+    }
+
+    // the abort handlers are replaced by the graceful unmount handler below.
+    for handler in abort_handlers {
+        low_level::unregister(handler);
+    }
+    if abort.load(Ordering::SeqCst) {
+        exit_with_error(ZffMountError::Aborted);
+    }
+
+    if args.benchmark {
+        run_benchmark(&mut filesystems, &args);
+    }
+    //unwrap should safe here, because it is a required argument defined by clap (if not in benchmark mode).
+    let mount_point = args.mount_point.clone().unwrap();
+    if args.no_fuse {
+        run_extraction(&mut filesystems, &mount_point);
+    }
+    let mut physical_images = Vec::new();
+    for (name, fs) in &filesystems {
+        let container_dir = name.as_ref().map(PathBuf::from).unwrap_or_default();
+        for object_number in fs.physical_object_numbers() {
+            let image_path = container_dir.join(fs.object_path(object_number)).join(fs.image_name(object_number));
+            physical_images.push((object_number, image_path));
+        }
+    }
+    let mount_options = gen_mount_options(&args);
+    print_mount_summary(&filesystems, &mount_options, &args.summary_format);
+    let (session, activity) = if args.containers.is_empty() {
+        // unwrap is safe here, as the single container was pushed above.
+        let (_, mut fs) = filesystems.pop().unwrap();
+        if let Some(identity) = args.run_as {
+            fs.set_owner(identity.uid.as_raw(), identity.gid.as_raw());
+        }
+        let activity = fs.activity_tracker();
+        (mount(fs, &mount_point, &mount_options), activity)
+    } else {
+        let containers = filesystems.into_iter().map(|(name, fs)| (name.unwrap_or_default(), fs)).collect();
+        let mut fs = ZffMultiFs::new(containers);
+        if let Some(identity) = args.run_as {
+            fs.set_owner(identity.uid.as_raw(), identity.gid.as_raw());
+        }
+        let activity = fs.activity_tracker();
+        (mount(fs, &mount_point, &mount_options), activity)
+    };
+    let loop_devices = if args.losetup {
+        setup_loop_devices(&mount_point, &physical_images, args.losetup_partscan)
+    } else {
+        Vec::new()
+    };
+    panic_hook::set_mount_point(&mount_point);
+    progress::set_container(None);
+    progress::report(progress::Phase::Mounted, None, None);
+    if args.quiet {
+        println!("MOUNTED {}", mount_point.display());
+    } else {
+        info!("Filesystem successfully mounted at {}.", mount_point.display());
+    }
+
+    // setup signal handler to unmount by using CTRL+C (or sending SIGHUB/SIGTERM/SIGINT to process).
+    let mut signals = match Signals::new([SIGINT, SIGHUP, SIGTERM]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            error!("an error occurred while trying to set the signal handler for graceful umounting: {e}");
+            exit(EXIT_STATUS_ERROR);
+        },
+    };
+    let running = Arc::new(AtomicBool::new(false));
+    let force = Arc::new(AtomicBool::new(false));
+    let r = Arc::clone(&running);
+    let f = Arc::clone(&force);
+    thread::spawn(move || {
+        for sig in signals.forever() {
+            // a second signal escalates to a lazy unmount, e.g. if the process which uses the filesystem hangs.
+            if r.swap(true, Ordering::SeqCst) {
+                warn!("UNMOUNT: Received shutdown signal {:?} again. The filesystems will be detached lazily.", sig);
+                f.store(true, Ordering::SeqCst);
+            } else {
+                warn!("UNMOUNT: Received shutdown signal {:?}. The filesystems will be unmounted, as soon as the resource is no longer busy. Send the signal again to force the unmount.", sig);
+            }
+        }
+    });
+
+    let pid_file = args.pid_file.as_ref().map(|path| match pidfile::PidFile::create(path) {
+        Ok(pid_file) => {
+            panic_hook::set_pid_file(path);
+            // the file is removed (or at least emptied) after the privileges were dropped.
+            if let Some(identity) = args.run_as {
+                chown_for_identity(path, identity);
+            }
+            pid_file
+        },
+        Err(e) => {
+            error!("Could not write the PID file {}: {e}", path.display());
+            exit(EXIT_STATUS_ERROR);
+        }
+    });
+
+    // the privileges are dropped after the filesystem was mounted and the loop devices were attached, as both needs the privileges.
+    if let Some(identity) = args.run_as {
+        if let Err(e) = privileges::drop_privileges(identity) {
+            error!("Could not drop the privileges to {}:{}. Aborting, to not continue with the current privileges.", identity.uid, identity.gid);
+            debug!("{e}");
+            drop(loop_devices);
+            drop(pid_file);
+            session.join();
+            exit(EXIT_STATUS_ERROR);
+        }
+        info!("Dropped the privileges to {}:{}.", identity.uid, identity.gid);
+    }
+
+    loop {
+        sleep(1); // to reduce the CPU usage
+        if let Some(idle_timeout) = args.idle_timeout {
+            if matches!(activity.idle_time(), Some(idle_time) if idle_time >= idle_timeout) {
+                warn!("UNMOUNT: The filesystem was idle for more than {} seconds. The filesystem will be unmounted.", idle_timeout.as_secs());
+                running.store(true, Ordering::SeqCst);
+            }
+        }
+        // the session thread finishes if the filesystem was unmounted externally (e.g. by zffmount umount).
+        if running.load(Ordering::SeqCst) || session.guard.is_finished() {
+            break;
+        }
+    }
+
+    let shutdown_start = Instant::now();
+    // the loop devices have to be detached first, otherwise the filesystem would be busy.
+    for loop_device in loop_devices {
+        let path = loop_device.path().to_path_buf();
+        match loop_device.detach() {
+            Ok(_) => info!("Loop device {} detached.", path.display()),
+            Err(e) => warn!("Could not detach loop device {}: {e}", path.display()),
+        }
+    }
+    // joining the session blocks as long as the filesystem is busy, so it is done in its own thread to be able to force the unmount.
+    let unmount = thread::spawn(move || session.join());
+    while !unmount.is_finished() {
+        let timed_out = matches!(args.force_unmount_timeout, Some(timeout) if shutdown_start.elapsed() >= timeout);
+        if timed_out {
+            warn!("UNMOUNT: The filesystem could not be unmounted within {} seconds. The filesystem will be detached lazily.", shutdown_start.elapsed().as_secs());
+        }
+        if force.load(Ordering::SeqCst) || timed_out {
+            if !umount::force_unmount(&mount_point) {
+                error!("Could not detach the filesystem at {}.", mount_point.display());
+                drop(pid_file);
+                exit(EXIT_STATUS_ERROR);
+            }
+            warn!("Filesystem at {} detached lazily (forced unmount). Processes which still use the filesystem will get I/O errors (EIO) from now on.", mount_point.display());
+            drop(pid_file);
+            exit(EXIT_STATUS_FORCED_UNMOUNT);
+        }
+        thread::sleep(SHUTDOWN_POLL_INTERVAL);
+    }
+    info!("Filesystem successfully unmounted. Session closed.");
+    // exit() does not run the destructors, so the PID file has to be removed explicitly.
+    drop(pid_file);
+    exit(EXIT_STATUS_SUCCESS);
+}
+
+fn gen_logger_config(args: &Cli) -> logging::LoggerConfig {
+    let level = match args.log_level {
+        _ if args.quiet => LevelFilter::Error,
         LogLevel::Error => LevelFilter::Error,
         LogLevel::Warn => LevelFilter::Warn,
         LogLevel::Info => LevelFilter::Info,
@@ -132,25 +880,172 @@ fn main() {
         LogLevel::FullDebug => LevelFilter::Debug,
         LogLevel::Trace => LevelFilter::Trace,
     };
-    if args.log_level == LogLevel::FullInfo || args.log_level == LogLevel::FullDebug || args.log_level == LogLevel::Trace {
-        env_logger::builder()
-        .format_timestamp_nanos()
-        .filter_level(log_level)
-        .init();
-    } else {
-        env_logger::builder()
-        .format_timestamp_nanos()
-        .filter_module(env!("CARGO_PKG_NAME"), log_level)
-        .init();
-    };
+    logging::LoggerConfig {
+        level,
+        all_modules: args.log_level == LogLevel::FullInfo || args.log_level == LogLevel::FullDebug || args.log_level == LogLevel::Trace,
+        target: match args.log_target {
+            LogTarget::Stderr => logging::LogTarget::Stderr,
+            LogTarget::Syslog => logging::LogTarget::Syslog,
+            LogTarget::Journald => logging::LogTarget::Journald,
+        },
+        format: match args.log_format {
+            LogFormat::Text => logging::LogFormat::Text,
+            LogFormat::Json => logging::LogFormat::Json,
+        },
+        log_file: args.log_file.clone(),
+        quiet_stderr: args.progress == ProgressFormat::Json,
+    }
+}
 
+// attaches the image of each physical object to a loop device. Failures will not abort the mount.
+// the image paths are relative to the mount point.
+fn setup_loop_devices(mount_point: &Path, physical_images: &[(u64, PathBuf)], partscan: bool) -> Vec<loopdev::LoopDevice> {
+    let mut loop_devices = Vec::new();
+    for (object_number, image_path) in physical_images {
+        let image_path = mount_point.join(image_path);
+        match loopdev::LoopDevice::attach(&image_path, partscan) {
+            Ok(loop_device) => {
+                info!("Object {object_number} attached to loop device {}.", loop_device.path().display());
+                loop_devices.push(loop_device);
+            },
+            Err(e) => warn!("Could not attach {} to a loop device: {e}", image_path.display()),
+        }
+    }
+    loop_devices
+}
 
-    let inputfiles = open_files(&args);
-    
-    let preload_chunkmap = gen_preload_chunkmap(&args);
+fn mount<FS: Filesystem + Send + 'static>(fs: FS, mount_point: &Path, mountoptions: &[MountOption]) -> BackgroundSession {
+    match fuser::spawn_mount2(fs, mount_point, mountoptions) {
+        Ok(session) => session,
+        Err(e) => {
+            error!("An error occurred while trying to mount the filesystem: {e}");
+            exit(EXIT_STATUS_ERROR);
+        }
+    }
+}
 
+// the atimes are faked anyway and nothing should be executed or interpreted from the evidence by accident, unless explicitly allowed.
+fn gen_mount_options(args: &Cli) -> Vec<MountOption> {
+    let mut mountoptions = vec![MountOption::RO, MountOption::FSName(String::from(ZFF_OVERLAY_FS_NAME)), MountOption::NoAtime];
+    mountoptions.push(if args.exec { MountOption::Exec } else { MountOption::NoExec });
+    mountoptions.push(if args.dev { MountOption::Dev } else { MountOption::NoDev });
+    mountoptions.push(if args.suid { MountOption::Suid } else { MountOption::NoSuid });
+    if args.allow_other {
+        mountoptions.push(MountOption::AllowOther);
+    }
+    if let Some(max_read) = args.max_read {
+        // the kernel splits larger reads into multiple requests.
+        mountoptions.push(MountOption::CUSTOM(format!("max_read={max_read}")));
+    }
+    mountoptions
+}
+
+// the built-in backends of the input segments; additional backends could be registered here.
+fn gen_source_registry(args: &Cli) -> SourceRegistry {
+    SourceRegistry::new(InputOptions {
+        direct_input: args.direct_input,
+        mmap: args.input_mmap,
+        #[cfg(feature = "uring")]
+        uring: args.input_uring,
+        http_block_size: args.http_block_size,
+        #[cfg(feature = "s3")]
+        s3_request_size: args.s3_request_size,
+        #[cfg(feature = "s3")]
+        s3_prefetch: args.s3_prefetch,
+    })
+}
+
+// creates the filesystem of a single container.
+fn gen_fs(args: &Cli, input_paths: &[PathBuf], container: Option<&str>, abort: &Arc<AtomicBool>) -> Result<ZffFs<InputReader>, ZffMountError> {
+    progress::set_container(container);
+    let inputfiles = open_files(input_paths, &gen_source_registry(args))?;
+    let preload_chunkmap = gen_preload_chunkmap(args, container, abort)?;
+    let fs_options = gen_fs_options(args);
+    let decryption_passwords = gen_decryption_passwords(args, container);
+    ZffFs::new(inputfiles, &decryption_passwords, preload_chunkmap, fs_options)
+}
+
+// acquires the lock of the container of the given segment files, unless --allow-multiple-mounts is set.
+fn lock_container(args: &Cli, input_paths: &[PathBuf]) -> Option<lock::ContainerLock> {
+    if args.allow_multiple_mounts {
+        return None;
+    }
+    // all segments of a container share the same unique identifier, so the first segment is sufficient.
+    // missing segment files will be reported while opening the files.
+    let segment_path = input_paths.first()?;
+    let segment = gen_source_registry(args).open(segment_path).map(|segments| segments.into_iter().next());
+    let unique_identifier = match segment {
+        Ok(Some(mut segment)) => lock::container_unique_identifier(&mut segment),
+        Ok(None) => Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+        Err(e) => Err(e.into()),
+    };
+    let unique_identifier = match unique_identifier {
+        Ok(unique_identifier) => unique_identifier,
+        Err(e) => {
+            error!("Could not read the segment header of {}.", segment_path.display());
+            debug!("{e}");
+            exit(EXIT_STATUS_ERROR);
+        }
+    };
+    let mount_point = match &args.mount_point {
+        Some(mount_point) => mount_point.display().to_string(),
+        None => String::from("(benchmark)"),
+    };
+    match lock::ContainerLock::acquire(unique_identifier, &mount_point) {
+        Ok(lock) => Some(lock),
+        Err(lock::LockError::AlreadyLocked(pid, mount_point)) => {
+            error!("The container is already mounted at {mount_point} by zffmount (PID {pid}). Use --allow-multiple-mounts to mount it anyway.");
+            exit(EXIT_STATUS_ERROR);
+        },
+        Err(lock::LockError::Io(e)) => {
+            error!("Could not acquire the lock of the container.");
+            debug!("{e}");
+            exit(EXIT_STATUS_ERROR);
+        }
+    }
+}
+
+// returns the sorted paths, which match the given glob pattern.
+fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    // URLs could not be globbed.
+    if input::is_url(Path::new(pattern)) {
+        return vec![PathBuf::from(pattern)];
+    }
+    let paths = match glob::glob(pattern) {
+        Ok(paths) => paths,
+        Err(e) => {
+            error!("Invalid glob pattern {pattern}: {e}");
+            exit(EXIT_STATUS_ERROR);
+        }
+    };
+    let mut input_paths = Vec::new();
+    for path in paths {
+        match path {
+            Ok(path) => input_paths.push(path),
+            Err(e) => {
+                error!("{e}");
+                exit(EXIT_STATUS_ERROR);
+            }
+        }
+    }
+    if input_paths.is_empty() {
+        error!("No input files found for {pattern}.");
+        exit(EXIT_STATUS_ERROR);
+    }
+    input_paths.sort();
+    input_paths
+}
+
+// returns the passwords of the given container. Passwords without container name (e.g. 1:password) are used for all containers,
+// passwords with container name (e.g. source/1:password) only for the appropriate container.
+fn gen_decryption_passwords(args: &Cli, container: Option<&str>) -> HashMap<u64, String> {
     let mut decryption_passwords = HashMap::new();
-    for (obj_no, pw) in args.decryption_passwords {
+    for (key, pw) in &args.decryption_passwords {
+        let obj_no = match key.rsplit_once('/') {
+            Some((container_name, obj_no)) if Some(container_name) == container => obj_no,
+            Some(_) => continue,
+            None => key.as_str(),
+        };
         let obj_no = match obj_no.parse::<u64>() {
             Ok(no) => no,
             Err(e) => {
@@ -158,48 +1053,267 @@ fn main() {
                 exit(EXIT_STATUS_ERROR);
             }
         };
-        decryption_passwords.insert(obj_no, pw);
+        decryption_passwords.insert(obj_no, pw.clone());
     }
+    decryption_passwords
+}
 
-    let fs = ZffFs::new(inputfiles, &decryption_passwords, preload_chunkmap);
-    let mountoptions = vec![MountOption::RO, MountOption::FSName(String::from(ZFF_OVERLAY_FS_NAME))];
-    let session = match fuser::spawn_mount2(fs, &args.mount_point, &mountoptions) {
-        Ok(session) => session,
+// extracts the given path (or the whole container) and exits the process.
+fn run_extract_command(args: &Cli, extract_args: &ExtractArgs, abort: &Arc<AtomicBool>, abort_handlers: Vec<SigId>) -> ! {
+    if extract_args.path.is_some() && args.object.is_none() {
+        error!("--path requires --object.");
+        exit(EXIT_STATUS_ERROR);
+    }
+    let mut fs = gen_fs(args, &args.inputfiles, None, abort).unwrap_or_else(|e| exit_with_error(e));
+    // the extraction is interrupted by the default signal handlers.
+    for handler in abort_handlers {
+        low_level::unregister(handler);
+    }
+    if abort.load(Ordering::SeqCst) {
+        exit_with_error(ZffMountError::Aborted);
+    }
+
+    let options = fs::ExtractOptions {
+        on_conflict: match extract_args.on_conflict {
+            OnConflict::Error => fs::ConflictPolicy::Error,
+            OnConflict::Skip => fs::ConflictPolicy::Skip,
+            OnConflict::Overwrite => fs::ConflictPolicy::Overwrite,
+            OnConflict::Rename => fs::ConflictPolicy::Rename,
+        },
+        hash_types: extract_args.hash.iter().map(|algorithm| algorithm.hash_type()).collect(),
+        sparse: extract_args.sparse,
+        verify: extract_args.verify,
+    };
+    let out = &extract_args.out;
+    let report = match (&extract_args.path, args.object) {
+        (Some(path), Some(object_number)) => fs.extract_path(object_number, path, out, &options),
+        _ => fs.extract_all(out, &options),
+    }.unwrap_or_else(|e| exit_with_error(e));
+    info!("Extracted to {}: {}", out.display(), report.stats);
+
+    for algorithm in &extract_args.hash {
+        let mut manifest_path = out.clone().into_os_string();
+        manifest_path.push(format!(".{}", fs::hash_file_extension(&algorithm.hash_type())));
+        let manifest_path = PathBuf::from(manifest_path);
+        match report.write_manifest(&algorithm.hash_type(), &manifest_path) {
+            Ok(_) => info!("Hash manifest written to {}.", manifest_path.display()),
+            Err(e) => {
+                error!("Could not write the hash manifest {}: {e}", manifest_path.display());
+                exit(EXIT_STATUS_ERROR);
+            }
+        }
+    }
+    if extract_args.verify {
+        let verification_report = report.verification_report();
+        info!("Verification: {}", verification_report.summary);
+        write_verification_report(&verification_report, out);
+        if verification_report.summary.mismatches > 0 {
+            exit(EXIT_STATUS_VERIFICATION_FAILED);
+        }
+    }
+    exit(if report.stats.errors > 0 { EXIT_STATUS_ERROR } else { EXIT_STATUS_SUCCESS });
+}
+
+// writes the given verification report as text and JSON next to the given destination directory.
+fn write_verification_report(report: &fs::VerificationReport, out: &Path) {
+    let report_path = |extension: &str| {
+        let mut path = out.to_path_buf().into_os_string();
+        path.push(format!(".verification.{extension}"));
+        PathBuf::from(path)
+    };
+    let json = match serde_json::to_string_pretty(report) {
+        Ok(json) => json,
         Err(e) => {
-            error!("An error occurred while trying to mount the filesystem.");
+            error!("An error occurred while trying to serialize the verification report.");
             debug!("{e}");
             exit(EXIT_STATUS_ERROR);
         }
     };
+    for (path, content) in [(report_path("txt"), report.to_string()), (report_path("json"), json)] {
+        match std::fs::write(&path, content) {
+            Ok(_) => info!("Verification report written to {}.", path.display()),
+            Err(e) => {
+                error!("Could not write the verification report {}: {e}", path.display());
+                exit(EXIT_STATUS_ERROR);
+            }
+        }
+    }
+}
 
-    // setup signal handler to unmount by using CTRL+C (or sending SIGHUB/SIGTERM/SIGINT to process).
-    let mut signals = match Signals::new([SIGINT, SIGHUP, SIGTERM]) {
-        Ok(signals) => signals,
-        Err(e) => {
-            error!("an error occurred while trying to set the signal handler for graceful umounting: {e}");
-            exit(EXIT_STATUS_ERROR);
+// prints the listing of the given path (or of the whole container) and exits the process.
+fn run_ls_command(args: &Cli, ls_args: &LsArgs, abort: &Arc<AtomicBool>, abort_handlers: Vec<SigId>) -> ! {
+    if ls_args.path.is_some() && args.object.is_none() {
+        error!("--path requires --object.");
+        exit(EXIT_STATUS_ERROR);
+    }
+    let mut fs = gen_fs(args, &args.inputfiles, None, abort).unwrap_or_else(|e| exit_with_error(e));
+    for handler in abort_handlers {
+        low_level::unregister(handler);
+    }
+    if abort.load(Ordering::SeqCst) {
+        exit_with_error(ZffMountError::Aborted);
+    }
+
+    let listing = match (&ls_args.path, args.object) {
+        (Some(path), Some(object_number)) => fs.list_path(object_number, path, ls_args.recursive),
+        (None, Some(object_number)) => fs.list_path(object_number, Path::new("/"), ls_args.recursive),
+        _ => fs.list_all(ls_args.recursive),
+    }.unwrap_or_else(|e| exit_with_error(e));
+    match ls_args.format {
+        ListFormat::Table => print!("{}", fs::ListingTable(&listing)),
+        ListFormat::Csv => print!("{}", fs::ListingCsv(&listing)),
+        ListFormat::Json => match serde_json::to_string_pretty(&listing) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                error!("An error occurred while trying to serialize the listing.");
+                debug!("{e}");
+                exit(EXIT_STATUS_ERROR);
+            }
         },
+    }
+    exit(EXIT_STATUS_SUCCESS);
+}
+
+// prints the summary of the initialized containers and the given mount options in the given format.
+fn print_mount_summary(filesystems: &[(Option<String>, ZffFs<InputReader>)], mount_options: &[MountOption], format: &SummaryFormat) {
+    let summary = fs::MountSummary {
+        containers: filesystems.iter().map(|(name, fs)| fs.summary(name.as_deref())).collect(),
+        mount_options: mount_options.iter().map(|option| format!("{option:?}")).collect(),
     };
-    let running = Arc::new(AtomicBool::new(false));
-    let r = Arc::clone(&running);
-    thread::spawn(move || {
-        for sig in signals.forever() {
-            warn!("UNMOUNT: Received shutdown signal {:?}. The filesystems will be unmounted, as soon as the resource is no longer busy.", sig);
-            r.store(true, Ordering::SeqCst);
+    match format {
+        SummaryFormat::Table => info!("{summary}"),
+        SummaryFormat::Json => match serde_json::to_string_pretty(&summary) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                warn!("An error occurred while trying to serialize the mount summary.");
+                debug!("{e}");
+            }
+        },
+    }
+}
+
+// extracts the content of all containers to the given destination directory (each container to its own subdirectory,
+// if multiple containers are given) and exits the process.
+fn run_extraction(filesystems: &mut [(Option<String>, ZffFs<InputReader>)], destination: &Path) -> ! {
+    let mut failed = false;
+    for (name, fs) in filesystems.iter_mut() {
+        let destination = match name {
+            Some(name) => destination.join(name),
+            None => destination.to_path_buf(),
+        };
+        match fs.extract_all(&destination, &fs::ExtractOptions::default()) {
+            Ok(report) => {
+                info!("Extracted to {}: {}", destination.display(), report.stats);
+                failed |= report.stats.errors > 0;
+            },
+            Err(e) => {
+                error!("{e}");
+                if let Some(detail) = e.detail() {
+                    debug!("{detail}");
+                }
+                failed = true;
+            }
         }
-    });
+    }
+    exit(if failed { EXIT_STATUS_ERROR } else { EXIT_STATUS_SUCCESS });
+}
 
-    loop {
-        sleep(1); // to reduce the CPU usage
-        if running.load(Ordering::SeqCst) {
-            session.join();
-            info!("Filesystem successfully unmounted. Session closed.");
-            exit(EXIT_STATUS_SUCCESS);
+fn run_benchmark(filesystems: &mut [(Option<String>, ZffFs<InputReader>)], args: &Cli) -> ! {
+    let config = fs::BenchmarkConfig {
+        size: args.benchmark_size,
+        random_reads: args.benchmark_random_reads,
+    };
+    let mut results = Vec::new();
+    let mut metadata_results = Vec::new();
+    for (name, fs) in filesystems.iter_mut() {
+        for mut result in fs.benchmark(&config) {
+            result.container = name.clone();
+            results.push(result);
+        }
+        let mut metadata_result = fs.benchmark_metadata(BENCHMARK_METADATA_ROUNDS);
+        metadata_result.container = name.clone();
+        metadata_results.push(metadata_result);
+    }
+    #[cfg(feature = "uring")]
+    let input_results = if args.benchmark_input { benchmark_input(args) } else { Vec::new() };
+    #[cfg(not(feature = "uring"))]
+    let input_results: Vec<fs::InputMeasurement> = Vec::new();
+    if args.benchmark_json {
+        // the JSON output keeps the list of the object results, so the metadata and input results are only logged.
+        for metadata_result in &metadata_results {
+            info!("{}", metadata_result.to_string().trim_end());
+        }
+        for input_result in &input_results {
+            info!("{}", input_result.to_string().trim_end());
+        }
+        match serde_json::to_string_pretty(&results) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                error!("An error occurred while trying to serialize the benchmark results.");
+                debug!("{e}");
+                exit(EXIT_STATUS_ERROR);
+            }
+        }
+    } else {
+        print!("{}", fs::BenchmarkTable(&results));
+        for metadata_result in &metadata_results {
+            print!("{metadata_result}");
+        }
+        for input_result in &input_results {
+            print!("{input_result}");
+        }
+    }
+    exit(EXIT_STATUS_SUCCESS);
+}
+
+// measures the random reads of the local input files with the standard and the io_uring read path (with the same offsets).
+#[cfg(feature = "uring")]
+fn benchmark_input(args: &Cli) -> Vec<fs::InputMeasurement> {
+    let input_paths: Vec<PathBuf> = if args.containers.is_empty() {
+        args.inputfiles.clone()
+    } else {
+        args.containers.iter().flat_map(|(_, pattern)| expand_glob(pattern)).collect()
+    };
+    let mut results = Vec::new();
+    for path in input_paths.iter().filter(|path| !input::is_url(path)) {
+        info!("Benchmark: {} random reads of input file {} ...", args.benchmark_random_reads, path.display());
+        let seed = fs::benchmark_seed();
+        let measurement = std::fs::File::open(path)
+            .and_then(|mut file| fs::benchmark_input_reads(&mut file, args.benchmark_random_reads, seed))
+            .and_then(|standard| {
+                let mut reader = input::UringReader::open(path)?;
+                let uring = fs::benchmark_input_reads(&mut reader, args.benchmark_random_reads, seed)?;
+                Ok((standard, uring))
+            });
+        match measurement {
+            Ok((standard, uring)) => results.push(fs::InputMeasurement { path: path.display().to_string(), standard, uring }),
+            Err(e) => warn!("Could not benchmark the input file {}: {e}", path.display()),
         }
     }
+    results
 }
 
-fn gen_preload_chunkmap(args: &Cli) -> fs::PreloadChunkmaps {
+/// sets the returned flag on SIGINT/SIGTERM. A second signal terminates the process immediately (e.g. if the
+/// initialization hangs at a point, where the flag is not checked).
+fn setup_abort_handler() -> (Arc<AtomicBool>, Vec<SigId>) {
+    let abort = Arc::new(AtomicBool::new(false));
+    let mut handlers = Vec::new();
+    for signal in [SIGINT, SIGTERM] {
+        // the conditional shutdown has to be registered first, so that the first signal only sets the flag.
+        let registered = flag::register_conditional_shutdown(signal, EXIT_STATUS_ABORTED, Arc::clone(&abort))
+            .and_then(|shutdown| Ok((shutdown, flag::register(signal, Arc::clone(&abort))?)));
+        match registered {
+            Ok((shutdown, handler)) => handlers.extend([shutdown, handler]),
+            Err(e) => {
+                error!("an error occurred while trying to set the signal handler for aborting the initialization: {e}");
+                exit(EXIT_STATUS_ERROR);
+            },
+        }
+    }
+    (abort, handlers)
+}
+
+fn gen_preload_chunkmap(args: &Cli, container: Option<&str>, abort: &Arc<AtomicBool>) -> Result<fs::PreloadChunkmaps, ZffMountError> {
     let mut offsets = args.preload_chunk_offset_map;
     let mut sizes = args.preload_chunk_size_map;
     let mut flags = args.preload_chunk_flags_map;
@@ -217,22 +1331,95 @@ fn gen_preload_chunkmap(args: &Cli) -> fs::PreloadChunkmaps {
         flags,
         samebytes,
         mode: fs::PreloadChunkmapsMode::None,
+        abort: Arc::clone(abort),
+        created_database: None,
     };
     match args.preload_mode {
         PreloadMode::None => (),
         PreloadMode::InMemory => preload_chunkmaps.mode = fs::PreloadChunkmapsMode::InMemory,
         PreloadMode::Redb => {
             //unwrap should safe here, because it is a required argument defined by clap.
-            let db = match redb::Database::create(args.redb_path.clone().unwrap()) {
-                Ok(db) => db,
-                Err(e) => {
-                    error!("An error occurred while trying to create preload chunmap database.");
-                    debug!("{e}");
-                    exit(EXIT_STATUS_ERROR);
-                }
-            };
+            let mut redb_path = args.redb_path.clone().unwrap();
+            // each container needs its own database.
+            if let Some(container) = container {
+                let mut file_name = redb_path.file_name().map(OsString::from).unwrap_or_default();
+                file_name.push(format!(".{container}"));
+                redb_path.set_file_name(file_name);
+            }
+            // an existing database is reused and should not be removed, if the preload is aborted.
+            if !redb_path.exists() {
+                preload_chunkmaps.created_database = Some(redb_path.clone());
+            }
+            let db = redb::Database::create(&redb_path)
+                .map_err(|e| ZffMountError::redb("An error occurred while trying to create preload chunmap database.", e))?;
+            if let Some(identity) = args.run_as {
+                chown_for_identity(&redb_path, identity);
+            }
             preload_chunkmaps.mode = fs::PreloadChunkmapsMode::Redb(db)
         }
     }
-    preload_chunkmaps
+    Ok(preload_chunkmaps)
+}
+
+// changes the owner of the given file, so the file remains writable after dropping the privileges.
+fn chown_for_identity(path: &Path, identity: privileges::Identity) {
+    if let Err(e) = privileges::chown(path, identity) {
+        error!("Could not change the owner of {} to {}:{}.", path.display(), identity.uid, identity.gid);
+        debug!("{e}");
+        exit(EXIT_STATUS_ERROR);
+    }
+}
+
+fn gen_fs_options(args: &Cli) -> fs::ZffFsOptions {
+    fs::ZffFsOptions {
+        verify_at_mount: args.verify_at_mount,
+        logical_blocks: args.logical_blocks,
+        direct_io: args.direct_io,
+        keep_page_cache: args.keep_page_cache || args.cache_forever,
+        cache_forever: args.cache_forever,
+        case_insensitive: args.case_insensitive,
+        normalize_lookup: match args.normalize_lookup {
+            NormalizeLookup::None => fs::LookupNormalization::None,
+            NormalizeLookup::Nfc => fs::LookupNormalization::Nfc,
+            NormalizeLookup::Nfd => fs::LookupNormalization::Nfd,
+        },
+        sanitize_names: args.sanitize_names,
+        latency_stats: args.latency_stats,
+        cache_size: args.cache_size,
+        split_raw: args.split_raw,
+        hash_sidecars: args.hash_sidecars,
+        report_format: match args.report_format {
+            AcquisitionReportFormat::Text => fs::ReportFormat::Text,
+            AcquisitionReportFormat::Json => fs::ReportFormat::Json,
+        },
+        fail_damaged_reads: args.fail_damaged_reads,
+        virtual_archives: args.virtual_archives,
+        hide_encrypted: args.hide_encrypted,
+        require_all_decrypted: args.require_all_decrypted,
+        access_restriction: if args.restrict_to_uid.is_empty() {
+            None
+        } else {
+            Some(fs::AccessRestriction::new(args.restrict_to_uid.iter().copied(), args.allow_root_access))
+        },
+        squash_owner: match args.squash_owner {
+            SquashOwner::None => fs::OwnerSquashing::None,
+            SquashOwner::Caller => fs::OwnerSquashing::Caller,
+        },
+        image_name: args.image_name.clone(),
+        object: args.object,
+        object_dir_prefix: Some(args.object_dir_prefix.clone()),
+        flatten_single: args.flatten_single,
+        epoch_for_missing_times: args.epoch_for_missing_times,
+        owner_map: args.map_owners.as_ref().map(|path| match fs::OwnerMap::from_file(path) {
+            Ok(owner_map) => owner_map,
+            Err(e) => {
+                error!("Could not read the owner mapping file {}: {e}", path.display());
+                exit(EXIT_STATUS_ERROR);
+            }
+        }),
+        rebase_symlinks: args.rebase_symlinks,
+        max_readahead: args.max_readahead,
+        max_background: args.max_background,
+        congestion_threshold: args.congestion_threshold,
+    }
 }
\ No newline at end of file
@@ -0,0 +1,126 @@
+// - STD
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+// PanicInfo is deprecated since Rust 1.82, but its replacement PanicHookInfo requires a newer Rust version than the MSRV.
+#[allow(deprecated)]
+use std::panic::PanicInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// - internal
+use crate::{pidfile, umount};
+
+// - external
+use log::{error, warn, debug};
+
+// the paths, which have to be cleaned up, if the process panics.
+struct CleanupPaths {
+    mount_point: Option<PathBuf>,
+    pid_file: Option<PathBuf>,
+}
+
+static CLEANUP_PATHS: Mutex<CleanupPaths> = Mutex::new(CleanupPaths { mount_point: None, pid_file: None });
+// set by the first panic, so a panic inside of the hook (or a concurrent panic of another thread) does not clean up twice.
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // set while a closure is executed by catch(), so the hook does not abort the process for a panic, which will be caught.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs the panic hook. A panic of any thread (e.g. the FUSE session thread) would leave a mount point, which is not
+/// connected to a process anymore ("Transport endpoint is not connected"). Therefore, the hook
+/// - logs the panic with a backtrace,
+/// - detaches the filesystem lazily (if it was already mounted),
+/// - removes the PID file (the container locks are released by the kernel, as soon as the process dies),
+/// - calls the default hook and aborts the process, so the process dies with a failure status.
+///
+/// The hook never touches the FUSE session (the panic could have happened while the session was used) and does not block
+/// on the registered paths, so it can not deadlock.
+pub(crate) fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if CATCHING.with(|catching| catching.get()) {
+            debug!("Caught panic: {}\nBacktrace:\n{}", panic_message(info.payload()), Backtrace::force_capture());
+            return;
+        }
+        if !PANICKING.swap(true, Ordering::SeqCst) {
+            cleanup(info);
+        }
+        default_hook(info);
+        std::process::abort();
+    }));
+}
+
+/// Executes the given closure and catches a panic of it, instead of aborting the process. The returned error contains the
+/// panic payload (see [panic_message]).
+pub(crate) fn catch<F: FnOnce() -> T, T>(f: F) -> Result<T, Box<dyn Any + Send>> {
+    let was_catching = CATCHING.with(|catching| catching.replace(true));
+    // the callers have to make sure, that their state is still usable after a panic (see ZffFs::guarded).
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(was_catching));
+    result
+}
+
+/// Sets the mount point, which will be detached lazily, if the process panics.
+pub(crate) fn set_mount_point(mount_point: &Path) {
+    update(|paths| paths.mount_point = Some(mount_point.to_path_buf()));
+}
+
+/// Sets the PID file, which will be removed, if the process panics.
+pub(crate) fn set_pid_file(pid_file: &Path) {
+    update(|paths| paths.pid_file = Some(pid_file.to_path_buf()));
+}
+
+fn update<F: FnOnce(&mut CleanupPaths)>(f: F) {
+    let mut paths = match CLEANUP_PATHS.lock() {
+        Ok(paths) => paths,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    f(&mut paths);
+}
+
+#[allow(deprecated)] // see the import of PanicInfo.
+fn cleanup(info: &PanicInfo) {
+    let location = match info.location() {
+        Some(location) => format!("{}:{}", location.file(), location.line()),
+        None => String::from("unknown location"),
+    };
+    error!("zffmount panicked at {location}: {}", panic_message(info.payload()));
+    error!("Backtrace:\n{}", Backtrace::force_capture());
+
+    // the paths are only set by the main thread, so the lock is never held for long. If it is held anyway (e.g. the main
+    // thread panicked while setting a path), the cleanup is skipped instead of waiting.
+    let paths = match CLEANUP_PATHS.try_lock() {
+        Ok(paths) => paths,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            warn!("Could not clean up after the panic. The mount point has to be unmounted manually.");
+            return;
+        }
+    };
+    if let Some(mount_point) = &paths.mount_point {
+        if umount::force_unmount(mount_point) {
+            warn!("Filesystem at {} detached lazily after the panic.", mount_point.display());
+        } else {
+            error!("Could not detach the filesystem at {} after the panic. Use fusermount -u to unmount it.", mount_point.display());
+        }
+    }
+    if let Some(pid_file) = &paths.pid_file {
+        pidfile::remove(pid_file);
+    }
+}
+
+/// Returns the message of the given panic payload (panic!() with a format string passes a String, otherwise a &str).
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic payload")
+    }
+}
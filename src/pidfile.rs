@@ -0,0 +1,76 @@
+// - STD
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// - external
+use log::{warn, debug};
+
+const PROC_COMM_PATH: &str = "/proc/{pid}/comm";
+const ZFFMOUNT_PROCESS_NAME: &str = "zffmount";
+
+/// A PID file, which is removed on drop (i.e. on a regular exit and while unwinding after a panic).
+/// Note: std::process::exit() does not run any destructors, so the file has to be dropped explicitly before.
+pub(crate) struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the PID of the current process to the given file.
+    pub(crate) fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        fs::write(&path, format!("{}\n", std::process::id()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        remove(&self.path);
+    }
+}
+
+/// Removes the given PID file. If the file could not be removed (e.g. the privileges were dropped and the directory, like /run,
+/// is not writable anymore), the file is emptied instead, so it is recognized as stale by the next zffmount process.
+pub(crate) fn remove(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied && fs::write(path, "").is_ok() => {
+            debug!("Could not remove the PID file {}, but emptied it: {e}", path.display());
+        },
+        Err(e) => warn!("Could not remove the PID file {}: {e}", path.display()),
+    }
+}
+
+/// Returns the PID of the running zffmount process, which is recorded in the given PID file.
+/// Returns None if the file does not exist or is stale (e.g. after a crash). Stale files are reported by a warning.
+pub(crate) fn running_pid<P: AsRef<Path>>(path: P) -> Option<i32> {
+    let path = path.as_ref();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("Could not read the PID file {}: {e}", path.display());
+            return None;
+        }
+    };
+    match content.trim().parse::<i32>() {
+        Ok(pid) if pid > 0 && is_zffmount_process(pid) => Some(pid),
+        _ => {
+            warn!("Found a stale PID file {}. It will be overwritten.", path.display());
+            None
+        }
+    }
+}
+
+// checks if a process with the given PID is alive and is a zffmount process.
+fn is_zffmount_process(pid: i32) -> bool {
+    let comm_path = PROC_COMM_PATH.replace("{pid}", &pid.to_string());
+    match fs::read_to_string(comm_path) {
+        Ok(comm) => comm.trim() == ZFFMOUNT_PROCESS_NAME,
+        Err(e) => {
+            debug!("{e}");
+            false
+        }
+    }
+}
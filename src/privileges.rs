@@ -0,0 +1,53 @@
+// - STD
+use std::error::Error;
+use std::path::Path;
+
+// - external
+use nix::unistd::{self, Uid, Gid, User, Group};
+
+/// The user and group, which will be used after dropping the privileges.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Identity {
+    pub uid: Uid,
+    pub gid: Gid,
+}
+
+/// Parse an identity (user[:group]). User and group could be given by name or by number.
+/// If the group is omitted, the primary group of the user will be used.
+pub(crate) fn parse_identity(s: &str) -> Result<Identity, Box<dyn Error + Send + Sync + 'static>> {
+    let (user, group) = match s.split_once(':') {
+        Some((user, group)) => (user, Some(group)),
+        None => (s, None),
+    };
+    let user = match user.parse::<u32>() {
+        Ok(uid) => User::from_uid(Uid::from_raw(uid))?,
+        Err(_) => User::from_name(user)?,
+    }.ok_or_else(|| format!("unknown user `{user}`"))?;
+    let gid = match group {
+        None => user.gid,
+        Some(group) => match group.parse::<u32>() {
+            Ok(gid) => Gid::from_raw(gid),
+            Err(_) => Group::from_name(group)?.ok_or_else(|| format!("unknown group `{group}`"))?.gid,
+        }
+    };
+    Ok(Identity { uid: user.uid, gid })
+}
+
+/// Changes the owner of the given file (e.g. the redb database or the log file), so the file remains writable after dropping the privileges.
+pub(crate) fn chown<P: AsRef<Path>>(path: P, identity: Identity) -> nix::Result<()> {
+    unistd::chown(path.as_ref(), Some(identity.uid), Some(identity.gid))
+}
+
+/// Drops the privileges of the current process to the given identity (including the supplementary groups).
+/// Returns an error, if the privileges could not be dropped completely.
+pub(crate) fn drop_privileges(identity: Identity) -> nix::Result<()> {
+    // the order is important: after setuid, the process is not allowed to change the groups anymore.
+    unistd::setgroups(&[identity.gid])?;
+    unistd::setgid(identity.gid)?;
+    unistd::setuid(identity.uid)?;
+    // ensure that the privileges can not be regained.
+    if identity.uid != Uid::from_raw(0) && unistd::setuid(Uid::from_raw(0)).is_ok() {
+        return Err(nix::errno::Errno::EPERM);
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// - STD
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// - external
+use serde::Serialize;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// the name of the container, which is currently initialized (if multiple containers are mounted).
+static CONTAINER: Mutex<Option<String>> = Mutex::new(None);
+
+/// The phases of the initialization, which are reported by the progress events (in this order; the preload phases
+/// are only reported, if the appropriate chunkmap is preloaded).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Phase {
+    OpenSegments,
+    InitializeObjects,
+    PreloadChunkOffsetMap,
+    PreloadChunkSizeMap,
+    PreloadChunkFlagsMap,
+    PreloadChunkSamebytesMap,
+    BuildCaches,
+    Mounted,
+}
+
+impl Phase {
+    fn name(&self) -> &'static str {
+        match self {
+            Phase::OpenSegments => "open_segments",
+            Phase::InitializeObjects => "initialize_objects",
+            Phase::PreloadChunkOffsetMap => "preload_chunk_offset_map",
+            Phase::PreloadChunkSizeMap => "preload_chunk_size_map",
+            Phase::PreloadChunkFlagsMap => "preload_chunk_flags_map",
+            Phase::PreloadChunkSamebytesMap => "preload_chunk_samebytes_map",
+            Phase::BuildCaches => "build_caches",
+            Phase::Mounted => "mounted",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    phase: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    done: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container: Option<&'a str>,
+}
+
+/// Enables the progress events.
+pub(crate) fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Sets the name of the container, which is added to the following events.
+pub(crate) fn set_container(name: Option<&str>) {
+    let mut container = match CONTAINER.lock() {
+        Ok(container) => container,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *container = name.map(String::from);
+}
+
+/// Emits a progress event of the given phase (if the progress events are enabled). Every event is a single JSON object
+/// on its own line on stderr, e.g. {"phase":"preload_chunk_size_map","done":0,"total":98765432}:
+/// - phase: the name of the phase (always present).
+/// - done: the number of processed units (segments, objects or chunks) of the phase.
+/// - total: the total number of units of the phase, if it is known.
+/// - container: the name of the container, if multiple containers are mounted.
+///
+/// Each phase starts with done = 0 and ends with done = total. The preload phases are executed by zff as a whole,
+/// so they only report their start and their end. The last event is {"phase":"mounted"}.
+pub(crate) fn report(phase: Phase, done: Option<u64>, total: Option<u64>) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    let container = match CONTAINER.lock() {
+        Ok(container) => container,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let event = ProgressEvent {
+        phase: phase.name(),
+        done,
+        total,
+        container: container.as_deref(),
+    };
+    // the serialization of the event can not fail and a closed stderr should not abort the mount.
+    if let Ok(json) = serde_json::to_string(&event) {
+        let mut stderr = io::stderr().lock();
+        let _ = writeln!(stderr, "{json}");
+        let _ = stderr.flush();
+    }
+}
@@ -0,0 +1,138 @@
+// - STD
+use std::sync::Mutex;
+
+// - external
+use time::{OffsetDateTime, UtcOffset, format_description::{self, OwnedFormatItem, well_known::Rfc3339}};
+use time_tz::{timezones, OffsetDateTimeExt, Tz};
+
+/// The timezone, in which the timestamps of the metadata output (e.g. the acquisition reports) are rendered.
+/// The timestamps of the file attributes are not affected, as the kernel gets them as seconds since the epoch.
+#[derive(Clone, Copy)]
+pub(crate) enum TimeZone {
+    Utc,
+    Named(&'static Tz),
+}
+
+/// A custom format of the rendered timestamps (in the format description syntax of the time crate).
+#[derive(Clone)]
+pub(crate) struct DateFormat(OwnedFormatItem);
+
+static TIMEZONE: Mutex<TimeZone> = Mutex::new(TimeZone::Utc);
+static DATE_FORMAT: Mutex<Option<DateFormat>> = Mutex::new(None);
+
+/// Parses the given timezone: utc, local (the timezone of the system) or an IANA name (e.g. Europe/Berlin).
+/// The local timezone is resolved by its IANA name (instead of the current offset of the process), so the daylight
+/// saving time is applied correctly to all timestamps and the offset is not read from the (unsound) libc functions.
+pub(crate) fn parse_timezone(value: &str) -> Result<TimeZone, String> {
+    match value {
+        "utc" | "UTC" => Ok(TimeZone::Utc),
+        "local" => time_tz::system::get_timezone()
+            .map(TimeZone::Named)
+            .map_err(|e| format!("the local timezone could not be determined ({e}), use an IANA name instead")),
+        name => timezones::get_by_name(name)
+            .map(TimeZone::Named)
+            .ok_or_else(|| format!("unknown timezone {name} (expected utc, local or an IANA name, e.g. Europe/Berlin)")),
+    }
+}
+
+/// Sets the timezone of the rendered timestamps (UTC by default).
+pub(crate) fn set_timezone(timezone: TimeZone) {
+    let mut current = match TIMEZONE.lock() {
+        Ok(current) => current,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *current = timezone;
+}
+
+/// Parses the given date format, e.g. [year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]:[offset_minute]
+/// (see https://time-rs.github.io/book/api/format-description.html).
+pub(crate) fn parse_date_format(value: &str) -> Result<DateFormat, String> {
+    let format = format_description::parse_owned::<1>(value).map_err(|e| format!("invalid date format: {e}"))?;
+    // the format is checked once, so the rendering of the timestamps could not fail later.
+    OffsetDateTime::UNIX_EPOCH.format(&format).map_err(|e| format!("invalid date format: {e}"))?;
+    Ok(DateFormat(format))
+}
+
+/// Sets the format of the rendered timestamps (RFC 3339 by default).
+pub(crate) fn set_date_format(date_format: Option<DateFormat>) {
+    let mut current = match DATE_FORMAT.lock() {
+        Ok(current) => current,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *current = date_format;
+}
+
+/// Renders the given timestamp in the configured timezone and format. The default format is RFC 3339, which always contains
+/// the offset (e.g. 2023-05-04T13:37:00+02:00). A custom format only contains the offset, if it is part of the format.
+pub(crate) fn format(timestamp: OffsetDateTime) -> String {
+    let timezone = match TIMEZONE.lock() {
+        Ok(timezone) => *timezone,
+        Err(poisoned) => *poisoned.into_inner(),
+    };
+    let date_format = match DATE_FORMAT.lock() {
+        Ok(date_format) => date_format,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    format_with(timestamp, timezone, date_format.as_ref())
+}
+
+// renders the given timestamp in the given timezone and format (see format).
+fn format_with(timestamp: OffsetDateTime, timezone: TimeZone, date_format: Option<&DateFormat>) -> String {
+    let timestamp = match timezone {
+        TimeZone::Utc => timestamp.to_offset(UtcOffset::UTC),
+        TimeZone::Named(tz) => timestamp.to_timezone(tz),
+    };
+    let formatted = match date_format {
+        Some(DateFormat(format)) => timestamp.format(format),
+        None => timestamp.format(&Rfc3339),
+    };
+    formatted.unwrap_or_else(|_| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::PrimitiveDateTime;
+
+    // 2023-05-04T11:37:00Z
+    const TIMESTAMP: i64 = 1_683_200_220;
+
+    fn timestamp() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(TIMESTAMP).unwrap()
+    }
+
+    fn berlin() -> TimeZone {
+        parse_timezone("Europe/Berlin").unwrap()
+    }
+
+    #[test]
+    fn default_format_round_trip() {
+        let formatted = format_with(timestamp(), berlin(), None);
+        assert_eq!(formatted, "2023-05-04T13:37:00+02:00");
+        assert_eq!(OffsetDateTime::parse(&formatted, &Rfc3339).unwrap(), timestamp());
+    }
+
+    #[test]
+    fn iso_8601_format_round_trip() {
+        let description = "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]";
+        let date_format = parse_date_format(description).unwrap();
+        let formatted = format_with(timestamp(), berlin(), Some(&date_format));
+        assert_eq!(formatted, "2023-05-04T13:37:00+02:00");
+        assert_eq!(OffsetDateTime::parse(&formatted, &date_format.0).unwrap(), timestamp());
+    }
+
+    #[test]
+    fn us_format_round_trip() {
+        let description = "[month]/[day]/[year] [hour repr:12]:[minute]:[second] [period]";
+        let date_format = parse_date_format(description).unwrap();
+        let formatted = format_with(timestamp(), TimeZone::Utc, Some(&date_format));
+        assert_eq!(formatted, "05/04/2023 11:37:00 AM");
+        assert_eq!(PrimitiveDateTime::parse(&formatted, &date_format.0).unwrap().assume_utc(), timestamp());
+    }
+
+    #[test]
+    fn invalid_date_format_is_rejected() {
+        assert!(parse_date_format("[year]-[month]-[invalid]").is_err());
+        assert!(parse_date_format("[year").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+// - STD
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// - internal
+use crate::addons::{unmount_mechanisms, UnmountMechanism};
+use crate::constants::*;
+use crate::pidfile;
+
+// - external
+use log::{info, warn, error, debug};
+
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const PROC_PATH: &str = "/proc";
+// the time to wait for the zffmount process to unmount the filesystem after it was signaled.
+const SIGNAL_UNMOUNT_TIMEOUT: Duration = Duration::from_secs(10);
+const SIGNAL_UNMOUNT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Unmounts the zffmount filesystem at the given mount point and exits the process with the appropriate exit status.
+/// If a PID file of a running zffmount process is given, the process will be signaled to unmount the filesystem gracefully
+/// (e.g. to detach the loop devices) first.
+/// If force is set, a busy filesystem will be detached lazily (the zffmount process will be signaled a second time to do so).
+pub(crate) fn umount(mount_point: &Path, lazy: bool, force: bool, pid_file: Option<&Path>) -> ! {
+    let mount_point = match mount_point.canonicalize() {
+        Ok(path) => path,
+        // a stale mount point (e.g. after the zffmount process crashed) could not be canonicalized.
+        Err(_) => mount_point.to_path_buf(),
+    };
+    match is_zff_mount(&mount_point) {
+        Ok(true) => (),
+        Ok(false) => {
+            error!("{} is not a zffmount mount point.", mount_point.display());
+            exit(EXIT_STATUS_NOT_MOUNTED);
+        },
+        Err(e) => {
+            error!("Could not read the mount table.");
+            debug!("{e}");
+            exit(EXIT_STATUS_ERROR);
+        }
+    }
+
+    if let Some(pid) = pid_file.and_then(pidfile::running_pid) {
+        // the second signal lets the zffmount process escalate to a lazy unmount.
+        if signal_unmount(pid, &mount_point) || (force && signal_unmount(pid, &mount_point)) {
+            info!("{} successfully unmounted.", mount_point.display());
+            exit(EXIT_STATUS_SUCCESS);
+        }
+        warn!("The zffmount process (PID {pid}) did not unmount the filesystem in time, trying to unmount it directly.");
+    }
+
+    if force {
+        log_processes_using(&mount_point);
+    }
+    let lazy = lazy || force;
+    let exit_status = unmount(&mount_point, lazy);
+    if exit_status == EXIT_STATUS_SUCCESS {
+        info!("{} successfully unmounted.", mount_point.display());
+    }
+    exit(exit_status);
+}
+
+/// Detaches the busy filesystem at the given mount point lazily. The processes which still hold references to the filesystem will be logged.
+/// Returns true, if the filesystem was detached.
+pub(crate) fn force_unmount(mount_point: &Path) -> bool {
+    log_processes_using(mount_point);
+    unmount(mount_point, true) == EXIT_STATUS_SUCCESS
+}
+
+// unmounts the filesystem by using the available unmount mechanisms (see addons::unmount_mechanisms) and returns the appropriate exit status.
+fn unmount(mount_point: &Path, lazy: bool) -> i32 {
+    let mechanisms = unmount_mechanisms();
+    if mechanisms.is_empty() {
+        error!("The filesystem can not be unmounted: zffmount does not run as root and no fusermount binary ({}) was found on the PATH. \
+            Install fuse3 or run the unmount as root.", FUSERMOUNT_BINARIES.join(", "));
+        return EXIT_STATUS_PERMISSION_DENIED;
+    }
+    for mechanism in mechanisms {
+        debug!("Unmounting {} by using {mechanism}.", mount_point.display());
+        match mechanism {
+            UnmountMechanism::Umount2 => match umount_syscall(mount_point, lazy) {
+                Ok(_) => return EXIT_STATUS_SUCCESS,
+                // e.g. root in a user namespace without CAP_SYS_ADMIN, the next mechanism will be tried.
+                Err(e) if e.raw_os_error() == Some(libc::EPERM) => debug!("{e}"),
+                Err(e) => return exit_status_of_error(&e),
+            },
+            UnmountMechanism::Fusermount(binary) => return umount_fusermount(&binary, mount_point, lazy),
+        }
+    }
+    error!("Permission denied.");
+    EXIT_STATUS_PERMISSION_DENIED
+}
+
+fn log_processes_using(mount_point: &Path) {
+    for (pid, name) in processes_using(mount_point) {
+        warn!("The process {name} (PID {pid}) still holds references to {}.", mount_point.display());
+    }
+}
+
+// scans the open files, the working directory, the root directory and the executable of all processes for paths below the mount point.
+// Reading the links does not access the (possibly hanging) filesystem itself.
+fn processes_using(mount_point: &Path) -> Vec<(u32, String)> {
+    let own_pid = std::process::id();
+    let mut processes = Vec::new();
+    let proc_entries = match fs::read_dir(PROC_PATH) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("Could not list the processes: {e}");
+            return processes;
+        }
+    };
+    for entry in proc_entries.flatten() {
+        let pid = match entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) {
+            Some(pid) if pid != own_pid => pid,
+            _ => continue,
+        };
+        let process_path = entry.path();
+        let mut links = vec![process_path.join("cwd"), process_path.join("root"), process_path.join("exe")];
+        // the file descriptors of processes of other users are not readable without privileges.
+        if let Ok(fds) = fs::read_dir(process_path.join("fd")) {
+            links.extend(fds.flatten().map(|fd| fd.path()));
+        }
+        if links.iter().filter_map(|link| fs::read_link(link).ok()).any(|target| target.starts_with(mount_point)) {
+            let name = fs::read_to_string(process_path.join("comm")).unwrap_or_default();
+            processes.push((pid, name.trim().to_string()));
+        }
+    }
+    processes
+}
+
+// checks the mount table for a FUSE mount with the zffmount filesystem name at the given mount point.
+fn is_zff_mount(mount_point: &Path) -> io::Result<bool> {
+    let mountinfo = fs::read_to_string(MOUNTINFO_PATH)?;
+    for line in mountinfo.lines() {
+        // format: <id> <parent id> <major:minor> <root> <mount point> <options> [<optional fields>...] - <fs type> <source> <super options>
+        let (mount_fields, fs_fields) = match line.split_once(" - ") {
+            Some(fields) => fields,
+            None => continue,
+        };
+        let entry_mount_point = match mount_fields.split(' ').nth(4) {
+            Some(path) => PathBuf::from(unescape_mountinfo(path)),
+            None => continue,
+        };
+        let mut fs_fields = fs_fields.split(' ');
+        let fs_type = fs_fields.next().unwrap_or_default();
+        let source = fs_fields.next().map(unescape_mountinfo).unwrap_or_default();
+        if entry_mount_point == mount_point && fs_type.starts_with("fuse") && source == ZFF_OVERLAY_FS_NAME {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// the mount table escapes spaces, tabs, newlines and backslashes as octal sequences (e.g. \040).
+fn unescape_mountinfo(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && index + 3 < bytes.len() && bytes[index+1..index+4].iter().all(|b| (b'0'..=b'7').contains(b)) {
+            let octal = std::str::from_utf8(&bytes[index+1..index+4]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(octal, 8) {
+                unescaped.push(byte);
+                index += 4;
+                continue;
+            }
+        }
+        unescaped.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+// sends SIGTERM to the given zffmount process and waits until the filesystem is unmounted.
+fn signal_unmount(pid: i32, mount_point: &Path) -> bool {
+    debug!("Sending SIGTERM to zffmount process {pid}.");
+    // Safety: kill has no memory safety requirements.
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        debug!("{}", io::Error::last_os_error());
+        return false;
+    }
+    let start = Instant::now();
+    while start.elapsed() < SIGNAL_UNMOUNT_TIMEOUT {
+        if matches!(is_zff_mount(mount_point), Ok(false)) {
+            return true;
+        }
+        thread::sleep(SIGNAL_UNMOUNT_POLL_INTERVAL);
+    }
+    false
+}
+
+fn umount_syscall(mount_point: &Path, lazy: bool) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use nix::mount::{umount2, MntFlags};
+        let flags = if lazy { MntFlags::MNT_DETACH } else { MntFlags::empty() };
+        umount2(mount_point, flags).map_err(|errno| io::Error::from_raw_os_error(errno as i32))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (mount_point, lazy);
+        Err(io::Error::from_raw_os_error(libc::EPERM))
+    }
+}
+
+// uses the given fusermount binary and returns the appropriate exit status.
+fn umount_fusermount(binary: &Path, mount_point: &Path, lazy: bool) -> i32 {
+    let mut command = Command::new(binary);
+    command.arg("-u");
+    if lazy {
+        command.arg("-z");
+    }
+    let output = match command.arg(mount_point).output() {
+        Ok(output) => output,
+        Err(e) => return exit_status_of_error(&e),
+    };
+    if output.status.success() {
+        return EXIT_STATUS_SUCCESS;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    error!("{}: {}", binary.display(), stderr.trim());
+    if stderr.contains("busy") {
+        EXIT_STATUS_BUSY
+    } else if stderr.contains("Permission denied") || stderr.contains("not permitted") {
+        EXIT_STATUS_PERMISSION_DENIED
+    } else {
+        EXIT_STATUS_ERROR
+    }
+}
+
+fn exit_status_of_error(e: &io::Error) -> i32 {
+    match e.raw_os_error() {
+        Some(libc::EBUSY) => {
+            error!("The filesystem is busy. Use --lazy or --force to detach it anyway.");
+            EXIT_STATUS_BUSY
+        },
+        Some(libc::EPERM) | Some(libc::EACCES) => {
+            error!("Permission denied.");
+            EXIT_STATUS_PERMISSION_DENIED
+        },
+        Some(libc::EINVAL) | Some(libc::ENOENT) => {
+            error!("The filesystem is not mounted.");
+            EXIT_STATUS_NOT_MOUNTED
+        },
+        _ => {
+            error!("An error occurred while trying to unmount the filesystem: {e}");
+            EXIT_STATUS_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_mountinfo_escapes() {
+        assert_eq!(unescape_mountinfo(r"/mnt/my\040evidence"), "/mnt/my evidence");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\011b"), "/mnt/a\tb");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\134b"), r"/mnt/a\b");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\012b\040c"), "/mnt/a\nb c");
+    }
+
+    #[test]
+    fn unescape_mountinfo_escape_at_the_end() {
+        assert_eq!(unescape_mountinfo(r"/mnt/evidence\040"), "/mnt/evidence ");
+        assert_eq!(unescape_mountinfo(r"\040"), " ");
+    }
+
+    #[test]
+    fn unescape_mountinfo_keeps_incomplete_escapes() {
+        assert_eq!(unescape_mountinfo(r"/mnt/a\b"), r"/mnt/a\b");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\"), r"/mnt/a\");
+        assert_eq!(unescape_mountinfo(r"\"), r"\");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\04"), r"/mnt/a\04");
+        assert_eq!(unescape_mountinfo(r"/mnt/a\089"), r"/mnt/a\089");
+        // \777 exceeds a single byte.
+        assert_eq!(unescape_mountinfo(r"/mnt/a\777"), r"/mnt/a\777");
+    }
+
+    #[test]
+    fn unescape_mountinfo_without_escapes() {
+        assert_eq!(unescape_mountinfo("/mnt/evidence"), "/mnt/evidence");
+        assert_eq!(unescape_mountinfo(""), "");
+    }
+}